@@ -0,0 +1,58 @@
+use std::{future::Future, time::Duration};
+
+use crate::{Error, Result};
+
+/// Await `future` for at most `duration`.
+///
+/// Returns [`Error::Timeout`] if `duration` elapses before `future` resolves, in which case
+/// `future` is dropped.
+///
+/// With `tokio` feature enabled, this must be called in the context of a tokio runtime with time
+/// enabled.
+pub(crate) async fn timeout<F, T>(future: F, duration: Duration) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    #[cfg(not(feature = "tokio"))]
+    {
+        use futures_lite::FutureExt;
+
+        future
+            .or(async {
+                async_io::Timer::after(duration).await;
+
+                Err(Error::Timeout)
+            })
+            .await
+    }
+
+    #[cfg(feature = "tokio")]
+    {
+        tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+}
+
+/// Await `future` for at most `duration`, if `duration` is specified.
+pub(crate) async fn maybe_timeout<F, T>(future: F, duration: Option<Duration>) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match duration {
+        Some(duration) => timeout(future, duration).await,
+        None => future.await,
+    }
+}
+
+/// Wait for `duration` to elapse.
+///
+/// With `tokio` feature enabled, this must be called in the context of a tokio runtime with time
+/// enabled.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(not(feature = "tokio"))]
+    async_io::Timer::after(duration).await;
+
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+}
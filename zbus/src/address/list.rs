@@ -0,0 +1,210 @@
+use std::{
+    env,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use super::{transport::Transport, Address};
+use crate::{Error, Result};
+
+/// A list of bus addresses, to be tried in order.
+///
+/// As per the D-Bus specification, multiple addresses can be given, separated by `;`. This is
+/// typically the case of the value of the `DBUS_SESSION_BUS_ADDRESS` environment variable on some
+/// systems, where fallback addresses are published. When connecting through an address list, each
+/// address is tried in turn until a connection is established and authenticated.
+///
+/// # Example
+///
+/// ```
+/// use zbus::address::AddressList;
+///
+/// let list: AddressList = "unix:path=/run/dbus/bus;tcp:host=localhost,port=4142"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(list.len(), 2);
+/// assert_eq!(
+///     list.iter().map(ToString::to_string).collect::<Vec<_>>(),
+///     ["unix:path=/run/dbus/bus", "tcp:host=localhost,port=4142"],
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressList {
+    addresses: Vec<Address>,
+}
+
+impl AddressList {
+    /// Get the addresses for the session socket respecting the `DBUS_SESSION_BUS_ADDRESS`
+    /// environment variable.
+    ///
+    /// See [`Address::session`] for the address used if the variable is not set.
+    pub fn session() -> Result<Self> {
+        match env::var("DBUS_SESSION_BUS_ADDRESS") {
+            Ok(val) => Self::from_str(&val),
+            _ => Address::session().map(Into::into),
+        }
+    }
+
+    /// Get the addresses for the system bus respecting the `DBUS_SYSTEM_BUS_ADDRESS` environment
+    /// variable.
+    ///
+    /// See [`Address::system`] for the address used if the variable is not set.
+    pub fn system() -> Result<Self> {
+        match env::var("DBUS_SYSTEM_BUS_ADDRESS") {
+            Ok(val) => Self::from_str(&val),
+            _ => Address::system().map(Into::into),
+        }
+    }
+
+    /// An iterator over the addresses, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Address> {
+        self.addresses.iter()
+    }
+
+    /// The number of addresses in the list.
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+}
+
+impl Display for AddressList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, address) in self.addresses.iter().enumerate() {
+            if i > 0 {
+                f.write_str(";")?;
+            }
+            address.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for AddressList {
+    type Err = Error;
+
+    /// Parse a `;`-separated list of D-Bus addresses.
+    ///
+    /// Empty entries are ignored but at least one address is required.
+    fn from_str(addresses: &str) -> Result<Self> {
+        let addresses = addresses
+            .split(';')
+            .filter(|a| !a.is_empty())
+            .map(Address::from_str)
+            .collect::<Result<Vec<_>>>()?;
+        if addresses.is_empty() {
+            return Err(Error::Address("Empty address list".to_owned()));
+        }
+
+        Ok(Self { addresses })
+    }
+}
+
+impl TryFrom<&str> for AddressList {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Self::from_str(value)
+    }
+}
+
+impl From<Address> for AddressList {
+    fn from(address: Address) -> Self {
+        Self {
+            addresses: vec![address],
+        }
+    }
+}
+
+impl From<Transport> for AddressList {
+    fn from(transport: Transport) -> Self {
+        Address::from(transport).into()
+    }
+}
+
+impl FromIterator<Address> for AddressList {
+    fn from_iter<T: IntoIterator<Item = Address>>(iter: T) -> Self {
+        Self {
+            addresses: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for AddressList {
+    type Item = Address;
+    type IntoIter = std::vec::IntoIter<Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.addresses.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AddressList {
+    type Item = &'a Address;
+    type IntoIter = std::slice::Iter<'a, Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::AddressList;
+    use crate::address::{
+        transport::{Tcp, Transport, Unix, UnixSocket},
+        Address,
+    };
+
+    #[test]
+    fn parse_address_lists() {
+        let list =
+            AddressList::from_str("unix:path=/tmp/dbus-foo;tcp:host=localhost,port=4142;").unwrap();
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            [
+                Address::from(Transport::Unix(Unix::new(UnixSocket::File(
+                    "/tmp/dbus-foo".into()
+                )))),
+                Address::from(Transport::Tcp(Tcp::new("localhost", 4142))),
+            ]
+        );
+
+        assert!(AddressList::from_str("").is_err());
+        assert!(AddressList::from_str(";").is_err());
+        assert!(AddressList::from_str("unix:path=/tmp/dbus-foo;foo").is_err());
+    }
+
+    #[test]
+    fn stringify_address_lists() {
+        let list: AddressList = [
+            Address::from(Transport::Unix(Unix::new(UnixSocket::File(
+                "/tmp/dbus-foo".into(),
+            )))),
+            Address::from(Transport::Tcp(Tcp::new("localhost", 4142))),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            list.to_string(),
+            "unix:path=/tmp/dbus-foo;tcp:host=localhost,port=4142"
+        );
+    }
+
+    #[test]
+    fn from_transport() {
+        let transport = Transport::Tcp(Tcp::new("localhost", 4142));
+        let list = AddressList::from(transport.clone());
+        assert_eq!(list.to_string(), "tcp:host=localhost,port=4142");
+
+        // Connection builders accept transports, as they did before address lists.
+        let _ = crate::connection::Builder::address(transport).unwrap();
+    }
+}
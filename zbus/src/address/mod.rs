@@ -11,6 +11,9 @@
 
 pub mod transport;
 
+mod list;
+pub use list::AddressList;
+
 use crate::{Error, Guid, OwnedGuid, Result};
 #[cfg(all(unix, not(target_os = "macos")))]
 use nix::unistd::Uid;
@@ -312,6 +315,11 @@ mod tests {
             Address::from_str("unix:tmpdir=/some/dir").unwrap(),
             Transport::Unix(Unix::new(UnixSocket::TmpDir("/some/dir".into()))).into(),
         );
+        assert_eq!(
+            Address::from_str("unix:runtime=yes").unwrap(),
+            Transport::Unix(Unix::new(UnixSocket::Runtime)).into(),
+        );
+        assert!(Address::from_str("unix:runtime=no").is_err());
     }
 
     #[test]
@@ -337,6 +345,10 @@ mod tests {
             .to_string(),
             "unix:tmpdir=/tmp/dbus-foo"
         );
+        assert_eq!(
+            Address::from(Transport::Unix(Unix::new(UnixSocket::Runtime))).to_string(),
+            "unix:runtime=yes"
+        );
         // FIXME: figure out how to handle abstract on Windows
         #[cfg(target_os = "linux")]
         assert_eq!(
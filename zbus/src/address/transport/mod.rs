@@ -33,6 +33,8 @@ use std::{
 };
 
 mod unix;
+#[cfg(all(unix, feature = "p2p"))]
+pub(crate) use unix::runtime_dir;
 pub use unix::{Unix, UnixSocket};
 mod tcp;
 pub use tcp::{Tcp, TcpTransportFamily};
@@ -104,8 +106,17 @@ impl Transport {
                     UnixSocket::Abstract(name) => {
                         SocketAddr::from_abstract_name(name.as_encoded_bytes())?
                     }
+                    #[cfg(unix)]
+                    UnixSocket::Runtime => SocketAddr::from_pathname(unix::runtime_socket_path(
+                        unix::runtime_dir().as_deref(),
+                    )?)?,
+                    #[cfg(windows)]
+                    UnixSocket::Runtime => {
+                        unix::runtime_socket_path(unix::runtime_dir().as_deref())?
+                    }
                     UnixSocket::Dir(_) | UnixSocket::TmpDir(_) => {
-                        // you can't connect to a unix:dir
+                        // you can't connect to a unix:dir or unix:tmpdir address, since the
+                        // socket name is only known to the server
                         return Err(Error::Unsupported);
                     }
                 };
@@ -3,7 +3,7 @@ use std::ffi::OsString;
 use std::{
     ffi::OsStr,
     fmt::{Display, Formatter},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 #[cfg(unix)]
@@ -36,18 +36,25 @@ impl Unix {
         let abs = opts.get("abstract");
         let dir = opts.get("dir");
         let tmpdir = opts.get("tmpdir");
-        let path = match (path, abs, dir, tmpdir) {
-            (Some(p), None, None, None) => UnixSocket::File(PathBuf::from(p)),
+        let runtime = opts.get("runtime");
+        let path = match (path, abs, dir, tmpdir, runtime) {
+            (Some(p), None, None, None, None) => UnixSocket::File(PathBuf::from(p)),
             #[cfg(target_os = "linux")]
-            (None, Some(p), None, None) => UnixSocket::Abstract(OsString::from(p)),
+            (None, Some(p), None, None, None) => UnixSocket::Abstract(OsString::from(p)),
             #[cfg(not(target_os = "linux"))]
-            (None, Some(_), None, None) => {
+            (None, Some(_), None, None, None) => {
                 return Err(crate::Error::Address(
                     "abstract sockets currently Linux-only".to_owned(),
                 ));
             }
-            (None, None, Some(p), None) => UnixSocket::Dir(PathBuf::from(p)),
-            (None, None, None, Some(p)) => UnixSocket::TmpDir(PathBuf::from(p)),
+            (None, None, Some(p), None, None) => UnixSocket::Dir(PathBuf::from(p)),
+            (None, None, None, Some(p), None) => UnixSocket::TmpDir(PathBuf::from(p)),
+            (None, None, None, None, Some(&"yes")) => UnixSocket::Runtime,
+            (None, None, None, None, Some(_)) => {
+                return Err(crate::Error::Address(
+                    "unix: `runtime` value must be `yes`".to_owned(),
+                ));
+            }
             _ => {
                 return Err(crate::Error::Address("unix: address is invalid".to_owned()));
             }
@@ -55,6 +62,43 @@ impl Unix {
 
         Ok(Self::new(path))
     }
+
+    /// The socket a server should listen on for this address.
+    ///
+    /// Listenable addresses are resolved to a concrete socket path: a unique one inside the
+    /// directory for `dir` and `tmpdir`, and the `bus` socket of the user `runtime_dir` (see
+    /// [`runtime_dir`]) for `runtime`.
+    #[cfg(feature = "p2p")]
+    pub(crate) fn listen_socket(&self, runtime_dir: Option<&Path>) -> crate::Result<UnixSocket> {
+        Ok(match &self.path {
+            UnixSocket::Dir(dir) | UnixSocket::TmpDir(dir) => {
+                // Same pattern as the reference implementation: `dbus-` + 10 random characters.
+                let name: String = std::iter::repeat_with(|| {
+                    const CHARS: &[u8] =
+                        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+                    CHARS[rand::random::<u32>() as usize % CHARS.len()] as char
+                })
+                .take(10)
+                .collect();
+
+                UnixSocket::File(dir.join(format!("dbus-{name}")))
+            }
+            UnixSocket::Runtime => UnixSocket::File(runtime_socket_path(runtime_dir)?),
+            path => path.clone(),
+        })
+    }
+}
+
+/// The user runtime directory, specified by the `XDG_RUNTIME_DIR` environment variable.
+pub(crate) fn runtime_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from)
+}
+
+/// The path of the `bus` socket in the user `runtime_dir`.
+pub(crate) fn runtime_socket_path(runtime_dir: Option<&Path>) -> crate::Result<PathBuf> {
+    runtime_dir.map(|dir| dir.join("bus")).ok_or_else(|| {
+        crate::Error::Address("`XDG_RUNTIME_DIR` environment variable is not set".to_owned())
+    })
 }
 
 impl Display for Unix {
@@ -86,6 +130,12 @@ pub enum UnixSocket {
     ///
     /// This address is mostly relevant to server (typically bus broker) implementations.
     TmpDir(PathBuf),
+    /// A listenable address, for which the server creates a socket file named `bus` in the
+    /// directory specified by the `XDG_RUNTIME_DIR` environment variable.
+    ///
+    /// This address is mostly relevant to server (typically bus broker) implementations. Since the
+    /// socket path is known in advance, clients can also connect to it.
+    Runtime,
 }
 
 impl Display for UnixSocket {
@@ -122,6 +172,7 @@ impl Display for UnixSocket {
                 f.write_str("tmpdir=")?;
                 fmt_unix_path(f, path.as_os_str())?;
             }
+            UnixSocket::Runtime => f.write_str("runtime=yes")?,
         }
 
         Ok(())
@@ -2,6 +2,7 @@
 use std::net::TcpStream;
 #[cfg(all(unix, not(feature = "tokio")))]
 use std::os::unix::net::UnixStream;
+use std::{future::Future, time::Duration};
 #[cfg(feature = "tokio")]
 use tokio::net::TcpStream;
 #[cfg(all(unix, feature = "tokio"))]
@@ -14,8 +15,15 @@ use zvariant::ObjectPath;
 #[cfg(feature = "p2p")]
 use crate::Guid;
 use crate::{
-    address::Address, blocking::Connection, conn::AuthMechanism, connection::socket::BoxedSplit,
-    names::WellKnownName, object_server::Interface, utils::block_on, Error, Result,
+    address::AddressList,
+    blocking::Connection,
+    conn::AuthMechanism,
+    connection::socket::BoxedSplit,
+    fdo,
+    names::WellKnownName,
+    object_server::{AccessRequest, CallLimits, Interceptor, Interface},
+    utils::block_on,
+    Error, Result,
 };
 
 /// A builder for [`zbus::blocking::Connection`].
@@ -36,10 +44,12 @@ impl<'a> Builder<'a> {
 
     /// Create a builder for a connection that will use the given [D-Bus bus address].
     ///
+    /// See [`crate::connection::Builder::address`] for details.
+    ///
     /// [D-Bus bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
     pub fn address<A>(address: A) -> Result<Self>
     where
-        A: TryInto<Address>,
+        A: TryInto<AddressList>,
         A::Error: Into<Error>,
     {
         crate::connection::Builder::address(address).map(Self)
@@ -92,6 +102,16 @@ impl<'a> Builder<'a> {
         Self(self.0.auth_mechanism(auth_mechanism))
     }
 
+    /// Specify a custom mechanism to use during authentication.
+    ///
+    /// See [`crate::connection::Builder::custom_auth_mechanism`] for details.
+    pub fn custom_auth_mechanism<M>(self, mechanism: M) -> Self
+    where
+        M: crate::connection::handshake::Mechanism + 'static,
+    {
+        Self(self.0.custom_auth_mechanism(mechanism))
+    }
+
     /// The to-be-created connection will be a peer-to-peer connection.
     ///
     /// This method is only available when the `p2p` feature is enabled.
@@ -119,6 +139,37 @@ impl<'a> Builder<'a> {
         self.0.server(guid).map(Self)
     }
 
+    /// Only accept clients running as one of the given users.
+    ///
+    /// See [`crate::connection::Builder::allowed_unix_user_ids`] for details.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    #[cfg(all(unix, feature = "p2p"))]
+    pub fn allowed_unix_user_ids<I>(self, uids: I) -> Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        Self(self.0.allowed_unix_user_ids(uids))
+    }
+
+    /// Set a callback to authorize clients.
+    ///
+    /// Unlike [`crate::connection::Builder::authorize`], which this method wraps, `authorize` is a
+    /// regular function here. Since it's called from the connection's executor, it should return
+    /// promptly.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    #[cfg(feature = "p2p")]
+    pub fn authorize<F>(self, authorize: F) -> Self
+    where
+        F: Fn(&crate::fdo::ConnectionCredentials) -> bool + Send + Sync + 'static,
+    {
+        Self(
+            self.0
+                .authorize(move |creds| std::future::ready(authorize(&creds))),
+        )
+    }
+
     /// Set the capacity of the main (unfiltered) queue.
     ///
     /// Since typically you'd want to set this at instantiation time, you can set it through the
@@ -142,6 +193,27 @@ impl<'a> Builder<'a> {
         Self(self.0.max_queued(max))
     }
 
+    /// Set the default timeout for method call replies.
+    ///
+    /// See [`zbus::connection::Builder::method_timeout`] for details.
+    pub fn method_timeout(self, timeout: Duration) -> Self {
+        Self(self.0.method_timeout(timeout))
+    }
+
+    /// Enable or disable automatic reconnection.
+    ///
+    /// See [`zbus::connection::Builder::auto_reconnect`] for details.
+    pub fn auto_reconnect(self, enabled: bool) -> Self {
+        Self(self.0.auto_reconnect(enabled))
+    }
+
+    /// Set the minimum and maximum delay between reconnection attempts.
+    ///
+    /// See [`zbus::connection::Builder::reconnect_delay`] for details.
+    pub fn reconnect_delay(self, min: Duration, max: Duration) -> Self {
+        Self(self.0.reconnect_delay(min, max))
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::blocking::ObjectServer::at`], except that it allows you to have
@@ -157,6 +229,52 @@ impl<'a> Builder<'a> {
         self.0.serve_at(path, iface).map(Self)
     }
 
+    /// Register a [`DynamicInterface`] to be served at a given path.
+    ///
+    /// See [`zbus::connection::Builder::serve_dynamic_at`] for details.
+    ///
+    /// [`DynamicInterface`]: crate::object_server::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn serve_dynamic_at<P>(
+        self,
+        path: P,
+        iface: crate::object_server::DynamicInterface,
+    ) -> Result<Self>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        self.0.serve_dynamic_at(path, iface).map(Self)
+    }
+
+    /// Set the access policy of the object server.
+    ///
+    /// See [`zbus::connection::Builder::access_policy`] for details.
+    pub fn access_policy<F, Fut>(self, policy: F) -> Self
+    where
+        F: Fn(AccessRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        Self(self.0.access_policy(policy))
+    }
+
+    /// Add an [`Interceptor`] to the object server.
+    ///
+    /// See [`zbus::connection::Builder::interceptor`] for details.
+    pub fn interceptor<I>(self, interceptor: I) -> Self
+    where
+        I: Interceptor,
+    {
+        Self(self.0.interceptor(interceptor))
+    }
+
+    /// Set the limits on the method calls the object server handles concurrently.
+    ///
+    /// See [`zbus::connection::Builder::call_limits`] for details.
+    pub fn call_limits(self, limits: CallLimits) -> Self {
+        Self(self.0.call_limits(limits))
+    }
+
     /// Register a well-known name for this connection on the bus.
     ///
     /// This is similar to [`zbus::blocking::Connection::request_name`], except the name is
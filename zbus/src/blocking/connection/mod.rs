@@ -2,16 +2,18 @@
 
 use enumflags2::BitFlags;
 use event_listener::EventListener;
-use std::{io, ops::Deref};
+use futures_lite::StreamExt;
+use std::{io, ops::Deref, time::Duration};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
 use zvariant::ObjectPath;
 
 use crate::{
     blocking::ObjectServer,
+    connection::{StateChange, StateChangeStream, Stats},
     fdo::{ConnectionCredentials, RequestNameFlags, RequestNameReply},
     message::Message,
     utils::block_on,
-    DBusError, Error, Result,
+    DBusError, Error, OwnedGuid, Result,
 };
 
 mod builder;
@@ -48,15 +50,33 @@ impl Connection {
     }
 
     /// The server's GUID.
+    ///
+    /// See [`crate::Connection::server_guid`] for details.
     pub fn server_guid(&self) -> &str {
         self.inner.server_guid()
     }
 
+    /// The GUID of the server the connection is currently connected to.
+    ///
+    /// See [`crate::Connection::current_server_guid`] for details.
+    pub fn current_server_guid(&self) -> OwnedGuid {
+        self.inner.current_server_guid()
+    }
+
     /// The unique name as assigned by the message bus or `None` if not a message bus connection.
+    ///
+    /// See [`crate::Connection::unique_name`] for details.
     pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
         self.inner.unique_name()
     }
 
+    /// The current unique name of the connection, if set/applicable.
+    ///
+    /// See [`crate::Connection::current_unique_name`] for details.
+    pub fn current_unique_name(&self) -> Option<OwnedUniqueName> {
+        self.inner.current_unique_name()
+    }
+
     /// Send `msg` to the peer.
     pub fn send(&self, msg: &Message) -> Result<()> {
         block_on(self.inner.send(msg))
@@ -93,6 +113,51 @@ impl Connection {
         )
     }
 
+    /// Send a method call, with the given reply timeout.
+    ///
+    /// This is the same as [`Connection::call_method`], except that `timeout` is used instead of
+    /// the default method timeout of the connection. If `timeout` is `None`, the call will wait
+    /// for the reply indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// If no reply is received within `timeout`, [`Error::Timeout`] is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn call_method_with_timeout<'d, 'p, 'i, 'm, D, P, I, M, B>(
+        &self,
+        destination: Option<D>,
+        path: P,
+        iface: Option<I>,
+        method_name: M,
+        timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<Message>
+    where
+        D: TryInto<BusName<'d>>,
+        P: TryInto<ObjectPath<'p>>,
+        I: TryInto<InterfaceName<'i>>,
+        M: TryInto<MemberName<'m>>,
+        D::Error: Into<Error>,
+        P::Error: Into<Error>,
+        I::Error: Into<Error>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        block_on(self.inner.call_method_with_timeout(
+            destination,
+            path,
+            iface,
+            method_name,
+            timeout,
+            body,
+        ))
+    }
+
+    /// The default timeout for method call replies.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner.method_timeout()
+    }
+
     /// Emit a signal.
     ///
     /// Create a signal message, and send it over the connection.
@@ -217,6 +282,13 @@ impl Connection {
         self.inner.is_bus()
     }
 
+    /// The identity the peer authenticated as.
+    ///
+    /// See [`crate::Connection::peer_auth_identity`] for details.
+    pub fn peer_auth_identity(&self) -> Option<&str> {
+        self.inner.peer_auth_identity()
+    }
+
     /// Get a reference to the associated [`ObjectServer`].
     ///
     /// The `ObjectServer` is created on-demand.
@@ -243,6 +315,21 @@ impl Connection {
         self.inner
     }
 
+    /// A snapshot of the statistics of the connection.
+    ///
+    /// Blocking version of [`crate::Connection::stats`]. See docs there for more details.
+    pub fn stats(&self) -> Stats {
+        block_on(self.inner.stats())
+    }
+
+    /// Receive changes of the connection state.
+    ///
+    /// Blocking version of [`crate::Connection::receive_state_changes`]. See docs there for more
+    /// details.
+    pub fn receive_state_changes(&self) -> StateChangeIterator {
+        StateChangeIterator(self.inner.receive_state_changes())
+    }
+
     /// Return a listener, notified on various connection activity.
     ///
     /// This function is meant for the caller to implement idle or timeout on inactivity.
@@ -257,7 +344,8 @@ impl Connection {
     ///
     /// # Caveats
     ///
-    /// Currently `unix_group_ids` and `linux_security_label` fields are not populated.
+    /// Currently `unix_group_ids`, `process_fd` and `linux_security_label` fields are only
+    /// populated on Linux.
     pub fn peer_credentials(&self) -> io::Result<ConnectionCredentials> {
         block_on(self.inner.peer_credentials())
     }
@@ -284,6 +372,20 @@ impl From<crate::Connection> for Connection {
     }
 }
 
+/// An [`std::iter::Iterator`] implementation that yields [`StateChange`]s.
+///
+/// Use [`Connection::receive_state_changes`] to create an instance of this type.
+#[derive(Debug)]
+pub struct StateChangeIterator(StateChangeStream);
+
+impl Iterator for StateChangeIterator {
+    type Item = StateChange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.0.next())
+    }
+}
+
 #[cfg(feature = "p2p")]
 #[cfg(all(test, unix))]
 mod tests {
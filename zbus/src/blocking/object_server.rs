@@ -1,9 +1,15 @@
 //! The object server API.
 
+use std::future::Future;
+
 use zvariant::ObjectPath;
 
 use crate::{
-    object_server::{Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter},
+    fdo,
+    object_server::{
+        AccessRequest, CallLimits, Interceptor, Interface, InterfaceDeref, InterfaceDerefMut,
+        PropertiesBatch, SignalEmitter, SubtreeHandler,
+    },
     utils::block_on,
     Error, Result,
 };
@@ -77,6 +83,13 @@ where
     pub fn signal_emitter(&self) -> &SignalEmitter<'static> {
         self.azync.signal_emitter()
     }
+
+    /// Start a batch of property changes on the interface's object.
+    ///
+    /// See [`PropertiesBatch`] for details.
+    pub fn batch(&self) -> PropertiesBatch<'static> {
+        self.azync.batch()
+    }
 }
 
 /// A blocking wrapper of [`crate::ObjectServer`].
@@ -174,6 +187,120 @@ impl ObjectServer {
         block_on(self.azync.remove::<I, P>(path))
     }
 
+    /// Register an object with several interfaces at once.
+    ///
+    /// See [`crate::ObjectServer::add_object`] for details.
+    pub fn add_object<'p, P>(&self, path: P) -> Result<ObjectBuilder<'_>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        self.azync.add_object(path).map(ObjectBuilder)
+    }
+
+    /// Unregister all the interfaces of the object at a given path, at once.
+    ///
+    /// See [`crate::ObjectServer::remove_object`] for details.
+    pub fn remove_object<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_object(path))
+    }
+
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// See [`crate::ObjectServer::at_dynamic`] for details.
+    ///
+    /// [`DynamicInterface`]: crate::object_server::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn at_dynamic<'p, P>(
+        &self,
+        path: P,
+        iface: crate::object_server::DynamicInterface,
+    ) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.at_dynamic(path, iface))
+    }
+
+    /// Unregister the [`DynamicInterface`] with the given name at a given path.
+    ///
+    /// See [`crate::ObjectServer::remove_dynamic`] for details.
+    ///
+    /// [`DynamicInterface`]: crate::object_server::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn remove_dynamic<'p, 'i, P, I>(&self, path: P, name: I) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        I: TryInto<crate::names::InterfaceName<'i>>,
+        I::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_dynamic(path, name))
+    }
+
+    /// Set the access policy of the server.
+    ///
+    /// See [`crate::ObjectServer::set_access_policy`] for details.
+    pub fn set_access_policy<F, Fut>(&self, policy: F)
+    where
+        F: Fn(AccessRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        self.azync.set_access_policy(policy)
+    }
+
+    /// Remove the access policy of the server, if any.
+    ///
+    /// See [`crate::ObjectServer::remove_access_policy`] for details.
+    pub fn remove_access_policy(&self) {
+        self.azync.remove_access_policy()
+    }
+
+    /// Set the limits on the method calls the server handles concurrently.
+    ///
+    /// See [`crate::ObjectServer::set_call_limits`] for details.
+    pub fn set_call_limits(&self, limits: CallLimits) {
+        self.azync.set_call_limits(limits)
+    }
+
+    /// Add an [`Interceptor`] to the server.
+    ///
+    /// See [`crate::ObjectServer::add_interceptor`] for details.
+    pub fn add_interceptor<I>(&self, interceptor: I)
+    where
+        I: Interceptor,
+    {
+        self.azync.add_interceptor(interceptor)
+    }
+
+    /// Register a [`SubtreeHandler`] serving the objects under the given path.
+    ///
+    /// See [`crate::ObjectServer::subtree_at`] for details.
+    pub fn subtree_at<'p, P, H>(&self, path: P, handler: H) -> Result<bool>
+    where
+        H: SubtreeHandler,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.subtree_at(path, handler))
+    }
+
+    /// Unregister the [`SubtreeHandler`] at the given path.
+    ///
+    /// See [`crate::ObjectServer::remove_subtree`] for details.
+    pub fn remove_subtree<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_subtree(path))
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -235,6 +362,40 @@ impl ObjectServer {
     }
 }
 
+/// A blocking wrapper of [`crate::object_server::ObjectBuilder`].
+#[derive(Debug)]
+#[must_use]
+pub struct ObjectBuilder<'s>(crate::object_server::ObjectBuilder<'s>);
+
+impl ObjectBuilder<'_> {
+    /// Add an interface to the object.
+    ///
+    /// See [`crate::object_server::ObjectBuilder::with`] for details.
+    pub fn with<I>(self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        Self(self.0.with(iface))
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    ///
+    /// See [`crate::object_server::ObjectBuilder::with_dynamic`] for details.
+    ///
+    /// [`DynamicInterface`]: crate::object_server::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn with_dynamic(self, iface: crate::object_server::DynamicInterface) -> Self {
+        Self(self.0.with_dynamic(iface))
+    }
+
+    /// Register the object.
+    ///
+    /// See [`crate::object_server::ObjectBuilder::commit`] for details.
+    pub fn commit(self) -> Result<()> {
+        block_on(self.0.commit())
+    }
+}
+
 impl From<crate::ObjectServer> for ObjectServer {
     fn from(azync: crate::ObjectServer) -> Self {
         Self { azync }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use zbus_names::{BusName, InterfaceName};
 use zvariant::ObjectPath;
 
@@ -49,6 +51,14 @@ impl<'a, T> Builder<'a, T> {
         Self(self.0.uncached_properties(properties))
     }
 
+    /// Set the default timeout for method call replies.
+    ///
+    /// See [`crate::proxy::Builder::method_timeout`] for details.
+    #[must_use]
+    pub fn method_timeout(self, timeout: Duration) -> Self {
+        Self(self.0.method_timeout(timeout))
+    }
+
     /// Build a proxy from the builder.
     ///
     /// # Panics
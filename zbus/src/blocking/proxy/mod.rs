@@ -2,7 +2,7 @@
 
 use enumflags2::BitFlags;
 use futures_lite::StreamExt;
-use std::{fmt, ops::Deref};
+use std::{fmt, ops::Deref, time::Duration};
 use zbus_names::{BusName, InterfaceName, MemberName, UniqueName};
 use zvariant::{ObjectPath, OwnedValue, Value};
 
@@ -154,6 +154,13 @@ impl<'a> Proxy<'a> {
         self.inner().interface()
     }
 
+    /// The default timeout for method call replies.
+    ///
+    /// See [`crate::Proxy::method_timeout`] for details.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner().method_timeout()
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](https://docs.rs/zbus_xml) crate for parsing the result.
@@ -239,6 +246,32 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call(method_name, body))
     }
 
+    /// Call a method and return the reply body, with the given reply timeout.
+    ///
+    /// This is the same as [`call`], except that `timeout` is used instead of the default method
+    /// timeout of the proxy. If `timeout` is `None`, the call will wait for the reply
+    /// indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// If no reply is received within `timeout`, [`Error::Timeout`] is returned.
+    ///
+    /// [`call`]: struct.Proxy.html#method.call
+    pub fn call_with_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<R>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        block_on(self.inner().call_with_timeout(method_name, timeout, body))
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -7,6 +7,9 @@ use std::net::TcpStream;
 use std::os::unix::net::UnixStream;
 use std::{
     collections::{HashMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::Duration,
     vec,
 };
 #[cfg(feature = "tokio")]
@@ -20,17 +23,24 @@ use uds_windows::UnixStream;
 #[cfg(all(feature = "vsock", not(feature = "tokio")))]
 use vsock::VsockStream;
 
+use tracing::debug;
 use zvariant::ObjectPath;
 
 use crate::{
-    address::{self, Address},
+    address::{self, AddressList},
+    fdo,
     names::{InterfaceName, WellKnownName},
-    object_server::{ArcInterface, Interface},
+    object_server::{
+        AccessPolicy, AccessRequest, ArcInterface, CallLimits, Interceptor, Interface,
+    },
     Connection, Error, Executor, Guid, OwnedGuid, Result,
 };
 
+#[cfg(feature = "p2p")]
+use super::handshake::{Authorization, Server};
 use super::{
-    handshake::{AuthMechanism, Authenticated},
+    handshake::{AuthMechanism, Authenticated, Client, Handshake, Mechanism},
+    reconnect::{self, Reconnector},
     socket::{BoxedSplit, ReadHalf, Split, WriteHalf},
 };
 
@@ -46,7 +56,7 @@ enum Target {
         feature = "tokio-vsock"
     ))]
     VsockStream(VsockStream),
-    Address(Address),
+    Address(AddressList),
     Socket(Split<Box<dyn ReadHalf>, Box<dyn WriteHalf>>),
     AuthenticatedSocket(Split<Box<dyn ReadHalf>, Box<dyn WriteHalf>>),
 }
@@ -65,22 +75,33 @@ pub struct Builder<'a> {
     p2p: bool,
     internal_executor: bool,
     interfaces: Interfaces<'a>,
+    access_policy: Option<AccessPolicy>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    call_limits: Option<CallLimits>,
     names: HashSet<WellKnownName<'a>>,
     auth_mechanism: Option<AuthMechanism>,
+    custom_auth_mechanism: Option<Arc<dyn Mechanism>>,
+    #[cfg(feature = "p2p")]
+    authorization: Authorization,
+    #[cfg(feature = "p2p")]
+    monitoring: Option<crate::fdo::Monitoring>,
     #[cfg(feature = "bus-impl")]
     unique_name: Option<crate::names::UniqueName<'a>>,
     impersonate_user_id: Option<usize>,
+    method_timeout: Option<Duration>,
+    auto_reconnect: bool,
+    reconnect_delay: (Duration, Duration),
 }
 
 impl<'a> Builder<'a> {
     /// Create a builder for the session/user message bus connection.
     pub fn session() -> Result<Self> {
-        Ok(Self::new(Target::Address(Address::session()?)))
+        Ok(Self::new(Target::Address(AddressList::session()?)))
     }
 
     /// Create a builder for the system-wide message bus connection.
     pub fn system() -> Result<Self> {
-        Ok(Self::new(Target::Address(Address::system()?)))
+        Ok(Self::new(Target::Address(AddressList::system()?)))
     }
 
     /// Create a builder for a connection that will use the given [D-Bus bus address].
@@ -113,10 +134,15 @@ impl<'a> Builder<'a> {
     /// **Note:** The IBus address is different for each session. You can find the address for your
     /// current session using `ibus address` command.
     ///
+    /// `address` can also be a `;`-separated list of addresses (see [`AddressList`]), in which case
+    /// each address is tried in turn until a connection can be established and authenticated. If
+    /// none of them can be connected to, the returned [`Error::Address`] lists the error for each
+    /// address.
+    ///
     /// [D-Bus bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
     pub fn address<A>(address: A) -> Result<Self>
     where
-        A: TryInto<Address>,
+        A: TryInto<AddressList>,
         A::Error: Into<Error>,
     {
         Ok(Self::new(Target::Address(
@@ -189,6 +215,21 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Specify a custom mechanism to use during authentication.
+    ///
+    /// This takes precedence over [`Builder::auth_mechanism`]. On the server side of a peer-to-peer
+    /// connection, the custom mechanism is then the only one accepted from the client.
+    ///
+    /// See [`Mechanism`] for details and an example.
+    pub fn custom_auth_mechanism<M>(mut self, mechanism: M) -> Self
+    where
+        M: Mechanism + 'static,
+    {
+        self.custom_auth_mechanism = Some(Arc::new(mechanism));
+
+        self
+    }
+
     /// Lets you impersonate a user
     pub fn impersonate_user_id(mut self, id: usize) -> Self {
         self.impersonate_user_id = Some(id);
@@ -227,6 +268,76 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Only accept clients running as one of the given users.
+    ///
+    /// This only applies to server connections (see [`Builder::server`]). Once the client is
+    /// authenticated, its user ID, as reported by the socket credentials, is checked against
+    /// `uids`. Clients not in the list, or for which the user ID can't be determined (e.g. on TCP
+    /// sockets), are rejected.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    #[cfg(all(unix, feature = "p2p"))]
+    pub fn allowed_unix_user_ids<I>(mut self, uids: I) -> Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        self.authorization
+            .set_unix_user_ids(uids.into_iter().collect());
+
+        self
+    }
+
+    /// Set a callback to authorize clients.
+    ///
+    /// This only applies to server connections (see [`Builder::server`]). Once the client is
+    /// authenticated, and before the handshake is completed, `authorize` is called with the
+    /// credentials of the client. If it returns `false`, the client is rejected. Which credentials
+    /// are available depends on the platform and the socket type.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use zbus::connection::Builder;
+    /// # async fn example(server_builder: Builder<'_>) -> zbus::Result<()> {
+    /// let conn = server_builder
+    ///     // Only accept clients from our own process.
+    ///     .authorize(|creds| async move { creds.process_id() == Some(std::process::id()) })
+    ///     .build()
+    ///     .await?;
+    /// #     drop(conn);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "p2p")]
+    pub fn authorize<F, Fut>(mut self, authorize: F) -> Self
+    where
+        F: Fn(Arc<crate::fdo::ConnectionCredentials>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.authorization.set_callback(authorize);
+
+        self
+    }
+
+    /// Let the peer become a monitor of the connections of a `monitoring` hub.
+    ///
+    /// The [`org.freedesktop.DBus.Monitoring`][fdo::Monitoring] interface is served at
+    /// `/org/freedesktop/DBus`, and the messages of the connection are forwarded to the monitors of
+    /// the hub. Pass the same hub (or a clone) to all the server connections to monitor together.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    ///
+    /// [fdo::Monitoring]: crate::fdo::Monitoring
+    #[cfg(feature = "p2p")]
+    pub fn monitoring(mut self, monitoring: crate::fdo::Monitoring) -> Result<Self> {
+        self = self.serve_at("/org/freedesktop/DBus", monitoring.clone())?;
+        self.monitoring = Some(monitoring);
+
+        Ok(self)
+    }
+
     /// Set the capacity of the main (unfiltered) queue.
     ///
     /// Since typically you'd want to set this at instantiation time, you can set it through the
@@ -258,6 +369,79 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Set the default timeout for method call replies.
+    ///
+    /// By default, method calls wait indefinitely for a reply. If a timeout is set, method calls
+    /// made through the connection (and proxies created for it) fail with [`Error::Timeout`] if no
+    /// reply is received in time. The timeout can be overridden for individual calls through
+    /// [`Connection::call_method_with_timeout`] and per-proxy through
+    /// [`crate::proxy::Builder::method_timeout`].
+    ///
+    /// **Note:** With `tokio` feature enabled, the tokio runtime must have time enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # use zbus::connection::Builder;
+    /// # use zbus::block_on;
+    /// use std::time::Duration;
+    /// #
+    /// # block_on(async {
+    /// let conn = Builder::session()?
+    ///     .method_timeout(Duration::from_secs(10))
+    ///     .build()
+    ///     .await?;
+    /// assert_eq!(conn.method_timeout(), Some(Duration::from_secs(10)));
+    ///
+    /// #     Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// #
+    /// // Do something useful with `conn`..
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.method_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Enable or disable automatic reconnection.
+    ///
+    /// When enabled, the connection is re-established each time it's lost (e.g. if the bus is
+    /// restarted), with a delay between attempts (see [`Builder::reconnect_delay`]). Once
+    /// reconnected, the well-known names owned by the connection are requested again and the match
+    /// rules of existing signal streams are added again, so the streams and proxies created from
+    /// the connection keep working. Method calls pending at the time of disconnection fail.
+    ///
+    /// Use [`Connection::receive_state_changes`] to be notified of disconnections and
+    /// reconnections. Note that the bus assigns a new unique name on each reconnection.
+    ///
+    /// Disabled by default.
+    ///
+    /// # Errors
+    ///
+    /// Only connections to a bus, created from an address (e.g. through [`Builder::session`] or
+    /// [`Builder::address`]) can be re-established. [`Builder::build`] returns
+    /// [`Error::Unsupported`] for other connections.
+    pub fn auto_reconnect(mut self, enabled: bool) -> Self {
+        self.auto_reconnect = enabled;
+
+        self
+    }
+
+    /// Set the minimum and maximum delay between reconnection attempts.
+    ///
+    /// The first attempt is made after `min` and the delay doubles after each failed attempt, up to
+    /// `max`. Only relevant if [`Builder::auto_reconnect`] is enabled.
+    ///
+    /// The default is 100 milliseconds to 30 seconds.
+    pub fn reconnect_delay(mut self, min: Duration, max: Duration) -> Self {
+        self.reconnect_delay = (min, max.max(min));
+
+        self
+    }
+
     /// Enable or disable the internal executor thread.
     ///
     /// The thread is enabled by default.
@@ -290,6 +474,70 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Register a [`DynamicInterface`] to be served at a given path.
+    ///
+    /// This is the equivalent of [`Builder::serve_at`] for interfaces defined at runtime.
+    ///
+    /// This method is only available when the `dynamic-interface` feature is enabled.
+    ///
+    /// [`DynamicInterface`]: crate::object_server::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn serve_dynamic_at<P>(
+        mut self,
+        path: P,
+        iface: crate::object_server::DynamicInterface,
+    ) -> Result<Self>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let entry = self.interfaces.entry(path).or_default();
+        let (name, iface) = iface.into_arc_interface();
+        entry.insert(name, iface);
+        Ok(self)
+    }
+
+    /// Set the access policy of the object server.
+    ///
+    /// This is similar to [`zbus::ObjectServer::set_access_policy`], except that the policy is in
+    /// effect right from the start, before any interface registered through
+    /// [`Builder::serve_at`] is served.
+    pub fn access_policy<F, Fut>(mut self, policy: F) -> Self
+    where
+        F: Fn(AccessRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        self.access_policy = Some(AccessPolicy::new(policy));
+
+        self
+    }
+
+    /// Add an [`Interceptor`] to the object server.
+    ///
+    /// This is similar to [`zbus::ObjectServer::add_interceptor`], except that the interceptor is
+    /// in effect right from the start, before any interface registered through
+    /// [`Builder::serve_at`] is served.
+    pub fn interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: Interceptor,
+    {
+        self.interceptors.push(Arc::new(interceptor));
+
+        self
+    }
+
+    /// Set the limits on the method calls the object server handles concurrently.
+    ///
+    /// This is similar to [`zbus::ObjectServer::set_call_limits`], except that the limits are in
+    /// effect right from the start, before any interface registered through
+    /// [`Builder::serve_at`] is served.
+    pub fn call_limits(mut self, limits: CallLimits) -> Self {
+        self.call_limits = Some(limits);
+
+        self
+    }
+
     /// Register a well-known name for this connection on the bus.
     ///
     /// This is similar to [`zbus::Connection::request_name`], except the name is requested as part
@@ -356,6 +604,18 @@ impl<'a> Builder<'a> {
         #[cfg(not(feature = "p2p"))]
         let is_bus_conn = true;
 
+        let reconnector = if self.auto_reconnect {
+            let template = self
+                .reconnection()
+                .filter(|_| is_bus_conn)
+                .ok_or(Error::Unsupported)?;
+            let (min_delay, max_delay) = self.reconnect_delay;
+
+            Some(Reconnector::new(template, min_delay, max_delay))
+        } else {
+            None
+        };
+
         let mut auth = self.connect(is_bus_conn).await?;
 
         // SAFETY: `Authenticated` is always built with these fields set to `Some`.
@@ -364,11 +624,35 @@ impl<'a> Builder<'a> {
         #[cfg(unix)]
         let already_received_fds = auth.already_received_fds.drain(..).collect();
 
-        let mut conn = Connection::new(auth, is_bus_conn, executor).await?;
+        let mut conn = Connection::new(
+            auth,
+            is_bus_conn,
+            executor,
+            self.method_timeout,
+            reconnector.is_some(),
+        )
+        .await?;
         conn.set_max_queued(self.max_queued.unwrap_or(DEFAULT_MAX_QUEUED));
+        #[cfg(feature = "p2p")]
+        if let Some(monitoring) = &self.monitoring {
+            monitoring.observe(&conn);
+        }
 
-        if !self.interfaces.is_empty() {
+        if !self.interfaces.is_empty()
+            || self.access_policy.is_some()
+            || !self.interceptors.is_empty()
+            || self.call_limits.is_some()
+        {
             let object_server = conn.ensure_object_server(false);
+            if let Some(policy) = self.access_policy {
+                object_server.set_access_policy_inner(policy);
+            }
+            for interceptor in self.interceptors {
+                object_server.add_arc_interceptor(interceptor);
+            }
+            if let Some(limits) = self.call_limits {
+                object_server.set_call_limits(limits);
+            }
             for (path, interfaces) in self.interfaces {
                 for (name, iface) in interfaces {
                     let added = object_server
@@ -393,6 +677,7 @@ impl<'a> Builder<'a> {
             already_received_bytes,
             #[cfg(unix)]
             already_received_fds,
+            reconnector,
         );
 
         for name in self.names {
@@ -405,25 +690,131 @@ impl<'a> Builder<'a> {
     fn new(target: Target) -> Self {
         Self {
             target: Some(target),
+            ..Self::empty()
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            target: None,
             #[cfg(feature = "p2p")]
             p2p: false,
             max_queued: None,
             guid: None,
             internal_executor: true,
             interfaces: HashMap::new(),
+            access_policy: None,
+            interceptors: vec![],
+            call_limits: None,
             names: HashSet::new(),
             auth_mechanism: None,
+            custom_auth_mechanism: None,
+            #[cfg(feature = "p2p")]
+            authorization: Authorization::default(),
+            #[cfg(feature = "p2p")]
+            monitoring: None,
             #[cfg(feature = "bus-impl")]
             unique_name: None,
             impersonate_user_id: None,
+            method_timeout: None,
+            auto_reconnect: false,
+            reconnect_delay: (reconnect::DEFAULT_MIN_DELAY, reconnect::DEFAULT_MAX_DELAY),
+        }
+    }
+
+    /// A builder without a target, to be used as a template for server connections of a
+    /// [`super::Listener`].
+    #[cfg(feature = "p2p")]
+    pub(crate) fn listener_template() -> Self {
+        Self {
+            p2p: true,
+            ..Self::empty()
+        }
+    }
+
+    /// Create a builder for a server connection on `socket`, with the same configuration as this
+    /// template.
+    #[cfg(feature = "p2p")]
+    pub(crate) fn for_socket(&self, socket: BoxedSplit) -> Self {
+        // No `..` here, so the configuration added later doesn't go unnoticed.
+        Self {
+            target: Some(Target::Socket(socket)),
+            max_queued: self.max_queued,
+            guid: self.guid.clone(),
+            p2p: self.p2p,
+            internal_executor: self.internal_executor,
+            interfaces: self.interfaces.clone(),
+            access_policy: self.access_policy.clone(),
+            interceptors: self.interceptors.clone(),
+            call_limits: self.call_limits,
+            names: self.names.clone(),
+            auth_mechanism: self.auth_mechanism,
+            custom_auth_mechanism: self.custom_auth_mechanism.clone(),
+            authorization: self.authorization.clone(),
+            monitoring: self.monitoring.clone(),
+            #[cfg(feature = "bus-impl")]
+            unique_name: self.unique_name.clone(),
+            impersonate_user_id: self.impersonate_user_id,
+            method_timeout: self.method_timeout,
+            auto_reconnect: self.auto_reconnect,
+            reconnect_delay: self.reconnect_delay,
+        }
+    }
+
+    /// A builder to establish the same connection again, if it was created from an address.
+    pub(super) fn reconnection(&self) -> Option<Builder<'static>> {
+        match &self.target {
+            Some(Target::Address(addresses)) => Some(Builder {
+                target: Some(Target::Address(addresses.clone())),
+                auth_mechanism: self.auth_mechanism,
+                custom_auth_mechanism: self.custom_auth_mechanism.clone(),
+                impersonate_user_id: self.impersonate_user_id,
+                ..Builder::empty()
+            }),
+            _ => None,
+        }
+    }
+
+    pub(super) async fn connect(&mut self, is_bus_conn: bool) -> Result<Authenticated> {
+        let addresses = match self.target.take() {
+            Some(Target::Address(addresses)) if addresses.len() != 1 => addresses,
+            target => {
+                // Keep the original error if there is no fallback.
+                self.target = target;
+
+                return self.connect_target(is_bus_conn).await;
+            }
+        };
+
+        // An address is only skipped if connecting to it or authenticating fails.
+        let mut errors = vec![];
+        for address in addresses {
+            let address_str = address.to_string();
+            self.target = Some(Target::Address(address.into()));
+            match self.connect_target(is_bus_conn).await {
+                Ok(auth) => return Ok(auth),
+                Err(e) => {
+                    debug!("Failed to connect to `{address_str}`: {e}");
+                    errors.push(format!("`{address_str}`: {e}"));
+                }
+            }
         }
+
+        if errors.is_empty() {
+            return Err(Error::Address("Empty address list".to_owned()));
+        }
+
+        Err(Error::Address(format!(
+            "Failed to connect to any address: {}",
+            errors.join(", ")
+        )))
     }
 
-    async fn connect(&mut self, is_bus_conn: bool) -> Result<Authenticated> {
+    async fn connect_target(&mut self, is_bus_conn: bool) -> Result<Authenticated> {
         #[cfg(not(feature = "bus-impl"))]
         let unique_name = None;
         #[cfg(feature = "bus-impl")]
-        let unique_name = self.unique_name.take().map(Into::into);
+        let unique_name = self.unique_name.clone().map(Into::into);
 
         #[allow(unused_mut)]
         let (mut stream, server_guid, authenticated) = self.target_connect().await?;
@@ -440,20 +831,16 @@ impl<'a> Builder<'a> {
                 unique_name,
                 #[cfg(unix)]
                 already_received_fds: vec![],
+                #[cfg(feature = "p2p")]
+                peer_auth_identity: None,
             })
         } else {
             #[cfg(feature = "p2p")]
-            match self.guid.take() {
+            match self.guid.clone() {
                 None => {
                     // SASL Handshake
-                    Authenticated::client(
-                        stream,
-                        server_guid,
-                        self.auth_mechanism,
-                        is_bus_conn,
-                        self.impersonate_user_id,
-                    )
-                    .await
+                    self.client_handshake(stream, server_guid, is_bus_conn)
+                        .await
                 }
                 Some(guid) => {
                     if !self.p2p {
@@ -464,9 +851,9 @@ impl<'a> Builder<'a> {
                     #[cfg(unix)]
                     let client_uid = creds.unix_user_id();
                     #[cfg(windows)]
-                    let client_sid = creds.into_windows_sid();
+                    let client_sid = creds.windows_sid().cloned();
 
-                    Authenticated::server(
+                    let mut server = Server::new(
                         stream,
                         guid.to_owned().into(),
                         #[cfg(unix)]
@@ -475,23 +862,43 @@ impl<'a> Builder<'a> {
                         client_sid,
                         self.auth_mechanism,
                         unique_name,
-                    )
-                    .await
+                    )?;
+                    server.set_peer_credentials(creds);
+                    server.set_authorization(self.authorization.clone());
+                    if let Some(mechanism) = &self.custom_auth_mechanism {
+                        server.set_custom_mechanism(mechanism.clone())?;
+                    }
+
+                    server.perform().await
                 }
             }
 
             #[cfg(not(feature = "p2p"))]
-            Authenticated::client(
-                stream,
-                server_guid,
-                self.auth_mechanism,
-                is_bus_conn,
-                self.impersonate_user_id,
-            )
-            .await
+            self.client_handshake(stream, server_guid, is_bus_conn)
+                .await
         }
     }
 
+    async fn client_handshake(
+        &self,
+        stream: BoxedSplit,
+        server_guid: Option<OwnedGuid>,
+        is_bus_conn: bool,
+    ) -> Result<Authenticated> {
+        let mut client = Client::new(
+            stream,
+            self.auth_mechanism,
+            server_guid,
+            is_bus_conn,
+            self.impersonate_user_id,
+        );
+        if let Some(mechanism) = &self.custom_auth_mechanism {
+            client.set_custom_mechanism(mechanism.clone())?;
+        }
+
+        client.perform().await
+    }
+
     async fn target_connect(&mut self) -> Result<(BoxedSplit, Option<OwnedGuid>, bool)> {
         let mut authenticated = false;
         let mut guid = None;
@@ -510,9 +917,15 @@ impl<'a> Builder<'a> {
             Target::VsockStream(stream) => Async::new(stream)?.into(),
             #[cfg(feature = "tokio-vsock")]
             Target::VsockStream(stream) => stream.into(),
-            Target::Address(address) => {
+            Target::Address(addresses) => {
+                // Lists of several addresses are handled by `connect`.
+                let address = addresses
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| Error::Address("Empty address list".to_owned()))?;
                 guid = address.guid().map(|g| g.to_owned().into());
-                match address.connect().await? {
+                let stream = address.connect().await?;
+                match stream {
                     #[cfg(any(unix, not(feature = "tokio")))]
                     address::transport::Stream::Unix(stream) => stream.into(),
                     #[cfg(unix)]
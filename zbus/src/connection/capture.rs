@@ -0,0 +1,747 @@
+//! Capture and replay of D-Bus traffic, in the pcap formats.
+//!
+//! [`Connection::capture`] writes all the messages sent and received by a connection to a
+//! [pcapng] file, with the `LINKTYPE_DBUS` link type (231), the same that `dbus-monitor --pcap`
+//! uses. Each message is recorded with the time it was sent or received and its [`Direction`], so
+//! the captures of peer-to-peer connections, invisible to `dbus-monitor`, can be inspected with
+//! Wireshark as well.
+//!
+//! [`Reader`] replays a capture into [`Message`]s. It reads both the pcapng captures written by
+//! zbus and the classic pcap captures written by `dbus-monitor --pcap`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use zbus::{connection::capture::Reader, Connection};
+//! # use zbus::block_on;
+//!
+//! # block_on(async {
+//! let conn = Connection::session().await?;
+//! conn.capture(File::create("session.pcapng")?)?;
+//! conn.call_method(
+//!     Some("org.freedesktop.DBus"),
+//!     "/org/freedesktop/DBus",
+//!     Some("org.freedesktop.DBus.Peer"),
+//!     "Ping",
+//!     &(),
+//! )
+//! .await?;
+//!
+//! for record in Reader::new(File::open("session.pcapng")?)? {
+//!     let record = record?;
+//!     println!("{:?} {:?}: {}", record.timestamp(), record.direction(), record.message());
+//! }
+//! #     Ok::<(), zbus::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! [`Connection::capture`]: super::Connection::capture
+//! [pcapng]: https://www.ietf.org/archive/id/draft-ietf-opsawg-pcapng-03.html
+
+use std::{
+    io::{self, Read, Write},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tracing::warn;
+use zvariant::{
+    serialized::{self, Context},
+    Endian,
+};
+
+use super::{Direction, Tap};
+use crate::{
+    message::header::{EndianSig, MAX_MESSAGE_SIZE},
+    Error, Message, Result,
+};
+
+/// The link type of D-Bus messages.
+const LINKTYPE_DBUS: u16 = 231;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const ENHANCED_PACKET_BLOCK: u32 = 6;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const OPT_END: u16 = 0;
+const OPT_EPB_FLAGS: u16 = 2;
+const OPT_IF_TSRESOL: u16 = 9;
+const EPB_FLAGS_INBOUND: u32 = 1;
+const EPB_FLAGS_OUTBOUND: u32 = 2;
+
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+
+/// The maximum length of the blocks read, leaving room for the packet block fields and options
+/// around the largest messages. Anything larger can't be a D-Bus message and isn't allocated.
+const MAX_BLOCK_LEN: usize = MAX_MESSAGE_SIZE + 1024;
+
+/// Writes the messages of a connection to a pcapng stream.
+///
+/// The messages are encoded as they're observed, and written by a dedicated thread, so the
+/// connection is never held up by `writer`.
+pub(crate) struct Writer {
+    // `None` once writing failed, to not queue messages for nothing.
+    blocks: Mutex<Option<Sender<Vec<u8>>>>,
+}
+
+impl Writer {
+    /// Write the pcapng headers to `writer`, and start the thread writing the messages.
+    pub(crate) fn new<W>(mut writer: W) -> Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let mut header = vec![];
+        // The section, of unspecified length.
+        header.extend(SECTION_HEADER_BLOCK.to_ne_bytes());
+        header.extend(28u32.to_ne_bytes());
+        header.extend(BYTE_ORDER_MAGIC.to_ne_bytes());
+        header.extend(1u16.to_ne_bytes());
+        header.extend(0u16.to_ne_bytes());
+        header.extend((-1i64).to_ne_bytes());
+        header.extend(28u32.to_ne_bytes());
+        // Its only interface, with microsecond timestamps and no limit on the packets length.
+        header.extend(INTERFACE_DESCRIPTION_BLOCK.to_ne_bytes());
+        header.extend(20u32.to_ne_bytes());
+        header.extend(LINKTYPE_DBUS.to_ne_bytes());
+        header.extend(0u16.to_ne_bytes());
+        header.extend(0u32.to_ne_bytes());
+        header.extend(20u32.to_ne_bytes());
+        writer.write_all(&header)?;
+        writer.flush()?;
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("zbus::Connection capture".into())
+            .spawn(move || {
+                if let Err(e) = write_blocks(writer, receiver) {
+                    warn!("Failed to capture a message, stopping the capture: {e}");
+                }
+            })?;
+
+        Ok(Self {
+            blocks: Mutex::new(Some(sender)),
+        })
+    }
+
+    /// Encode `msg` as an enhanced packet block.
+    fn block(msg: &Message, direction: Direction) -> io::Result<Vec<u8>> {
+        let bytes = msg.data().bytes();
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+        let padding = padding_for_4_bytes(bytes.len());
+        let block_len = 44 + len + padding as u32;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        let flags = match direction {
+            Direction::Incoming => EPB_FLAGS_INBOUND,
+            Direction::Outgoing => EPB_FLAGS_OUTBOUND,
+        };
+
+        let mut block = Vec::with_capacity(block_len as usize);
+        block.extend(ENHANCED_PACKET_BLOCK.to_ne_bytes());
+        block.extend(block_len.to_ne_bytes());
+        block.extend(0u32.to_ne_bytes());
+        block.extend(((timestamp >> 32) as u32).to_ne_bytes());
+        block.extend((timestamp as u32).to_ne_bytes());
+        block.extend(len.to_ne_bytes());
+        block.extend(len.to_ne_bytes());
+        block.extend(bytes);
+        block.resize(block.len() + padding, 0);
+        block.extend(OPT_EPB_FLAGS.to_ne_bytes());
+        block.extend(4u16.to_ne_bytes());
+        block.extend(flags.to_ne_bytes());
+        block.extend(OPT_END.to_ne_bytes());
+        block.extend(0u16.to_ne_bytes());
+        block.extend(block_len.to_ne_bytes());
+
+        Ok(block)
+    }
+}
+
+impl std::fmt::Debug for Writer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Writer").finish_non_exhaustive()
+    }
+}
+
+impl Tap for Writer {
+    fn message(&self, msg: &Message, direction: Direction) {
+        let mut blocks = self.blocks.lock().expect("poisoned lock");
+        let Some(sender) = blocks.as_ref() else {
+            return;
+        };
+        let sent = match Self::block(msg, direction) {
+            Ok(block) => sender.send(block).is_ok(),
+            Err(e) => {
+                warn!("Failed to capture a message, stopping the capture: {e}");
+
+                false
+            }
+        };
+        // Either way, the capture is over.
+        if !sent {
+            *blocks = None;
+        }
+    }
+}
+
+/// Write the blocks to `writer` until the `Writer` is dropped.
+///
+/// The writer is flushed whenever there are no more blocks to write for now, rather than after
+/// each of them.
+fn write_blocks<W: Write>(mut writer: W, blocks: Receiver<Vec<u8>>) -> io::Result<()> {
+    while let Ok(block) = blocks.recv() {
+        writer.write_all(&block)?;
+        for block in blocks.try_iter() {
+            writer.write_all(&block)?;
+        }
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// A message read from a capture.
+#[derive(Debug, Clone)]
+pub struct Record {
+    timestamp: SystemTime,
+    direction: Option<Direction>,
+    message: Message,
+}
+
+impl Record {
+    /// When the message was captured.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Whether the message was received or sent by the capturing connection.
+    ///
+    /// This is `None` for the captures that don't record it, such as the ones of `dbus-monitor`.
+    pub fn direction(&self) -> Option<Direction> {
+        self.direction
+    }
+
+    /// The message.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// The message, consuming the record.
+    pub fn into_message(self) -> Message {
+        self.message
+    }
+}
+
+/// Reads the messages of a pcap or pcapng capture.
+///
+/// The reader is an iterator over the [`Record`]s of the capture. Packets of other link types than
+/// D-Bus are skipped. See the [module documentation](self) for an example.
+#[derive(Debug)]
+pub struct Reader<R> {
+    reader: R,
+    format: Format,
+}
+
+#[derive(Debug)]
+enum Format {
+    Pcap {
+        endian: Endian,
+        nanos: bool,
+    },
+    Pcapng {
+        endian: Endian,
+        // The link type and timestamp unit (per second) of each interface of the section.
+        interfaces: Vec<(u16, u64)>,
+    },
+}
+
+impl<R: Read> Reader<R> {
+    /// Create a reader for the capture in `reader`, reading its headers.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let format = if u32::from_ne_bytes(magic) == SECTION_HEADER_BLOCK {
+            let endian = read_section_header(&mut reader)?;
+
+            Format::Pcapng {
+                endian,
+                interfaces: vec![],
+            }
+        } else {
+            let (endian, nanos) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+                (PCAP_MAGIC_MICROS, _) => (Endian::Little, false),
+                (PCAP_MAGIC_NANOS, _) => (Endian::Little, true),
+                (_, PCAP_MAGIC_MICROS) => (Endian::Big, false),
+                (_, PCAP_MAGIC_NANOS) => (Endian::Big, true),
+                _ => return Err(invalid_data("not a pcap or pcapng capture")),
+            };
+            let mut header = [0; 20];
+            reader.read_exact(&mut header)?;
+            if read_u32(&header[16..], endian)? != u32::from(LINKTYPE_DBUS) {
+                return Err(invalid_data("not a capture of D-Bus messages"));
+            }
+
+            Format::Pcap { endian, nanos }
+        };
+
+        Ok(Self { reader, format })
+    }
+
+    /// Read the next record, or `None` at the end of the capture.
+    fn read_record(&mut self) -> Result<Option<Record>> {
+        match &mut self.format {
+            Format::Pcap { endian, nanos } => {
+                let mut header = [0; 16];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+                let secs = read_u32(&header, *endian)?;
+                let fraction = read_u32(&header[4..], *endian)?;
+                let len = read_u32(&header[8..], *endian)? as usize;
+                if len > MAX_MESSAGE_SIZE {
+                    return Err(invalid_data("packet too large"));
+                }
+                let mut packet = vec![0; len];
+                self.reader.read_exact(&mut packet)?;
+
+                let subsec = if *nanos {
+                    Duration::from_nanos(fraction.into())
+                } else {
+                    Duration::from_micros(fraction.into())
+                };
+                let timestamp = UNIX_EPOCH + Duration::from_secs(secs.into()) + subsec;
+
+                Ok(Some(Record {
+                    timestamp,
+                    direction: None,
+                    message: message_from_bytes(packet)?,
+                }))
+            }
+            Format::Pcapng { endian, interfaces } => loop {
+                let mut header = [0; 8];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+                let block_type = u32::from_ne_bytes(header[..4].try_into().unwrap());
+                if block_type == SECTION_HEADER_BLOCK {
+                    *endian = read_section_header(&mut self.reader)?;
+                    interfaces.clear();
+
+                    continue;
+                }
+
+                let block_type = read_u32(&header, *endian)?;
+                let block_len = read_u32(&header[4..], *endian)? as usize;
+                if block_len > MAX_BLOCK_LEN {
+                    return Err(invalid_data("block too large"));
+                }
+                let body_len = block_len
+                    .checked_sub(12)
+                    .ok_or_else(|| invalid_data("invalid block length"))?;
+                let mut body = vec![0; body_len + 4];
+                self.reader.read_exact(&mut body)?;
+                body.truncate(body_len);
+
+                match block_type {
+                    INTERFACE_DESCRIPTION_BLOCK => {
+                        let link_type = read_u16(&body, *endian)?;
+                        let options = body.get(8..).unwrap_or_default();
+                        let mut units = 1_000_000;
+                        if let Some(value) = find_option(options, OPT_IF_TSRESOL, *endian) {
+                            let resolution = value.first().copied().unwrap_or(6);
+                            units = if resolution & 0x80 == 0 {
+                                10u64.checked_pow(resolution.into())
+                            } else {
+                                2u64.checked_pow((resolution & 0x7f).into())
+                            }
+                            .ok_or_else(|| invalid_data("invalid timestamp resolution"))?;
+                        }
+                        interfaces.push((link_type, units));
+                    }
+                    ENHANCED_PACKET_BLOCK => {
+                        if body.len() < 20 {
+                            return Err(invalid_data("invalid packet block"));
+                        }
+                        let interface = read_u32(&body, *endian)? as usize;
+                        let (link_type, units) = *interfaces
+                            .get(interface)
+                            .ok_or_else(|| invalid_data("unknown interface"))?;
+                        if link_type != LINKTYPE_DBUS {
+                            continue;
+                        }
+
+                        let timestamp = (u64::from(read_u32(&body[4..], *endian)?) << 32)
+                            | u64::from(read_u32(&body[8..], *endian)?);
+                        let len = read_u32(&body[12..], *endian)? as usize;
+                        let end = 20usize.saturating_add(len);
+                        let packet = body
+                            .get(20..end)
+                            .ok_or_else(|| invalid_data("invalid packet length"))?;
+                        let options = body
+                            .get(end + padding_for_4_bytes(len)..)
+                            .unwrap_or_default();
+                        let direction = find_option(options, OPT_EPB_FLAGS, *endian)
+                            .and_then(|value| read_u32(value, *endian).ok())
+                            .and_then(|flags| match flags & 0b11 {
+                                EPB_FLAGS_INBOUND => Some(Direction::Incoming),
+                                EPB_FLAGS_OUTBOUND => Some(Direction::Outgoing),
+                                _ => None,
+                            });
+
+                        let timestamp = UNIX_EPOCH
+                            + Duration::from_secs(timestamp / units)
+                            + Duration::from_nanos(
+                                ((timestamp % units) as u128 * 1_000_000_000 / units as u128)
+                                    as u64,
+                            );
+
+                        return Ok(Some(Record {
+                            timestamp,
+                            direction,
+                            message: message_from_bytes(packet.to_vec())?,
+                        }));
+                    }
+                    // Other blocks don't contain D-Bus messages.
+                    _ => continue,
+                }
+            },
+        }
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Read the rest of a section header block, after its type, returning its byte order.
+fn read_section_header<R: Read>(reader: &mut R) -> Result<Endian> {
+    let mut header = [0; 8];
+    reader.read_exact(&mut header)?;
+    let endian = match header[4..] {
+        [0x1A, 0x2B, 0x3C, 0x4D] => Endian::Big,
+        [0x4D, 0x3C, 0x2B, 0x1A] => Endian::Little,
+        _ => return Err(invalid_data("invalid byte-order magic")),
+    };
+    let block_len = read_u32(&header, endian)? as usize;
+    let rest_len = block_len
+        .checked_sub(12)
+        .ok_or_else(|| invalid_data("invalid block length"))?;
+    // The version, section length and options don't matter.
+    io::copy(&mut reader.take(rest_len as u64), &mut io::sink())?;
+
+    Ok(endian)
+}
+
+/// Find the value of the option `code` in the options of a block.
+fn find_option(mut options: &[u8], code: u16, endian: Endian) -> Option<&[u8]> {
+    while options.len() >= 4 {
+        let option_code = read_u16(options, endian).ok()?;
+        let len = read_u16(&options[2..], endian).ok()? as usize;
+        if option_code == OPT_END {
+            break;
+        }
+        let value = options.get(4..4 + len)?;
+        if option_code == code {
+            return Some(value);
+        }
+        options = options.get(4 + len + padding_for_4_bytes(len)..)?;
+    }
+
+    None
+}
+
+/// Fill `buf`, returning `false` if the reader was at its end.
+fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(true)
+}
+
+fn message_from_bytes(bytes: Vec<u8>) -> Result<Message> {
+    let sig = bytes
+        .first()
+        .copied()
+        .ok_or_else(|| invalid_data("empty packet"))?;
+    let endian = Endian::from(EndianSig::try_from(sig)?);
+    let data = serialized::Data::new(bytes, Context::new_dbus(endian, 0));
+
+    Message::from_raw_parts(data, 0)
+}
+
+fn read_u16(bytes: &[u8], endian: Endian) -> Result<u16> {
+    let bytes = bytes
+        .get(..2)
+        .ok_or_else(|| invalid_data("truncated block"))?
+        .try_into()
+        .unwrap();
+    let value = match endian {
+        Endian::Little => u16::from_le_bytes(bytes),
+        Endian::Big => u16::from_be_bytes(bytes),
+    };
+
+    Ok(value)
+}
+
+fn read_u32(bytes: &[u8], endian: Endian) -> Result<u32> {
+    let bytes = bytes
+        .get(..4)
+        .ok_or_else(|| invalid_data("truncated block"))?
+        .try_into()
+        .unwrap();
+    let value = match endian {
+        Endian::Little => u32::from_le_bytes(bytes),
+        Endian::Big => u32::from_be_bytes(bytes),
+    };
+
+    Ok(value)
+}
+
+fn padding_for_4_bytes(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+fn invalid_data(msg: &str) -> Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::mpsc,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use test_log::test;
+
+    use super::{
+        Reader, Writer, ENHANCED_PACKET_BLOCK, INTERFACE_DESCRIPTION_BLOCK, LINKTYPE_DBUS,
+        PCAP_MAGIC_MICROS,
+    };
+    use crate::{
+        connection::{Direction, Tap},
+        Error, Message,
+    };
+
+    // Hands the captured bytes over once the capture thread is done with it.
+    struct Capture(Vec<u8>, mpsc::Sender<Vec<u8>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for Capture {
+        fn drop(&mut self) {
+            let _ = self.1.send(std::mem::take(&mut self.0));
+        }
+    }
+
+    fn capture(messages: &[(Message, Direction)]) -> Vec<u8> {
+        let (sender, receiver) = mpsc::channel();
+        let writer = Writer::new(Capture(vec![], sender)).unwrap();
+        for (msg, direction) in messages {
+            writer.message(msg, *direction);
+        }
+        drop(writer);
+
+        receiver.recv().unwrap()
+    }
+
+    fn is_invalid_data(err: &Error) -> bool {
+        matches!(err, Error::InputOutput(e) if e.kind() == io::ErrorKind::InvalidData)
+    }
+
+    fn messages() -> Vec<Message> {
+        let call = Message::method_call("/org/zbus/capture", "Ping")
+            .unwrap()
+            .interface("org.freedesktop.DBus.Peer")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        let reply = Message::method_return(&call.header())
+            .unwrap()
+            .build(&("odd length",))
+            .unwrap();
+
+        vec![call, reply]
+    }
+
+    #[test]
+    fn pcapng_roundtrip() {
+        let messages = messages();
+        let capture = capture(&[
+            (messages[0].clone(), Direction::Outgoing),
+            (messages[1].clone(), Direction::Incoming),
+        ]);
+
+        let records = Reader::new(capture.as_slice())
+            .unwrap()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].direction(), Some(Direction::Outgoing));
+        assert_eq!(records[1].direction(), Some(Direction::Incoming));
+        assert!(records[0].timestamp() > UNIX_EPOCH);
+        for (record, msg) in records.iter().zip(&messages) {
+            assert_eq!(record.message().data().bytes(), msg.data().bytes());
+        }
+        assert_eq!(
+            records[1].message().body().deserialize::<&str>().unwrap(),
+            "odd length"
+        );
+    }
+
+    #[test]
+    fn read_pcap() {
+        // As written by `dbus-monitor --pcap`, in big endian.
+        let messages = messages();
+        let mut capture = vec![];
+        capture.extend(PCAP_MAGIC_MICROS.to_be_bytes());
+        capture.extend(2u16.to_be_bytes());
+        capture.extend(4u16.to_be_bytes());
+        capture.extend([0; 8]);
+        capture.extend((1u32 << 27).to_be_bytes());
+        capture.extend(u32::from(LINKTYPE_DBUS).to_be_bytes());
+        for (i, msg) in messages.iter().enumerate() {
+            let bytes = msg.data().bytes();
+            capture.extend((1_700_000_000u32 + i as u32).to_be_bytes());
+            capture.extend(500u32.to_be_bytes());
+            capture.extend((bytes.len() as u32).to_be_bytes());
+            capture.extend((bytes.len() as u32).to_be_bytes());
+            capture.extend(bytes);
+        }
+
+        let records = Reader::new(capture.as_slice())
+            .unwrap()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[1].timestamp(),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_001) + Duration::from_micros(500)
+        );
+        assert_eq!(records[1].direction(), None);
+        assert_eq!(
+            records[0].message().header().member().unwrap().as_str(),
+            "Ping"
+        );
+    }
+
+    #[test]
+    fn truncated_captures() {
+        let messages = messages();
+        let capture = capture(&[
+            (messages[0].clone(), Direction::Outgoing),
+            (messages[1].clone(), Direction::Incoming),
+        ]);
+
+        // Every truncation is either reported, or happens to fall between records.
+        for len in 0..capture.len() {
+            let records = Reader::new(&capture[..len])
+                .and_then(|reader| reader.collect::<crate::Result<Vec<_>>>());
+            match records {
+                Ok(records) => assert!(records.len() < 2, "{len}"),
+                Err(Error::InputOutput(e)) => assert!(
+                    matches!(
+                        e.kind(),
+                        io::ErrorKind::UnexpectedEof | io::ErrorKind::InvalidData
+                    ),
+                    "{len}: {e}"
+                ),
+                Err(e) => panic!("{len}: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_captures() {
+        // The pcapng section header, in little endian.
+        let mut section = vec![];
+        section.extend(super::SECTION_HEADER_BLOCK.to_le_bytes());
+        section.extend(28u32.to_le_bytes());
+        section.extend(super::BYTE_ORDER_MAGIC.to_le_bytes());
+        section.extend([1, 0, 0, 0]);
+        section.extend((-1i64).to_le_bytes());
+        section.extend(28u32.to_le_bytes());
+        let read = |blocks: &[u8]| {
+            let capture = [section.as_slice(), blocks].concat();
+            Reader::new(capture.as_slice())
+                .unwrap()
+                .collect::<crate::Result<Vec<_>>>()
+                .unwrap_err()
+        };
+
+        // An interface description block too short for its link type.
+        let mut block = vec![];
+        block.extend(INTERFACE_DESCRIPTION_BLOCK.to_le_bytes());
+        block.extend(12u32.to_le_bytes());
+        block.extend(12u32.to_le_bytes());
+        assert!(is_invalid_data(&read(&block)));
+
+        // A block claiming more than any message, which isn't allocated.
+        let mut block = vec![];
+        block.extend(ENHANCED_PACKET_BLOCK.to_le_bytes());
+        block.extend(u32::MAX.to_le_bytes());
+        assert!(is_invalid_data(&read(&block)));
+
+        // A packet claiming more than the block holds.
+        let mut block = vec![];
+        block.extend(INTERFACE_DESCRIPTION_BLOCK.to_le_bytes());
+        block.extend(20u32.to_le_bytes());
+        block.extend(LINKTYPE_DBUS.to_le_bytes());
+        block.extend([0; 6]);
+        block.extend(20u32.to_le_bytes());
+        block.extend(ENHANCED_PACKET_BLOCK.to_le_bytes());
+        block.extend(32u32.to_le_bytes());
+        block.extend([0; 12]);
+        block.extend(u32::MAX.to_le_bytes());
+        block.extend(0u32.to_le_bytes());
+        block.extend(32u32.to_le_bytes());
+        assert!(is_invalid_data(&read(&block)));
+
+        // A pcap packet claiming more than any message.
+        let mut capture = vec![];
+        capture.extend(PCAP_MAGIC_MICROS.to_le_bytes());
+        capture.extend([2, 0, 4, 0]);
+        capture.extend([0; 8]);
+        capture.extend((1u32 << 27).to_le_bytes());
+        capture.extend(u32::from(LINKTYPE_DBUS).to_le_bytes());
+        capture.extend([0; 8]);
+        capture.extend(u32::MAX.to_le_bytes());
+        capture.extend(u32::MAX.to_le_bytes());
+        let err = Reader::new(capture.as_slice())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(is_invalid_data(&err));
+    }
+}
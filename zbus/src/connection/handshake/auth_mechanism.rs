@@ -4,11 +4,11 @@ use crate::{Error, Result};
 
 /// Authentication mechanisms
 ///
-/// Note that the `DBUS_COOKIE_SHA1` mechanism is not supported by zbus since version 5.0. The
-/// reasons are:
+/// Note that the `DBUS_COOKIE_SHA1` mechanism is only available if the `cookie-sha1` feature is
+/// enabled. It's not enabled by default since:
 ///
 /// * It drags the `sha1` crate as a dependency, which can be [problematic for some users].
-/// * It makes the handshake more complex, now allowing use to pipeline all the commands.
+/// * It requires an additional round-trip, so the handshake commands can't all be pipelined.
 /// * It's not widely used. If `EXTERNAL` is not an option, you might as well just use `ANONYMOUS`.
 ///
 /// See <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-mechanisms>
@@ -24,6 +24,14 @@ pub enum AuthMechanism {
     /// Does not perform any authentication at all, and should not be accepted by message buses.
     /// However, it might sometimes be useful for non-message-bus uses of D-Bus.
     Anonymous,
+
+    /// This mechanism is designed to establish that a client has the ability to read a private
+    /// file owned by the user being authenticated.
+    ///
+    /// The client and the server must share the user's home directory. On the server side, only
+    /// clients claiming the same user as the one the server runs as can be authenticated.
+    #[cfg(feature = "cookie-sha1")]
+    CookieSha1,
 }
 
 impl AuthMechanism {
@@ -31,6 +39,8 @@ impl AuthMechanism {
         match self {
             AuthMechanism::External => "EXTERNAL",
             AuthMechanism::Anonymous => "ANONYMOUS",
+            #[cfg(feature = "cookie-sha1")]
+            AuthMechanism::CookieSha1 => "DBUS_COOKIE_SHA1",
         }
     }
 }
@@ -49,6 +59,8 @@ impl FromStr for AuthMechanism {
         match s {
             "EXTERNAL" => Ok(AuthMechanism::External),
             "ANONYMOUS" => Ok(AuthMechanism::Anonymous),
+            #[cfg(feature = "cookie-sha1")]
+            "DBUS_COOKIE_SHA1" => Ok(AuthMechanism::CookieSha1),
             _ => Err(Error::Handshake(format!("Unsupported mechanism: {s}"))),
         }
     }
@@ -0,0 +1,60 @@
+#[cfg(unix)]
+use std::collections::HashSet;
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use crate::fdo::ConnectionCredentials;
+
+type Callback =
+    dyn Fn(Arc<ConnectionCredentials>) -> Pin<Box<dyn Future<Output = bool> + Send>> + Send + Sync;
+
+/// Rules to authorize clients on the server side, once they're authenticated.
+#[derive(Clone, Default)]
+pub(crate) struct Authorization {
+    #[cfg(unix)]
+    unix_user_ids: Option<Arc<HashSet<u32>>>,
+    callback: Option<Arc<Callback>>,
+}
+
+impl Authorization {
+    /// Only authorize clients running as one of the given users.
+    #[cfg(unix)]
+    pub fn set_unix_user_ids(&mut self, uids: HashSet<u32>) {
+        self.unix_user_ids = Some(Arc::new(uids));
+    }
+
+    /// Only authorize clients for which `callback` returns `true`.
+    pub fn set_callback<F, Fut>(&mut self, callback: F)
+    where
+        F: Fn(Arc<ConnectionCredentials>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.callback = Some(Arc::new(move |creds| Box::pin(callback(creds))));
+    }
+
+    /// Check if the client with the given credentials is authorized.
+    pub async fn check(&self, credentials: &Arc<ConnectionCredentials>) -> bool {
+        #[cfg(unix)]
+        if let Some(uids) = &self.unix_user_ids {
+            match credentials.unix_user_id() {
+                Some(uid) if uids.contains(&uid) => (),
+                _ => return false,
+            }
+        }
+
+        match &self.callback {
+            Some(callback) => callback(credentials.clone()).await,
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Authorization");
+        #[cfg(unix)]
+        s.field("unix_user_ids", &self.unix_user_ids);
+
+        s.field("callback", &self.callback.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
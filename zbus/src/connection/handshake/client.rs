@@ -1,11 +1,16 @@
 use async_trait::async_trait;
+#[cfg(feature = "cookie-sha1")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::{instrument, trace, warn};
 
 use crate::{conn::socket::ReadHalf, is_flatpak, names::OwnedUniqueName, Message};
 
+#[cfg(feature = "cookie-sha1")]
+use super::cookies::{random_challenge, Keyring};
 use super::{
-    sasl_auth_id, AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake,
-    OwnedGuid, Result,
+    mechanism::validate_name, sasl_auth_id, AuthMechanism, Authenticated, BoxedSplit,
+    ClientMechanism, Command, Common, Error, Handshake, Mechanism, OwnedGuid, Result,
 };
 
 /// A representation of an in-progress handshake, client-side
@@ -18,6 +23,10 @@ pub struct Client {
     server_guid: Option<OwnedGuid>,
     bus: bool,
     user_id: Result<String>,
+    custom_mechanism: Option<Arc<dyn Mechanism>>,
+    // The keyring directory for `DBUS_COOKIE_SHA1`, if not the one of the current user.
+    #[cfg(feature = "cookie-sha1")]
+    keyring_dir: Option<PathBuf>,
 }
 
 impl Client {
@@ -39,9 +48,27 @@ impl Client {
                 Some(value) => Ok(value.to_string()),
                 None => sasl_auth_id(),
             },
+            custom_mechanism: None,
+            #[cfg(feature = "cookie-sha1")]
+            keyring_dir: None,
         }
     }
 
+    /// Look up `DBUS_COOKIE_SHA1` cookies in `dir`, instead of the keyring directory of the
+    /// current user.
+    #[cfg(all(test, feature = "cookie-sha1"))]
+    pub fn set_keyring_dir(&mut self, dir: PathBuf) {
+        self.keyring_dir = Some(dir);
+    }
+
+    /// Authenticate with a custom mechanism, instead of the built-in one.
+    pub fn set_custom_mechanism(&mut self, mechanism: Arc<dyn Mechanism>) -> Result<()> {
+        validate_name(mechanism.name())?;
+        self.custom_mechanism = Some(mechanism);
+
+        Ok(())
+    }
+
     fn set_guid(&mut self, guid: OwnedGuid) -> Result<()> {
         match &self.server_guid {
             Some(server_guid) if *server_guid != guid => {
@@ -84,32 +111,59 @@ impl Client {
     /// Perform the authentication handshake with the server.
     #[instrument(skip(self))]
     async fn authenticate(&mut self) -> Result<()> {
-        let mechanism = self.common.mechanism();
+        let (mechanism, mut exchange): (String, Box<dyn ClientMechanism>) =
+            match &self.custom_mechanism {
+                Some(custom) => (custom.name().to_string(), custom.client()?),
+                None => {
+                    let mechanism = self.common.mechanism();
+                    let builtin = Builtin {
+                        mechanism,
+                        user_id: self.user_id.clone(),
+                        #[cfg(feature = "cookie-sha1")]
+                        keyring_dir: self.keyring_dir.clone(),
+                    };
+
+                    (mechanism.to_string(), Box::new(builtin))
+                }
+            };
         trace!("Trying {mechanism} mechanism");
-        let user_id = self.user_id.clone();
-        let auth_cmd = match mechanism {
-            AuthMechanism::Anonymous => Command::Auth(Some(mechanism), Some("zbus".into())),
-            AuthMechanism::External => Command::Auth(Some(mechanism), Some(user_id?.into_bytes())),
-        };
-        self.common.write_command(auth_cmd).await?;
+        let initial_response = exchange.initial_response().await?;
+        self.common
+            .write_command(Command::Auth(Some(mechanism.clone()), initial_response))
+            .await?;
 
-        match self.common.read_command().await? {
-            Command::Ok(guid) => {
-                trace!("Received OK from server");
-                self.set_guid(guid)?;
+        loop {
+            match self.common.read_command().await? {
+                Command::Ok(guid) => {
+                    trace!("Received OK from server");
+                    self.set_guid(guid)?;
 
-                Ok(())
-            }
-            Command::Rejected(accepted) => {
-                let list = accepted.replace(" ", ", ");
-                Err(Error::Handshake(format!(
-                    "{mechanism} rejected by the server. Accepted mechanisms: [{list}]"
-                )))
+                    return Ok(());
+                }
+                Command::Data(challenge) => {
+                    trace!("Received challenge from server");
+                    let response = exchange
+                        .respond(challenge.as_deref().unwrap_or_default())
+                        .await?;
+                    self.common
+                        .write_command(Command::Data(Some(response)))
+                        .await?;
+                }
+                Command::Rejected(accepted) => {
+                    let list = accepted.replace(" ", ", ");
+                    return Err(Error::Handshake(format!(
+                        "{mechanism} rejected by the server. Accepted mechanisms: [{list}]"
+                    )));
+                }
+                Command::Error(e) => {
+                    return Err(Error::Handshake(format!("Received error from server: {e}")))
+                }
+                cmd => {
+                    return Err(Error::Handshake(format!(
+                        "Unexpected command from server: {cmd}"
+                    )))
+                }
             }
-            Command::Error(e) => Err(Error::Handshake(format!("Received error from server: {e}"))),
-            cmd => Err(Error::Handshake(format!(
-                "Unexpected command from server: {cmd}"
-            ))),
         }
     }
 
@@ -218,10 +272,80 @@ impl Handshake for Client {
             #[cfg(unix)]
             already_received_fds: received_fds,
             unique_name,
+            #[cfg(feature = "p2p")]
+            peer_auth_identity: None,
         })
     }
 }
 
+/// Compute the response to a `DBUS_COOKIE_SHA1` challenge from the server.
+///
+/// The challenge is of the form `<context> <cookie ID> <server challenge>` and the response of the
+/// form `<client challenge> <hash>`.
+#[cfg(feature = "cookie-sha1")]
+async fn cookie_sha1_response(challenge: &[u8], keyring_dir: Option<&Path>) -> Result<Vec<u8>> {
+    let challenge = std::str::from_utf8(challenge)
+        .map_err(|e| Error::Handshake(format!("Invalid cookie challenge: {e}")))?;
+    let mut fields = challenge.split_ascii_whitespace();
+    let (context, id, server_challenge) =
+        match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(context), Some(id), Some(server_challenge), None) => {
+                (context, id, server_challenge)
+            }
+            _ => {
+                return Err(Error::Handshake(format!(
+                    "Invalid cookie challenge: {challenge}"
+                )))
+            }
+        };
+    let id = id
+        .parse()
+        .map_err(|e| Error::Handshake(format!("Invalid cookie ID: {e}")))?;
+    let keyring = Keyring::in_dir(keyring_dir, context.parse()?)?;
+    let cookie = crate::Task::spawn_blocking(move || keyring.lookup(id), "cookie lookup").await?;
+    let client_challenge = random_challenge();
+    let hash = cookie.hash(server_challenge, &client_challenge);
+
+    Ok(format!("{client_challenge} {hash}").into_bytes())
+}
+
+// The client side of the built-in mechanisms.
+#[derive(Debug)]
+struct Builtin {
+    mechanism: AuthMechanism,
+    user_id: Result<String>,
+    #[cfg(feature = "cookie-sha1")]
+    keyring_dir: Option<PathBuf>,
+}
+
+#[async_trait]
+impl ClientMechanism for Builtin {
+    async fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+        let response = match self.mechanism {
+            AuthMechanism::Anonymous => "zbus".into(),
+            AuthMechanism::External => self.user_id.clone()?.into_bytes(),
+            #[cfg(feature = "cookie-sha1")]
+            AuthMechanism::CookieSha1 => self.user_id.clone()?.into_bytes(),
+        };
+
+        Ok(Some(response))
+    }
+
+    async fn respond(&mut self, challenge: &[u8]) -> Result<Vec<u8>> {
+        match self.mechanism {
+            #[cfg(feature = "cookie-sha1")]
+            AuthMechanism::CookieSha1 => {
+                cookie_sha1_response(challenge, self.keyring_dir.as_deref()).await
+            }
+            _ => Err(Error::Handshake(format!(
+                "Unexpected challenge from server for {} mechanism: {}",
+                self.mechanism,
+                hex::encode(challenge),
+            ))),
+        }
+    }
+}
+
 fn create_hello_method_call() -> Message {
     Message::method_call("/org/freedesktop/DBus", "Hello")
         .unwrap()
@@ -1,6 +1,6 @@
 use std::{borrow::Cow, fmt, str::FromStr};
 
-use crate::{conn::AuthMechanism, Error, Guid, OwnedGuid, Result};
+use crate::{Error, Guid, OwnedGuid, Result};
 
 // The plain-text SASL profile authentication protocol described here:
 // <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-protocol>
@@ -9,7 +9,8 @@ use crate::{conn::AuthMechanism, Error, Guid, OwnedGuid, Result};
 #[derive(Debug)]
 #[allow(clippy::upper_case_acronyms)]
 pub(super) enum Command {
-    Auth(Option<AuthMechanism>, Option<Vec<u8>>),
+    // The mechanism is kept as a string, as it's not necessarily one of `AuthMechanism`.
+    Auth(Option<String>, Option<Vec<u8>>),
     Cancel,
     Begin,
     Data(Option<Vec<u8>>),
@@ -56,11 +57,7 @@ impl FromStr for Command {
         let mut words = s.split_ascii_whitespace();
         let cmd = match words.next() {
             Some("AUTH") => {
-                let mech = if let Some(m) = words.next() {
-                    Some(m.parse()?)
-                } else {
-                    None
-                };
+                let mech = words.next().map(String::from);
                 let resp = match words.next() {
                     Some(resp) => Some(hex::decode(resp)?),
                     None => None,
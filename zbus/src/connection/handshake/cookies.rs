@@ -0,0 +1,423 @@
+// Keyring handling for the `DBUS_COOKIE_SHA1` authentication mechanism.
+//
+// See <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-mechanisms-sha>.
+
+// Managing cookies is only needed on the server side.
+#![cfg_attr(not(feature = "p2p"), allow(dead_code))]
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use sha1::{Digest, Sha1};
+use tracing::{debug, trace};
+
+use crate::{Error, Result};
+
+/// The cookie context used when the server doesn't specify one.
+pub(super) const DEFAULT_CONTEXT: &str = "org_freedesktop_general";
+
+// Same values as the reference implementation.
+//
+// Cookies created more than this many seconds ago are not used anymore for new authentications.
+const NEW_KEY_TIMEOUT_SECS: u64 = 5 * 60;
+// Cookies created more than this many seconds ago are removed from the keyring. This must be
+// larger than `NEW_KEY_TIMEOUT_SECS` so ongoing authentications have a chance to complete.
+const EXPIRE_KEYS_TIMEOUT_SECS: u64 = NEW_KEY_TIMEOUT_SECS + 2 * 60;
+// Cookies that claim to be created this far in the future are considered bogus.
+const MAX_TIME_TRAVEL_SECS: u64 = 5 * 60;
+// How many times we try to acquire the keyring lock, and how long we wait between the attempts.
+const MAX_LOCK_ATTEMPTS: usize = 32;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(250);
+// Number of random bytes in a generated cookie or challenge.
+const COOKIE_LEN: usize = 24;
+const CHALLENGE_LEN: usize = 16;
+
+/// The name of a cookie context, i.e. the name of a keyring file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CookieContext(String);
+
+impl CookieContext {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for CookieContext {
+    fn default() -> Self {
+        Self(DEFAULT_CONTEXT.into())
+    }
+}
+
+impl FromStr for CookieContext {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // The context name is used as a file name so we need to be careful about what we accept.
+        if s.is_empty() {
+            return Err(Error::Handshake("Empty cookie context".into()));
+        }
+        if s.starts_with('.') || s.contains(['/', '\\']) || s.contains(char::is_whitespace) {
+            return Err(Error::Handshake(format!("Invalid cookie context: {s}")));
+        }
+
+        Ok(Self(s.into()))
+    }
+}
+
+impl fmt::Display for CookieContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A cookie from a keyring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Cookie {
+    pub id: u32,
+    created: u64,
+    cookie: String,
+}
+
+impl Cookie {
+    /// Compute the hash the client is expected to send back for the given challenges.
+    pub fn hash(&self, server_challenge: &str, client_challenge: &str) -> String {
+        let data = format!("{server_challenge}:{client_challenge}:{}", self.cookie);
+
+        hex::encode(Sha1::digest(data.as_bytes()))
+    }
+
+    fn generate(id: u32, created: u64) -> Self {
+        Self {
+            id,
+            created,
+            cookie: hex::encode(rand::random::<[u8; COOKIE_LEN]>()),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let id = fields.next()?.parse().ok()?;
+        let created = fields.next()?.parse().ok()?;
+        let cookie = fields.next()?;
+        if fields.next().is_some() || !cookie.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            created,
+            cookie: cookie.into(),
+        })
+    }
+}
+
+/// A keyring file in a keyring directory.
+#[derive(Debug)]
+pub(super) struct Keyring {
+    dir: PathBuf,
+    context: CookieContext,
+}
+
+impl Keyring {
+    /// The keyring for `context`, in the keyring directory of the current user.
+    pub fn new(context: CookieContext) -> Result<Self> {
+        let home = xdg_home::home_dir()
+            .ok_or_else(|| Error::Handshake("Failed to determine home directory".into()))?;
+
+        Ok(Self::with_dir(home.join(".dbus-keyrings"), context))
+    }
+
+    /// The keyring for `context`, in `dir` if given or the keyring directory of the current user
+    /// otherwise.
+    pub fn in_dir(dir: Option<&Path>, context: CookieContext) -> Result<Self> {
+        match dir {
+            Some(dir) => Ok(Self::with_dir(dir.to_path_buf(), context)),
+            None => Self::new(context),
+        }
+    }
+
+    /// The keyring for `context`, in the keyring directory `dir`.
+    pub fn with_dir(dir: PathBuf, context: CookieContext) -> Self {
+        Self { dir, context }
+    }
+
+    pub fn context(&self) -> &CookieContext {
+        &self.context
+    }
+
+    /// Look up the cookie with the given `id`.
+    ///
+    /// This is what the client side does.
+    pub fn lookup(&self, id: u32) -> Result<Cookie> {
+        self.check_dir_permissions()?;
+
+        self.load()?
+            .into_iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| {
+                Error::Handshake(format!(
+                    "No cookie with ID {id} in keyring `{}`",
+                    self.context
+                ))
+            })
+    }
+
+    /// Get a recent enough cookie, creating one if needed.
+    ///
+    /// While doing so, cookies that are expired are removed from the keyring. This is what the
+    /// server side does.
+    pub fn get_or_create(&self) -> Result<Cookie> {
+        self.create_dir()?;
+        let _lock = self.lock()?;
+
+        let now = now_secs();
+        let mut cookies = self.load()?;
+        let n_cookies = cookies.len();
+        cookies.retain(|c| {
+            c.created <= now + MAX_TIME_TRAVEL_SECS && c.created + EXPIRE_KEYS_TIMEOUT_SECS >= now
+        });
+        let mut changed = cookies.len() != n_cookies;
+
+        let cookie = match cookies
+            .iter()
+            .filter(|c| c.created + NEW_KEY_TIMEOUT_SECS >= now)
+            .max_by_key(|c| c.created)
+        {
+            Some(cookie) => cookie.clone(),
+            None => {
+                let id = loop {
+                    let id = rand::random::<u32>();
+                    if !cookies.iter().any(|c| c.id == id) {
+                        break id;
+                    }
+                };
+                let cookie = Cookie::generate(id, now);
+                trace!("Created cookie {id} in keyring `{}`", self.context);
+                cookies.push(cookie.clone());
+                changed = true;
+
+                cookie
+            }
+        };
+
+        if changed {
+            self.save(&cookies)?;
+        }
+
+        Ok(cookie)
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(self.context.as_str())
+    }
+
+    fn load(&self) -> Result<Vec<Cookie>> {
+        let content = match fs::read_to_string(self.path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(keyring_error(&self.path(), e)),
+        };
+
+        // Like the reference implementation, we silently skip lines we don't understand.
+        Ok(content.lines().filter_map(Cookie::parse).collect())
+    }
+
+    fn save(&self, cookies: &[Cookie]) -> Result<()> {
+        let path = self.path();
+        let tmp_path = self.dir.join(format!(
+            "{}.{:08x}.tmp",
+            self.context,
+            rand::random::<u32>()
+        ));
+        let res = (|| {
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(&tmp_path)?;
+            for c in cookies {
+                writeln!(file, "{} {} {}", c.id, c.created, c.cookie)?;
+            }
+            file.sync_all()?;
+
+            fs::rename(&tmp_path, &path)
+        })();
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+
+        res.map_err(|e| keyring_error(&path, e))
+    }
+
+    fn create_dir(&self) -> Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder
+            .create(&self.dir)
+            .map_err(|e| keyring_error(&self.dir, e))?;
+
+        self.check_dir_permissions()
+    }
+
+    #[cfg(unix)]
+    fn check_dir_permissions(&self) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let metadata = fs::metadata(&self.dir).map_err(|e| keyring_error(&self.dir, e))?;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            return Err(Error::Handshake(format!(
+                "Keyring directory `{}` is accessible by other users",
+                self.dir.display()
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn check_dir_permissions(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<LockFile> {
+        let path = self.dir.join(format!("{}.lock", self.context));
+
+        for attempt in 0..=MAX_LOCK_ATTEMPTS {
+            if attempt == MAX_LOCK_ATTEMPTS {
+                // Whoever created the lock must have died without removing it.
+                debug!("Removing stale keyring lock `{}`", path.display());
+                fs::remove_file(&path).map_err(|e| keyring_error(&path, e))?;
+            }
+
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(LockFile(path)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if attempt < MAX_LOCK_ATTEMPTS {
+                        thread::sleep(LOCK_RETRY_DELAY);
+                    }
+                }
+                Err(e) => return Err(keyring_error(&path, e)),
+            }
+        }
+
+        Err(Error::Handshake(format!(
+            "Failed to lock keyring `{}`",
+            self.context
+        )))
+    }
+}
+
+// Removes the lock file when dropped.
+#[derive(Debug)]
+struct LockFile(PathBuf);
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            debug!("Failed to remove keyring lock `{}`: {e}", self.0.display());
+        }
+    }
+}
+
+/// Generate a random challenge string.
+pub(super) fn random_challenge() -> String {
+    hex::encode(rand::random::<[u8; CHALLENGE_LEN]>())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn keyring_error(path: &Path, e: io::Error) -> Error {
+    Error::Handshake(format!("Keyring `{}` error: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn context_validation() {
+        assert!("org_freedesktop_general".parse::<CookieContext>().is_ok());
+        assert!("".parse::<CookieContext>().is_err());
+        assert!("../foo".parse::<CookieContext>().is_err());
+        assert!("foo\\bar".parse::<CookieContext>().is_err());
+        assert!(".hidden".parse::<CookieContext>().is_err());
+        assert!("foo bar".parse::<CookieContext>().is_err());
+    }
+
+    #[test]
+    fn keyring() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path().join(".dbus-keyrings");
+        let keyring = Keyring::with_dir(dir.clone(), CookieContext::default());
+
+        // Unknown cookies aren't found, whether or not the keyring exists.
+        assert!(keyring.lookup(1).is_err());
+
+        let cookie = keyring.get_or_create().unwrap();
+        assert_eq!(keyring.lookup(cookie.id).unwrap(), cookie);
+        // A recent cookie gets reused.
+        assert_eq!(keyring.get_or_create().unwrap(), cookie);
+        assert!(!dir.join(format!("{DEFAULT_CONTEXT}.lock")).exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&dir), 0o700);
+            assert_eq!(mode(&dir.join(DEFAULT_CONTEXT)), 0o600);
+        }
+
+        // Old cookies are replaced and expired ones removed, bogus lines are ignored.
+        let now = now_secs();
+        let expired = Cookie::generate(1, now - EXPIRE_KEYS_TIMEOUT_SECS - 1);
+        let old = Cookie::generate(2, now - NEW_KEY_TIMEOUT_SECS - 1);
+        let future = Cookie::generate(3, now + MAX_TIME_TRAVEL_SECS + 1);
+        keyring
+            .save(&[expired.clone(), old.clone(), future.clone()])
+            .unwrap();
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(keyring.path())
+            .unwrap();
+        writeln!(file, "garbage").unwrap();
+        drop(file);
+
+        let cookie = keyring.get_or_create().unwrap();
+        assert!(![1, 2, 3].contains(&cookie.id));
+        assert_eq!(keyring.load().unwrap(), vec![old, cookie]);
+    }
+
+    #[test]
+    fn hash() {
+        let cookie = Cookie {
+            id: 0,
+            created: 0,
+            cookie: "cookie".into(),
+        };
+
+        // sha1("server:client:cookie")
+        assert_eq!(
+            cookie.hash("server", "client"),
+            "da886d42e1fee942674b87d4f0d6c3d35804d5eb"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+use crate::{fdo::ConnectionCredentials, Error, Result};
+
+/// A custom SASL authentication mechanism.
+///
+/// In addition to the built-in mechanisms of [`AuthMechanism`], applications can implement their
+/// own mechanism through this trait and use it on either side of a connection, through
+/// [`Builder::custom_auth_mechanism`]. This is mostly useful for peer-to-peer connections, as bus
+/// implementations typically only support the standard mechanisms.
+///
+/// A mechanism is a factory for the state of each authentication exchange: [`Mechanism::client`]
+/// and [`Mechanism::server`] are called once per handshake on the respective side, and the returned
+/// objects drive the exchange of `DATA` commands, for as many rounds as needed.
+///
+/// # Example
+///
+/// A mechanism where the client proves it knows a pre-shared token:
+///
+/// ```
+/// use async_trait::async_trait;
+/// use zbus::{
+///     connection::handshake::{ClientMechanism, Mechanism, ServerMechanism, ServerStep},
+///     fdo::ConnectionCredentials,
+///     Result,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Token(String);
+///
+/// impl Mechanism for Token {
+///     fn name(&self) -> &str {
+///         "X_TOKEN"
+///     }
+///
+///     fn client(&self) -> Result<Box<dyn ClientMechanism>> {
+///         Ok(Box::new(TokenClient(self.0.clone())))
+///     }
+///
+///     fn server(&self, _peer: &ConnectionCredentials) -> Result<Box<dyn ServerMechanism>> {
+///         Ok(Box::new(TokenServer(self.0.clone())))
+///     }
+/// }
+///
+/// #[derive(Debug)]
+/// struct TokenClient(String);
+///
+/// #[async_trait]
+/// impl ClientMechanism for TokenClient {
+///     async fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+///         // Let the server ask for the token.
+///         Ok(None)
+///     }
+///
+///     async fn respond(&mut self, _challenge: &[u8]) -> Result<Vec<u8>> {
+///         Ok(self.0.clone().into_bytes())
+///     }
+/// }
+///
+/// #[derive(Debug)]
+/// struct TokenServer(String);
+///
+/// #[async_trait]
+/// impl ServerMechanism for TokenServer {
+///     async fn step(&mut self, response: Option<&[u8]>) -> Result<ServerStep> {
+///         Ok(match response {
+///             None => ServerStep::Challenge(vec![]),
+///             Some(token) if token == self.0.as_bytes() => {
+///                 ServerStep::Accept(Some("token-holder".into()))
+///             }
+///             Some(_) => ServerStep::Reject,
+///         })
+///     }
+/// }
+/// ```
+///
+/// [`AuthMechanism`]: super::AuthMechanism
+/// [`Builder::custom_auth_mechanism`]: crate::connection::Builder::custom_auth_mechanism
+pub trait Mechanism: Debug + Send + Sync {
+    /// The name of the mechanism, as sent in the `AUTH` command.
+    ///
+    /// As per the SASL specification, it must be composed of 1 to 20 uppercase ASCII letters,
+    /// digits, hyphens and underscores.
+    fn name(&self) -> &str;
+
+    /// Start the client side of an authentication exchange.
+    ///
+    /// The default implementation returns [`Error::Unsupported`], for mechanisms that are only
+    /// meant to be used on the server side.
+    fn client(&self) -> Result<Box<dyn ClientMechanism>> {
+        Err(Error::Unsupported)
+    }
+
+    /// Start the server side of an authentication exchange, with the peer identified by
+    /// `peer_credentials`.
+    ///
+    /// Which credentials are available depends on the platform and the socket type. The default
+    /// implementation returns [`Error::Unsupported`], for mechanisms that are only meant to be
+    /// used on the client side.
+    fn server(&self, peer_credentials: &ConnectionCredentials) -> Result<Box<dyn ServerMechanism>> {
+        let _ = peer_credentials;
+
+        Err(Error::Unsupported)
+    }
+}
+
+/// The client side of an authentication exchange of a custom [`Mechanism`].
+#[async_trait]
+pub trait ClientMechanism: Debug + Send {
+    /// The initial response, sent along with the `AUTH` command.
+    ///
+    /// If `None` is returned, no initial response is sent and the server is expected to send a
+    /// challenge first.
+    async fn initial_response(&mut self) -> Result<Option<Vec<u8>>>;
+
+    /// Compute the response to a `challenge` sent by the server.
+    ///
+    /// This is called for each `DATA` command received from the server, until the server either
+    /// accepts or rejects the authentication. Returning an error aborts the handshake.
+    async fn respond(&mut self, challenge: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// The server side of an authentication exchange of a custom [`Mechanism`].
+#[async_trait]
+pub trait ServerMechanism: Debug + Send {
+    /// Handle a `response` from the client.
+    ///
+    /// This is first called with the initial response from the `AUTH` command (`None` if the
+    /// client did not send any), and then for each `DATA` command received from the client in
+    /// reply to a [`ServerStep::Challenge`]. Returning an error aborts the handshake.
+    async fn step(&mut self, response: Option<&[u8]>) -> Result<ServerStep>;
+}
+
+/// The outcome of a [`ServerMechanism::step`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerStep {
+    /// Send the given challenge to the client and wait for its response.
+    Challenge(Vec<u8>),
+    /// The client is authenticated, optionally as the given identity.
+    ///
+    /// The identity is then available through [`Connection::peer_auth_identity`].
+    ///
+    /// [`Connection::peer_auth_identity`]: crate::Connection::peer_auth_identity
+    Accept(Option<String>),
+    /// The client could not be authenticated.
+    Reject,
+}
+
+/// Check that `name` is a valid SASL mechanism name.
+pub(super) fn validate_name(name: &str) -> Result<()> {
+    let valid = (1..=20).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+    if !valid {
+        return Err(Error::Handshake(format!(
+            "Invalid authentication mechanism name: {name}"
+        )));
+    }
+
+    Ok(())
+}
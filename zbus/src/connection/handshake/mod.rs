@@ -1,7 +1,12 @@
 mod auth_mechanism;
+#[cfg(feature = "p2p")]
+mod authorization;
 mod client;
 mod command;
 mod common;
+#[cfg(feature = "cookie-sha1")]
+mod cookies;
+mod mechanism;
 #[cfg(feature = "p2p")]
 mod server;
 
@@ -18,11 +23,14 @@ use crate::{Error, OwnedGuid, Result};
 use super::socket::{BoxedSplit, ReadHalf, WriteHalf};
 
 pub use auth_mechanism::AuthMechanism;
-use client::Client;
+#[cfg(feature = "p2p")]
+pub(crate) use authorization::Authorization;
+pub(crate) use client::Client;
 use command::Command;
 use common::Common;
+pub use mechanism::{ClientMechanism, Mechanism, ServerMechanism, ServerStep};
 #[cfg(feature = "p2p")]
-use server::Server;
+pub(crate) use server::Server;
 
 /// The result of a finalized handshake
 ///
@@ -44,6 +52,9 @@ pub struct Authenticated {
     #[cfg(unix)]
     pub(crate) already_received_fds: Vec<std::os::fd::OwnedFd>,
     pub(crate) unique_name: Option<OwnedUniqueName>,
+    /// The identity the peer authenticated as, on the server side.
+    #[cfg(feature = "p2p")]
+    pub(crate) peer_auth_identity: Option<String>,
 }
 
 impl Authenticated {
@@ -122,6 +133,7 @@ mod tests {
     use ntest::timeout;
     #[cfg(not(feature = "tokio"))]
     use std::os::unix::net::UnixStream;
+    use std::sync::Arc;
     use test_log::test;
     #[cfg(feature = "tokio")]
     use tokio::{
@@ -277,4 +289,228 @@ mod tests {
             .unwrap();
         crate::utils::block_on(server.perform()).unwrap();
     }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_sha1_handshake() {
+        let home = tempfile::tempdir().unwrap();
+        let keyrings = home.path().join(".dbus-keyrings");
+
+        let (p0, p1) = create_async_socket_pair();
+        let guid = OwnedGuid::from(Guid::generate());
+        let mut client = Client::new(
+            p0.into(),
+            Some(AuthMechanism::CookieSha1),
+            Some(guid.clone()),
+            false,
+            None,
+        );
+        client.set_keyring_dir(keyrings.clone());
+        let mut server = Server::new(
+            p1.into(),
+            guid,
+            Some(Uid::effective().into()),
+            Some(AuthMechanism::CookieSha1),
+            None,
+        )
+        .unwrap();
+        server.set_keyring_dir(keyrings.clone());
+
+        let (client, server) = crate::utils::block_on(join(
+            async move { client.perform().await.unwrap() },
+            async move { server.perform().await.unwrap() },
+        ));
+
+        assert_eq!(client.server_guid, server.server_guid);
+        assert!(keyrings.join(cookies::DEFAULT_CONTEXT).exists());
+    }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_sha1_other_user() {
+        let (p0, p1) = create_async_socket_pair();
+        let client = Client::new(
+            p0.into(),
+            Some(AuthMechanism::CookieSha1),
+            None,
+            false,
+            Some(Uid::effective().as_raw() as usize + 1),
+        );
+        let server = Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            None,
+            Some(AuthMechanism::CookieSha1),
+            None,
+        )
+        .unwrap();
+
+        let (client, _) = crate::utils::block_on(join(client.perform(), server.perform()));
+        assert!(matches!(client, Err(Error::Handshake(_))));
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn unknown_mechanism() {
+        let (mut p0, p1) = create_async_socket_pair();
+        let server = Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            Some(Uid::effective().into()),
+            None,
+            None,
+        )
+        .unwrap();
+
+        crate::utils::block_on(
+            p0.write_all(
+                format!(
+                    "\0AUTH X_UNKNOWN abcd\r\nAUTH EXTERNAL {}\r\nBEGIN\r\n",
+                    hex::encode(sasl_auth_id().unwrap())
+                )
+                .as_bytes(),
+            ),
+        )
+        .unwrap();
+        let server = crate::utils::block_on(server.perform()).unwrap();
+        assert_eq!(
+            server.peer_auth_identity,
+            Some(Uid::effective().to_string())
+        );
+    }
+
+    // A mechanism where the server sends a nonce and the client replies with the nonce and a token.
+    #[derive(Debug)]
+    struct NonceToken {
+        token: &'static str,
+    }
+
+    impl Mechanism for NonceToken {
+        fn name(&self) -> &str {
+            "X_NONCE_TOKEN"
+        }
+
+        fn client(&self) -> Result<Box<dyn ClientMechanism>> {
+            Ok(Box::new(NonceTokenClient(self.token)))
+        }
+
+        fn server(
+            &self,
+            peer: &crate::fdo::ConnectionCredentials,
+        ) -> Result<Box<dyn ServerMechanism>> {
+            assert_eq!(peer.unix_user_id(), Some(Uid::effective().into()));
+
+            Ok(Box::new(NonceTokenServer {
+                token: self.token,
+                nonce: None,
+            }))
+        }
+    }
+
+    #[derive(Debug)]
+    struct NonceTokenClient(&'static str);
+
+    #[async_trait]
+    impl ClientMechanism for NonceTokenClient {
+        async fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+            Ok(Some(b"hello".to_vec()))
+        }
+
+        async fn respond(&mut self, challenge: &[u8]) -> Result<Vec<u8>> {
+            let mut response = challenge.to_vec();
+            response.extend_from_slice(self.0.as_bytes());
+
+            Ok(response)
+        }
+    }
+
+    #[derive(Debug)]
+    struct NonceTokenServer {
+        token: &'static str,
+        nonce: Option<Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl ServerMechanism for NonceTokenServer {
+        async fn step(&mut self, response: Option<&[u8]>) -> Result<ServerStep> {
+            match (&self.nonce, response) {
+                (None, Some(b"hello")) => {
+                    let nonce = b"nonce".to_vec();
+                    self.nonce = Some(nonce.clone());
+
+                    Ok(ServerStep::Challenge(nonce))
+                }
+                (Some(nonce), Some(response))
+                    if *response == [nonce.as_slice(), b"secret"].concat() =>
+                {
+                    Ok(ServerStep::Accept(Some(self.token.into())))
+                }
+                _ => Ok(ServerStep::Reject),
+            }
+        }
+    }
+
+    fn custom_mechanism_pair(
+        client_token: &'static str,
+        server_token: &'static str,
+    ) -> (Client, Server) {
+        let (p0, p1) = create_async_socket_pair();
+        let guid = OwnedGuid::from(Guid::generate());
+        let mut client = Client::new(p0.into(), None, Some(guid.clone()), false, None);
+        client
+            .set_custom_mechanism(Arc::new(NonceToken {
+                token: client_token,
+            }))
+            .unwrap();
+        let mut server =
+            Server::new(p1.into(), guid, Some(Uid::effective().into()), None, None).unwrap();
+        server
+            .set_custom_mechanism(Arc::new(NonceToken {
+                token: server_token,
+            }))
+            .unwrap();
+
+        (client, server)
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn custom_mechanism() {
+        let (client, server) = custom_mechanism_pair("secret", "secret");
+
+        let (client, server) = crate::utils::block_on(join(
+            async move { client.perform().await.unwrap() },
+            async move { server.perform().await.unwrap() },
+        ));
+
+        assert_eq!(client.server_guid, server.server_guid);
+        assert_eq!(client.peer_auth_identity, None);
+        assert_eq!(server.peer_auth_identity.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn custom_mechanism_rejected() {
+        let (client, server) = custom_mechanism_pair("wrong", "secret");
+
+        let (client, _) = crate::utils::block_on(join(client.perform(), server.perform()));
+        assert!(matches!(client, Err(Error::Handshake(_))));
+    }
+
+    #[test]
+    fn custom_mechanism_name() {
+        let (p0, _) = create_async_socket_pair();
+        let mut client = Client::new(p0.into(), None, None, false, None);
+
+        #[derive(Debug)]
+        struct Invalid;
+        impl Mechanism for Invalid {
+            fn name(&self) -> &str {
+                "lowercase"
+            }
+        }
+        assert!(client.set_custom_mechanism(Arc::new(Invalid)).is_err());
+    }
 }
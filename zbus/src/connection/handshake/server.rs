@@ -1,10 +1,22 @@
 use async_trait::async_trait;
+#[cfg(feature = "cookie-sha1")]
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "cookie-sha1")]
+use tracing::warn;
 use tracing::{instrument, trace};
 
-use crate::names::OwnedUniqueName;
+use crate::{fdo::ConnectionCredentials, names::OwnedUniqueName};
 
 use super::{
-    AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake, OwnedGuid, Result,
+    authorization::Authorization, mechanism::validate_name, AuthMechanism, Authenticated,
+    BoxedSplit, Command, Common, Error, Handshake, Mechanism, OwnedGuid, Result, ServerMechanism,
+    ServerStep,
+};
+#[cfg(feature = "cookie-sha1")]
+use super::{
+    cookies::{random_challenge, Cookie, CookieContext, Keyring},
+    sasl_auth_id,
 };
 
 /*
@@ -15,6 +27,7 @@ use super::{
 enum ServerHandshakeStep {
     WaitingForAuth,
     WaitingForData(AuthMechanism),
+    WaitingForCustomData,
     WaitingForBegin,
     Done,
 }
@@ -32,6 +45,17 @@ pub struct Server {
     #[cfg(windows)]
     client_sid: Option<String>,
     unique_name: Option<OwnedUniqueName>,
+    // The cookie and the challenge we sent to the client, in `DBUS_COOKIE_SHA1` authentication.
+    #[cfg(feature = "cookie-sha1")]
+    cookie_challenge: Option<(Cookie, String)>,
+    // The keyring directory for `DBUS_COOKIE_SHA1`, if not the one of the current user.
+    #[cfg(feature = "cookie-sha1")]
+    keyring_dir: Option<PathBuf>,
+    peer_credentials: Arc<ConnectionCredentials>,
+    authorization: Authorization,
+    custom_mechanism: Option<Arc<dyn Mechanism>>,
+    custom_exchange: Option<Box<dyn ServerMechanism>>,
+    peer_auth_identity: Option<String>,
 }
 
 impl Server {
@@ -44,6 +68,17 @@ impl Server {
         unique_name: Option<OwnedUniqueName>,
     ) -> Result<Self> {
         let mechanism = mechanism.unwrap_or_else(|| socket.read().auth_mechanism());
+        let peer_credentials = ConnectionCredentials::default();
+        #[cfg(unix)]
+        let peer_credentials = match client_uid {
+            Some(uid) => peer_credentials.set_unix_user_id(uid),
+            None => peer_credentials,
+        };
+        #[cfg(windows)]
+        let peer_credentials = match &client_sid {
+            Some(sid) => peer_credentials.set_windows_sid(sid.clone()),
+            None => peer_credentials,
+        };
 
         Ok(Server {
             common: Common::new(socket, mechanism),
@@ -54,11 +89,52 @@ impl Server {
             client_sid,
             guid,
             unique_name,
+            #[cfg(feature = "cookie-sha1")]
+            cookie_challenge: None,
+            #[cfg(feature = "cookie-sha1")]
+            keyring_dir: None,
+            peer_credentials: Arc::new(peer_credentials),
+            authorization: Authorization::default(),
+            custom_mechanism: None,
+            custom_exchange: None,
+            peer_auth_identity: None,
         })
     }
 
+    /// Keep `DBUS_COOKIE_SHA1` cookies in `dir`, instead of the keyring directory of the current
+    /// user.
+    #[cfg(all(test, feature = "cookie-sha1"))]
+    pub fn set_keyring_dir(&mut self, dir: PathBuf) {
+        self.keyring_dir = Some(dir);
+    }
+
+    /// Set the full credentials of the peer, passed to custom mechanisms.
+    pub fn set_peer_credentials(&mut self, credentials: ConnectionCredentials) {
+        self.peer_credentials = Arc::new(credentials);
+    }
+
+    /// Set the rules to authorize the client with, once authenticated.
+    pub fn set_authorization(&mut self, authorization: Authorization) {
+        self.authorization = authorization;
+    }
+
+    /// Only accept the given custom mechanism, instead of the built-in one.
+    pub fn set_custom_mechanism(&mut self, mechanism: Arc<dyn Mechanism>) -> Result<()> {
+        validate_name(mechanism.name())?;
+        self.custom_mechanism = Some(mechanism);
+
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn auth_ok(&mut self) -> Result<()> {
+        if !self.authorization.check(&self.peer_credentials).await {
+            trace!("Client is authenticated but not authorized");
+            self.peer_auth_identity = None;
+
+            return self.rejected_error().await;
+        }
+
         let guid = self.guid.clone();
         let cmd = Command::Ok(guid);
         trace!("Sending authentication OK");
@@ -69,9 +145,9 @@ impl Server {
     }
 
     async fn check_external_auth(&mut self, sasl_id: &[u8]) -> Result<()> {
+        let id = std::str::from_utf8(sasl_id)
+            .map_err(|e| Error::Handshake(format!("Invalid ID: {e}")))?;
         let auth_ok = {
-            let id = std::str::from_utf8(sasl_id)
-                .map_err(|e| Error::Handshake(format!("Invalid ID: {e}")))?;
             #[cfg(unix)]
             {
                 let uid = id
@@ -86,6 +162,83 @@ impl Server {
         };
 
         if auth_ok {
+            self.peer_auth_identity = Some(id.to_string());
+            self.auth_ok().await
+        } else {
+            self.rejected_error().await
+        }
+    }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[instrument(skip(self))]
+    async fn send_cookie_challenge(&mut self, sasl_id: &[u8]) -> Result<()> {
+        let id = std::str::from_utf8(sasl_id)
+            .map_err(|e| Error::Handshake(format!("Invalid ID: {e}")))?;
+        // We can only authenticate the user we're running as, since we only have access to its
+        // keyring.
+        let id_ok = sasl_auth_id()? == id && {
+            #[cfg(unix)]
+            {
+                // If we know who's on the other side, it better be the user they claim to be.
+                self.client_uid.map(|u| u.to_string() == id).unwrap_or(true)
+            }
+            #[cfg(windows)]
+            {
+                self.client_sid.as_ref().map(|s| s == id).unwrap_or(true)
+            }
+        };
+        if !id_ok {
+            return self.rejected_error().await;
+        }
+
+        let keyring = Keyring::in_dir(self.keyring_dir.as_deref(), CookieContext::default())?;
+        let context = keyring.context().clone();
+        let cookie =
+            match crate::Task::spawn_blocking(move || keyring.get_or_create(), "cookie keyring")
+                .await
+            {
+                Ok(cookie) => cookie,
+                Err(e) => {
+                    warn!("Failed to get a cookie: {e}");
+
+                    return self.rejected_error().await;
+                }
+            };
+        let challenge = random_challenge();
+        let data = format!("{context} {} {challenge}", cookie.id);
+        trace!("Sending cookie challenge");
+        self.common
+            .write_command(Command::Data(Some(data.into_bytes())))
+            .await?;
+        self.cookie_challenge = Some((cookie, challenge));
+        self.step = ServerHandshakeStep::WaitingForData(AuthMechanism::CookieSha1);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[instrument(skip(self))]
+    async fn check_cookie_response(&mut self, response: &[u8]) -> Result<()> {
+        let (cookie, server_challenge) = self
+            .cookie_challenge
+            .take()
+            .expect("cookie challenge must have been sent");
+        let auth_ok = std::str::from_utf8(response)
+            .ok()
+            .and_then(|response| {
+                let mut fields = response.split_ascii_whitespace();
+                match (fields.next(), fields.next(), fields.next()) {
+                    (Some(client_challenge), Some(hash), None) => {
+                        Some(cookie.hash(&server_challenge, client_challenge) == hash)
+                    }
+                    _ => None,
+                }
+            })
+            .unwrap_or(false);
+
+        if auth_ok {
+            // We only ever authenticate the user we're running as.
+            self.peer_auth_identity = Some(sasl_auth_id()?);
             self.auth_ok().await
         } else {
             self.rejected_error().await
@@ -102,10 +255,19 @@ impl Server {
 
     #[instrument(skip(self))]
     async fn rejected_error(&mut self) -> Result<()> {
-        let cmd = Command::Rejected(self.common.mechanism().as_str().into());
+        let mechanism = match &self.custom_mechanism {
+            Some(custom) => custom.name().to_string().into(),
+            None => self.common.mechanism().as_str().into(),
+        };
+        let cmd = Command::Rejected(mechanism);
         trace!("Sending authentication error");
         self.common.write_command(cmd).await?;
         self.step = ServerHandshakeStep::WaitingForAuth;
+        self.custom_exchange = None;
+        #[cfg(feature = "cookie-sha1")]
+        {
+            self.cookie_challenge = None;
+        }
 
         Ok(())
     }
@@ -116,6 +278,7 @@ impl Server {
         match self.step {
             ServerHandshakeStep::WaitingForAuth => self.handle_auth().await?,
             ServerHandshakeStep::WaitingForData(mech) => self.handle_auth_data(mech).await?,
+            ServerHandshakeStep::WaitingForCustomData => self.handle_custom_auth_data().await?,
             ServerHandshakeStep::WaitingForBegin => self.finalize().await?,
             ServerHandshakeStep::Done => return Ok(true),
         }
@@ -132,8 +295,21 @@ impl Server {
         let reply = self.common.read_command().await?;
         match reply {
             Command::Auth(requested_mech, resp) => {
+                if let Some(custom) = self.custom_mechanism.clone() {
+                    if requested_mech.as_deref() != Some(custom.name()) {
+                        self.rejected_error().await?;
+
+                        return Ok(());
+                    }
+
+                    self.custom_exchange = Some(custom.server(&self.peer_credentials)?);
+                    self.custom_step(resp.as_deref()).await?;
+
+                    return Ok(());
+                }
+
                 let mech = self.common.mechanism();
-                if requested_mech != Some(mech) {
+                if requested_mech.as_deref() != Some(mech.as_str()) {
                     self.rejected_error().await?;
 
                     return Ok(());
@@ -148,6 +324,8 @@ impl Server {
                     Some(sasl_id) => match mech {
                         AuthMechanism::Anonymous => self.auth_ok().await?,
                         AuthMechanism::External => self.check_external_auth(sasl_id).await?,
+                        #[cfg(feature = "cookie-sha1")]
+                        AuthMechanism::CookieSha1 => self.send_cookie_challenge(sasl_id).await?,
                     },
                 }
             }
@@ -169,16 +347,86 @@ impl Server {
         trace!("Waiting for authentication data");
         let reply = self.common.read_command().await?;
         match (mech, reply) {
-            (AuthMechanism::External, Command::Data(None)) => self.auth_ok().await?,
+            (AuthMechanism::External, Command::Data(None)) => {
+                #[cfg(unix)]
+                {
+                    self.peer_auth_identity = self.client_uid.map(|uid| uid.to_string());
+                }
+                #[cfg(windows)]
+                {
+                    self.peer_auth_identity = self.client_sid.clone();
+                }
+                self.auth_ok().await?
+            }
             (AuthMechanism::External, Command::Data(Some(data))) => {
                 self.check_external_auth(&data).await?;
             }
             (AuthMechanism::Anonymous, Command::Data(_)) => self.auth_ok().await?,
+            #[cfg(feature = "cookie-sha1")]
+            (AuthMechanism::CookieSha1, Command::Data(Some(data))) => {
+                if self.cookie_challenge.is_some() {
+                    self.check_cookie_response(&data).await?;
+                } else {
+                    self.send_cookie_challenge(&data).await?;
+                }
+            }
+            #[cfg(feature = "cookie-sha1")]
+            (AuthMechanism::CookieSha1, Command::Cancel | Command::Error(_)) => {
+                trace!("Received CANCEL or ERROR command from the client");
+                self.rejected_error().await?;
+            }
             (_, _) => self.unsupported_command_error().await?,
         }
         Ok(())
     }
 
+    /// Handle the authentication data receiving step of the handshake, for a custom mechanism.
+    #[instrument(skip(self))]
+    async fn handle_custom_auth_data(&mut self) -> Result<()> {
+        assert_eq!(self.step, ServerHandshakeStep::WaitingForCustomData);
+
+        trace!("Waiting for authentication data");
+        let reply = self.common.read_command().await?;
+        match reply {
+            Command::Data(data) => {
+                self.custom_step(Some(data.as_deref().unwrap_or_default()))
+                    .await?
+            }
+            Command::Cancel | Command::Error(_) => {
+                trace!("Received CANCEL or ERROR command from the client");
+                self.rejected_error().await?;
+            }
+            _ => self.unsupported_command_error().await?,
+        }
+
+        Ok(())
+    }
+
+    /// Pass a response from the client to the custom mechanism and act on the outcome.
+    #[instrument(skip(self))]
+    async fn custom_step(&mut self, response: Option<&[u8]>) -> Result<()> {
+        let exchange = self
+            .custom_exchange
+            .as_mut()
+            .expect("custom mechanism exchange must have been started");
+        match exchange.step(response).await? {
+            ServerStep::Challenge(challenge) => {
+                trace!("Sending challenge");
+                let data = (!challenge.is_empty()).then_some(challenge);
+                self.common.write_command(Command::Data(data)).await?;
+                self.step = ServerHandshakeStep::WaitingForCustomData;
+            }
+            ServerStep::Accept(identity) => {
+                self.custom_exchange = None;
+                self.peer_auth_identity = identity;
+                self.auth_ok().await?;
+            }
+            ServerStep::Reject => self.rejected_error().await?,
+        }
+
+        Ok(())
+    }
+
     /// Finalize the handshake.
     #[instrument(skip(self))]
     async fn finalize(&mut self) -> Result<()> {
@@ -238,6 +486,7 @@ impl Handshake for Server {
             #[cfg(unix)]
             already_received_fds: received_fds,
             unique_name: self.unique_name,
+            peer_auth_identity: self.peer_auth_identity,
         })
     }
 }
@@ -0,0 +1,947 @@
+//! Accepting peer-to-peer connections.
+//!
+//! This module is only available when the `p2p` feature is enabled.
+
+#[cfg(not(feature = "tokio"))]
+use async_io::Async;
+use futures_core::Stream;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tracing::{debug, trace};
+
+use zvariant::ObjectPath;
+
+use crate::{
+    address::{
+        transport::{Tcp, TcpTransportFamily, Transport, Unix, UnixSocket},
+        Address,
+    },
+    connection::{
+        handshake::{AuthMechanism, Mechanism},
+        socket::BoxedSplit,
+    },
+    object_server::Interface,
+    timeout, Connection, Error, Guid, OwnedGuid, Result,
+};
+
+/// The length of the nonce of `nonce-tcp:` addresses.
+const NONCE_LEN: usize = 16;
+/// How long clients have to authenticate, unless set through [`Builder::handshake_timeout`].
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A listening socket, accepting peer-to-peer connections from clients.
+///
+/// A listener is bound to a [D-Bus address] and creates a server [`Connection`] for each client
+/// that connects to it and successfully authenticates. All connections share the server GUID of
+/// the listener and the configuration set through its [`Builder`], including the interfaces
+/// registered through [`Builder::serve_at`].
+///
+/// The following transports are supported:
+///
+/// * `unix:path=` and `unix:abstract=` (Linux only), bound as given.
+/// * `unix:dir=` and `unix:tmpdir=`, for which a socket with a unique name is created in the given
+///   directory.
+/// * `unix:runtime=yes`, for which a socket named `bus` is created in the directory specified by
+///   the `XDG_RUNTIME_DIR` environment variable.
+/// * `tcp:`, where a `port` of `0` lets the OS pick a free port.
+/// * `nonce-tcp:`, for which a random nonce is written to the given `noncefile`. Clients must send
+///   the nonce before the authentication handshake.
+///
+/// Since the address the listener is bound to can differ from the one it was created for, clients
+/// should connect to [`Listener::address`]. Unix socket and nonce files created by the listener
+/// are removed when it's dropped.
+///
+/// This type is only available when the `p2p` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::StreamExt;
+/// use zbus::{connection::Listener, interface, Connection};
+/// # use zbus::block_on;
+///
+/// struct Greeter;
+///
+/// #[interface(name = "org.zbus.Greeter1")]
+/// impl Greeter {
+///     fn say_hello(&self, name: &str) -> String {
+///         format!("Hello {name}!")
+///     }
+/// }
+///
+/// # block_on(async {
+/// let dir = std::env::temp_dir();
+/// let listener = Listener::builder(format!("unix:tmpdir={}", dir.display()).as_str())?
+///     .serve_at("/org/zbus/Greeter", Greeter)?
+///     .build()
+///     .await?;
+///
+/// let address = listener.address().clone();
+/// let client = async {
+///     let conn = zbus::connection::Builder::address(address)?
+///         .p2p()
+///         .build()
+///         .await?;
+///     let reply: String = conn
+///         .call_method(
+///             None::<()>,
+///             "/org/zbus/Greeter",
+///             Some("org.zbus.Greeter1"),
+///             "SayHello",
+///             &"Maria",
+///         )
+///         .await?
+///         .body()
+///         .deserialize()?;
+///     assert_eq!(reply, "Hello Maria!");
+///
+///     Ok::<(), zbus::Error>(())
+/// };
+/// let server = async {
+///     let mut incoming = listener.incoming();
+///     let conn: Connection = incoming.next().await.unwrap()?;
+///
+///     Ok::<Connection, zbus::Error>(conn)
+/// };
+/// let (client, server) = futures_util::join!(client, server);
+/// client?;
+/// drop(server?);
+/// #     Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [D-Bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
+#[derive(Debug)]
+pub struct Listener {
+    socket: Socket,
+    address: Address,
+    guid: OwnedGuid,
+    template: super::Builder<'static>,
+    nonce: Option<[u8; NONCE_LEN]>,
+    handshake_timeout: Duration,
+    // Files we created and need to remove on drop.
+    files: Vec<PathBuf>,
+}
+
+impl Listener {
+    /// Create a listener bound to the given [D-Bus address], with the default configuration.
+    ///
+    /// Use [`Listener::builder`] to configure the connections created by the listener.
+    ///
+    /// [D-Bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
+    pub async fn bind<A>(address: A) -> Result<Self>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<Error>,
+    {
+        Self::builder(address)?.build().await
+    }
+
+    /// Create a builder for a listener bound to the given [D-Bus address].
+    ///
+    /// [D-Bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
+    pub fn builder<A>(address: A) -> Result<Builder>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<Error>,
+    {
+        Builder::new(address.try_into().map_err(Into::into)?)
+    }
+
+    /// The address clients can connect to.
+    ///
+    /// This is the concrete address the listener is bound to, including its GUID. For example, for
+    /// a listener created for `unix:tmpdir=/tmp`, this is a `unix:path=` address of the socket
+    /// created in `/tmp`.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The server GUID, shared by all connections created by this listener.
+    pub fn guid(&self) -> &OwnedGuid {
+        &self.guid
+    }
+
+    /// Wait for the next client and create a connection for it.
+    ///
+    /// The authentication handshake is performed before this method returns, so the next client
+    /// can't be accepted until then. Use [`Listener::incoming`] to handle multiple clients
+    /// concurrently.
+    ///
+    /// If the client doesn't complete the handshake within the timeout set through
+    /// [`Builder::handshake_timeout`], [`Error::Timeout`] is returned.
+    pub async fn accept(&self) -> Result<Connection> {
+        let stream = self.accept_stream().await?;
+
+        self.establish(stream).await
+    }
+
+    /// A stream of connections from clients.
+    ///
+    /// Unlike [`Listener::accept`], the authentication handshakes of all clients are performed
+    /// concurrently, each one being subject to the handshake timeout. The stream yields an error
+    /// for each client that fails to connect, so it's typically a good idea to log and ignore
+    /// errors, rather than stopping on the first one.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming {
+            listener: self,
+            accept: None,
+            handshakes: vec![],
+        }
+    }
+
+    async fn accept_stream(&self) -> Result<Accepted> {
+        let stream = match &self.socket {
+            #[cfg(unix)]
+            Socket::Unix(listener) => Accepted::Unix(listener.accept().await?.0),
+            Socket::Tcp(listener) => Accepted::Tcp(listener.accept().await?.0),
+        };
+        trace!("Accepted a client on {}", self.address);
+
+        Ok(stream)
+    }
+
+    async fn establish(&self, stream: Accepted) -> Result<Connection> {
+        timeout(self.handshake(stream), self.handshake_timeout).await
+    }
+
+    async fn handshake(&self, stream: Accepted) -> Result<Connection> {
+        let socket: BoxedSplit = match stream {
+            #[cfg(unix)]
+            Accepted::Unix(stream) => stream.into(),
+            #[allow(unused_mut)]
+            Accepted::Tcp(mut stream) => {
+                if let Some(nonce) = &self.nonce {
+                    let mut received = [0; NONCE_LEN];
+                    #[cfg(not(feature = "tokio"))]
+                    futures_lite::AsyncReadExt::read_exact(&mut stream, &mut received).await?;
+                    #[cfg(feature = "tokio")]
+                    tokio::io::AsyncReadExt::read_exact(&mut stream, &mut received).await?;
+
+                    if received != *nonce {
+                        return Err(Error::Handshake("Client sent an invalid nonce".into()));
+                    }
+                }
+
+                stream.into()
+            }
+        };
+
+        self.template.for_socket(socket).build().await
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        for file in &self.files {
+            if let Err(e) = std::fs::remove_file(file) {
+                debug!("Failed to remove `{}`: {e}", file.display());
+            }
+        }
+    }
+}
+
+/// A stream of connections from clients, created by [`Listener::incoming`].
+#[must_use = "streams do nothing unless polled"]
+pub struct Incoming<'l> {
+    listener: &'l Listener,
+    accept: Option<Pin<Box<dyn Future<Output = Result<Accepted>> + Send + 'l>>>,
+    handshakes: Vec<Pin<Box<dyn Future<Output = Result<Connection>> + Send + 'l>>>,
+}
+
+impl Stream for Incoming<'_> {
+    type Item = Result<Connection>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let listener = this.listener;
+
+        // Accept as many clients as are waiting, and start their handshakes.
+        loop {
+            let accept = this
+                .accept
+                .get_or_insert_with(|| Box::pin(listener.accept_stream()));
+            match accept.as_mut().poll(cx) {
+                Poll::Ready(res) => {
+                    this.accept = None;
+                    match res {
+                        Ok(stream) => this.handshakes.push(Box::pin(listener.establish(stream))),
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        for i in 0..this.handshakes.len() {
+            if let Poll::Ready(res) = this.handshakes[i].as_mut().poll(cx) {
+                drop(this.handshakes.swap_remove(i));
+
+                return Poll::Ready(Some(res));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl std::fmt::Debug for Incoming<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Incoming")
+            .field("listener", &self.listener)
+            .field("handshakes", &self.handshakes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A builder for [`Listener`].
+///
+/// The configuration applies to all the connections created by the listener. See the methods of
+/// the same name on [`super::Builder`] for details.
+#[derive(Debug)]
+#[must_use]
+pub struct Builder {
+    address: Address,
+    guid: Option<OwnedGuid>,
+    handshake_timeout: Duration,
+    template: super::Builder<'static>,
+}
+
+impl Builder {
+    fn new(address: Address) -> Result<Self> {
+        let guid = address.guid().map(|g| g.to_owned().into());
+
+        Ok(Self {
+            address,
+            guid,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+            template: super::Builder::listener_template(),
+        })
+    }
+
+    /// Use the given server GUID, instead of a generated one.
+    ///
+    /// This takes precedence over the GUID of the address, if any.
+    pub fn guid<G>(mut self, guid: G) -> Result<Self>
+    where
+        G: TryInto<Guid<'static>>,
+        G::Error: Into<Error>,
+    {
+        self.guid = Some(guid.try_into().map_err(Into::into)?.into());
+
+        Ok(self)
+    }
+
+    /// Set how long clients have to authenticate, once they connected.
+    ///
+    /// Clients that take longer are disconnected. The default is 30 seconds.
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+
+        self
+    }
+
+    /// Specify the mechanism to accept from clients.
+    pub fn auth_mechanism(mut self, auth_mechanism: AuthMechanism) -> Self {
+        self.template = self.template.auth_mechanism(auth_mechanism);
+
+        self
+    }
+
+    /// Specify a custom mechanism to accept from clients.
+    pub fn custom_auth_mechanism<M>(mut self, mechanism: M) -> Self
+    where
+        M: Mechanism + 'static,
+    {
+        self.template = self.template.custom_auth_mechanism(mechanism);
+
+        self
+    }
+
+    /// Only accept clients running as one of the given users.
+    #[cfg(unix)]
+    pub fn allowed_unix_user_ids<I>(mut self, uids: I) -> Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        self.template = self.template.allowed_unix_user_ids(uids);
+
+        self
+    }
+
+    /// Set a callback to authorize clients.
+    pub fn authorize<F, Fut>(mut self, authorize: F) -> Self
+    where
+        F: Fn(Arc<crate::fdo::ConnectionCredentials>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.template = self.template.authorize(authorize);
+
+        self
+    }
+
+    /// Set the capacity of the main (unfiltered) queue of each connection.
+    pub fn max_queued(mut self, max: usize) -> Self {
+        self.template = self.template.max_queued(max);
+
+        self
+    }
+
+    /// Set the default timeout for method call replies of each connection.
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.template = self.template.method_timeout(timeout);
+
+        self
+    }
+
+    /// Enable or disable the internal executor thread of each connection.
+    pub fn internal_executor(mut self, enabled: bool) -> Self {
+        self.template = self.template.internal_executor(enabled);
+
+        self
+    }
+
+    /// Register a D-Bus [`Interface`] to be served at a given path, on each connection.
+    ///
+    /// Note that the same instance of `iface` is shared by all connections.
+    pub fn serve_at<P, I>(mut self, path: P, iface: I) -> Result<Self>
+    where
+        I: Interface,
+        P: TryInto<ObjectPath<'static>>,
+        P::Error: Into<Error>,
+    {
+        self.template = self.template.serve_at(path, iface)?;
+
+        Ok(self)
+    }
+
+    /// Let the peers become monitors of all the connections of the listener.
+    ///
+    /// See [`super::Builder::monitoring`] for details.
+    pub fn monitoring(mut self, monitoring: crate::fdo::Monitoring) -> Result<Self> {
+        self.template = self.template.monitoring(monitoring)?;
+
+        Ok(self)
+    }
+
+    /// Bind the listener, consuming the builder.
+    pub async fn build(mut self) -> Result<Listener> {
+        let guid = self.guid.take().unwrap_or_else(|| Guid::generate().into());
+        let mut files = vec![];
+        let mut nonce = None;
+
+        let (socket, transport) = match self.address.transport() {
+            #[cfg(unix)]
+            Transport::Unix(unix) => {
+                let (listener, unix) = bind_unix(unix, &mut files)?;
+
+                (Socket::Unix(listener), Transport::Unix(unix))
+            }
+            Transport::Tcp(tcp) => {
+                let (listener, tcp) = bind_tcp(tcp.clone()).await?;
+                if let Some(path) = tcp.nonce_file() {
+                    let path = path_from_bytes(path)?;
+                    let bytes = rand::random::<[u8; NONCE_LEN]>();
+                    write_nonce_file(&path, &bytes)?;
+                    files.push(path);
+                    nonce = Some(bytes);
+                }
+
+                (Socket::Tcp(listener), Transport::Tcp(tcp))
+            }
+            _ => return Err(Error::Unsupported),
+        };
+        let address = Address::new(transport).set_guid(guid.clone())?;
+        debug!("Listening on {address}");
+
+        let template = self.template.server(guid.clone())?;
+
+        Ok(Listener {
+            socket,
+            address,
+            guid,
+            template,
+            nonce,
+            handshake_timeout: self.handshake_timeout,
+            files,
+        })
+    }
+}
+
+#[cfg(all(unix, not(feature = "tokio")))]
+type UnixListener = Async<std::os::unix::net::UnixListener>;
+#[cfg(all(unix, feature = "tokio"))]
+type UnixListener = tokio::net::UnixListener;
+
+#[cfg(not(feature = "tokio"))]
+type TcpListener = Async<std::net::TcpListener>;
+#[cfg(feature = "tokio")]
+type TcpListener = tokio::net::TcpListener;
+
+#[derive(Debug)]
+enum Socket {
+    #[cfg(unix)]
+    Unix(UnixListener),
+    Tcp(TcpListener),
+}
+
+enum Accepted {
+    #[cfg(all(unix, not(feature = "tokio")))]
+    Unix(Async<std::os::unix::net::UnixStream>),
+    #[cfg(all(unix, feature = "tokio"))]
+    Unix(tokio::net::UnixStream),
+    #[cfg(not(feature = "tokio"))]
+    Tcp(Async<std::net::TcpStream>),
+    #[cfg(feature = "tokio")]
+    Tcp(tokio::net::TcpStream),
+}
+
+#[cfg(unix)]
+fn bind_unix(unix: &Unix, files: &mut Vec<PathBuf>) -> Result<(UnixListener, Unix)> {
+    let socket = unix.listen_socket(crate::address::transport::runtime_dir().as_deref())?;
+    let addr = match &socket {
+        UnixSocket::File(path) => std::os::unix::net::SocketAddr::from_pathname(path)?,
+        #[cfg(target_os = "linux")]
+        UnixSocket::Abstract(name) => {
+            use std::os::linux::net::SocketAddrExt;
+
+            std::os::unix::net::SocketAddr::from_abstract_name(name.as_encoded_bytes())?
+        }
+        _ => return Err(Error::Unsupported),
+    };
+    let listener = std::os::unix::net::UnixListener::bind_addr(&addr)?;
+    if let UnixSocket::File(path) = &socket {
+        files.push(path.clone());
+    }
+    listener.set_nonblocking(true)?;
+    #[cfg(not(feature = "tokio"))]
+    let listener = Async::new(listener)?;
+    #[cfg(feature = "tokio")]
+    let listener = tokio::net::UnixListener::from_std(listener)?;
+
+    Ok((listener, Unix::new(socket)))
+}
+
+async fn bind_tcp(tcp: Tcp) -> Result<(TcpListener, Tcp)> {
+    let (listener, tcp) = crate::Task::spawn_blocking(
+        move || -> Result<_> {
+            let addrs = std::net::ToSocketAddrs::to_socket_addrs(&(tcp.host(), tcp.port()))?
+                .filter(|a| match tcp.family() {
+                    Some(TcpTransportFamily::Ipv4) => a.is_ipv4(),
+                    Some(TcpTransportFamily::Ipv6) => a.is_ipv6(),
+                    None => true,
+                });
+
+            let mut last_err = Error::Address(format!("Failed to resolve `{}`", tcp.host()));
+            for addr in addrs {
+                match std::net::TcpListener::bind(addr) {
+                    Ok(listener) => {
+                        listener.set_nonblocking(true)?;
+                        // The port might have been picked by the OS.
+                        let bound = listener.local_addr()?;
+                        let family = match bound {
+                            SocketAddr::V4(_) => TcpTransportFamily::Ipv4,
+                            SocketAddr::V6(_) => TcpTransportFamily::Ipv6,
+                        };
+                        let tcp = Tcp::new(tcp.host(), bound.port())
+                            .set_family(Some(family))
+                            .set_nonce_file(tcp.nonce_file().map(Into::into));
+
+                        return Ok((listener, tcp));
+                    }
+                    Err(e) => last_err = e.into(),
+                }
+            }
+
+            Err(last_err)
+        },
+        "bind tcp",
+    )
+    .await?;
+    #[cfg(not(feature = "tokio"))]
+    let listener = Async::new(listener)?;
+    #[cfg(feature = "tokio")]
+    let listener = TcpListener::from_std(listener)?;
+
+    Ok((listener, tcp))
+}
+
+fn path_from_bytes(path: &[u8]) -> Result<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        Ok(std::ffi::OsStr::from_bytes(path).into())
+    }
+
+    #[cfg(windows)]
+    std::str::from_utf8(path)
+        .map(Into::into)
+        .map_err(|_| Error::Address("nonce file path is invalid UTF-8".to_owned()))
+}
+
+fn write_nonce_file(path: &std::path::Path, nonce: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(nonce)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+    use test_log::test;
+
+    use super::Listener;
+    use crate::{
+        address::{
+            transport::{Transport, UnixSocket},
+            Address,
+        },
+        connection::Builder,
+        Connection, Error, Result,
+    };
+
+    struct Counter(AtomicU32);
+
+    #[crate::interface(name = "org.zbus.Counter")]
+    impl Counter {
+        fn increment(&self) -> u32 {
+            self.0.fetch_add(1, Ordering::SeqCst) + 1
+        }
+    }
+
+    async fn increment(conn: &Connection) -> Result<u32> {
+        conn.call_method(
+            None::<()>,
+            "/org/zbus/Counter",
+            Some("org.zbus.Counter"),
+            "Increment",
+            &(),
+        )
+        .await?
+        .body()
+        .deserialize()
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn unix_tmpdir() {
+        crate::utils::block_on(test_unix_tmpdir()).unwrap();
+    }
+
+    async fn test_unix_tmpdir() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = Listener::builder(format!("unix:tmpdir={}", dir.path().display()).as_str())?
+            .serve_at("/org/zbus/Counter", Counter(AtomicU32::new(0)))?
+            .build()
+            .await?;
+        let address = listener.address().clone();
+        assert_eq!(address.guid(), Some(&listener.guid().inner().clone()));
+        let path = match address.transport() {
+            Transport::Unix(unix) => match unix.path() {
+                UnixSocket::File(path) => path.clone(),
+                _ => panic!("unexpected socket: {address}"),
+            },
+            _ => panic!("unexpected transport: {address}"),
+        };
+        assert!(path.starts_with(dir.path()));
+
+        let server = async {
+            let servers: Vec<Connection> = listener
+                .incoming()
+                .take(2)
+                .map(|res| res.unwrap())
+                .collect()
+                .await;
+
+            Ok::<_, Error>(servers)
+        };
+        let clients = async {
+            let client1 = Builder::address(address.clone())?.p2p().build().await?;
+            let client2 = Builder::address(address.clone())?.p2p().build().await?;
+
+            // The interface instance is shared by the two connections.
+            let mut counts = vec![increment(&client1).await?, increment(&client2).await?];
+            counts.sort();
+            assert_eq!(counts, [1, 2]);
+
+            Ok::<_, Error>((client1, client2))
+        };
+        let (servers, clients) = futures_util::try_join!(server, clients)?;
+        for server in &servers {
+            assert_eq!(server.server_guid(), listener.guid());
+        }
+        assert_eq!(clients.0.server_guid(), listener.guid());
+
+        drop(listener);
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn monitoring() {
+        crate::utils::block_on(test_monitoring()).unwrap();
+    }
+
+    async fn test_monitoring() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let monitoring = crate::fdo::Monitoring::new();
+        let listener = Listener::builder(format!("unix:tmpdir={}", dir.path().display()).as_str())?
+            .serve_at("/org/zbus/Counter", Counter(AtomicU32::new(0)))?
+            .monitoring(monitoring.clone())?
+            .build()
+            .await?;
+        let address = listener.address().clone();
+
+        let (_server1, monitor) = futures_util::try_join!(
+            listener.accept(),
+            Builder::address(address.clone())?.p2p().build(),
+        )?;
+        let rule = crate::MatchRule::builder()
+            .msg_type(crate::message::Type::MethodCall)
+            .build();
+        // Created before the call, so it deterministically sees its reply first.
+        let mut stream = crate::MessageStream::from(&monitor);
+        monitor
+            .call_method(
+                None::<()>,
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus.Monitoring"),
+                "BecomeMonitor",
+                &(vec![rule], 0u32),
+            )
+            .await?;
+        assert_eq!(monitoring.monitor_count(), 1);
+        let reply = stream.next().await.unwrap()?;
+        assert_eq!(reply.message_type(), crate::message::Type::MethodReturn);
+
+        let (_server2, client) = futures_util::try_join!(
+            listener.accept(),
+            Builder::address(address.clone())?.p2p().build(),
+        )?;
+        assert_eq!(increment(&client).await?, 1);
+
+        // Only the method call matches the rule, not its reply.
+        let msg = stream.next().await.unwrap()?;
+        let header = msg.header();
+        assert_eq!(header.message_type(), crate::message::Type::MethodCall);
+        assert_eq!(header.member().unwrap(), "Increment");
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn monitoring_access_denied() {
+        crate::utils::block_on(test_monitoring_access_denied()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_monitoring_access_denied() -> Result<()> {
+        let monitoring = crate::fdo::Monitoring::new();
+        // The user of TCP peers is unknown on Unix.
+        let listener = Listener::builder("tcp:host=127.0.0.1,port=0")?
+            .monitoring(monitoring.clone())?
+            .build()
+            .await?;
+        let address = listener.address().clone();
+
+        let (_server, monitor) =
+            futures_util::try_join!(listener.accept(), Builder::address(address)?.p2p().build())?;
+        let err = monitor
+            .call_method(
+                None::<()>,
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus.Monitoring"),
+                "BecomeMonitor",
+                &(Vec::<&str>::new(), 0u32),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                Error::MethodError(name, _, _) if *name == "org.freedesktop.DBus.Error.AccessDenied"
+            ),
+            "{err}"
+        );
+        assert_eq!(monitoring.monitor_count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn unix_listenable_addresses() {
+        crate::utils::block_on(test_unix_listenable_addresses()).unwrap();
+    }
+
+    async fn test_unix_listenable_addresses() -> Result<()> {
+        fn socket_path(listener: &Listener) -> std::path::PathBuf {
+            match listener.address().transport() {
+                Transport::Unix(unix) => match unix.path() {
+                    UnixSocket::File(path) => path.clone(),
+                    _ => panic!("unexpected socket: {}", listener.address()),
+                },
+                _ => panic!("unexpected transport: {}", listener.address()),
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir_address = format!("unix:dir={}", dir.path().display());
+        let listener1 = Listener::bind(dir_address.as_str()).await?;
+        let listener2 = Listener::bind(dir_address.as_str()).await?;
+        let (path1, path2) = (socket_path(&listener1), socket_path(&listener2));
+        assert_ne!(path1, path2);
+        for path in [path1, path2] {
+            assert_eq!(path.parent(), Some(dir.path()));
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert!(name.starts_with("dbus-") && name.len() == 15, "{name}");
+        }
+
+        // Clients can't know the name of the socket.
+        let err = Builder::address(dir_address.as_str())?
+            .p2p()
+            .build()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Unsupported), "{err}");
+
+        // `unix:runtime=yes` is resolved to the `bus` socket of the user runtime directory.
+        let runtime = Address::try_from("unix:runtime=yes")?;
+        let Transport::Unix(unix) = runtime.transport() else {
+            panic!("unexpected transport: {runtime}");
+        };
+        let runtime_dir = std::path::Path::new("/run/user/1000");
+        assert_eq!(
+            unix.listen_socket(Some(runtime_dir))?,
+            UnixSocket::File(runtime_dir.join("bus"))
+        );
+        assert!(unix.listen_socket(None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn tcp() {
+        crate::utils::block_on(test_tcp()).unwrap();
+    }
+
+    async fn test_tcp() -> Result<()> {
+        let listener = Listener::bind("tcp:host=127.0.0.1,port=0").await?;
+        let address = listener.address().clone();
+        match address.transport() {
+            Transport::Tcp(tcp) => assert_ne!(tcp.port(), 0),
+            _ => panic!("unexpected transport: {address}"),
+        }
+
+        let (server, client) =
+            futures_util::try_join!(listener.accept(), Builder::address(address)?.p2p().build(),)?;
+        assert_eq!(server.server_guid(), client.server_guid());
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn handshake_timeout() {
+        crate::utils::block_on(test_handshake_timeout()).unwrap();
+    }
+
+    async fn test_handshake_timeout() -> Result<()> {
+        let listener = Listener::builder("tcp:host=127.0.0.1,port=0")?
+            .handshake_timeout(Duration::from_millis(100))
+            .build()
+            .await?;
+        let address = listener.address().clone();
+        let port = match address.transport() {
+            Transport::Tcp(tcp) => tcp.port(),
+            _ => panic!("unexpected transport: {address}"),
+        };
+
+        // A client that never authenticates doesn't hold the listener up forever.
+        let _stalled = std::net::TcpStream::connect(("127.0.0.1", port))?;
+        let err = listener.accept().await.unwrap_err();
+        assert!(matches!(err, Error::Timeout), "{err}");
+
+        // Nor does it prevent other clients from connecting concurrently.
+        let _stalled = std::net::TcpStream::connect(("127.0.0.1", port))?;
+        let mut incoming = listener.incoming();
+        let (server, client) = futures_util::join!(
+            async {
+                loop {
+                    if let Ok(conn) = incoming.next().await.unwrap() {
+                        break conn;
+                    }
+                }
+            },
+            Builder::address(address)?.p2p().build(),
+        );
+        assert_eq!(server.server_guid(), client?.server_guid());
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn nonce_tcp() {
+        crate::utils::block_on(test_nonce_tcp()).unwrap();
+    }
+
+    async fn test_nonce_tcp() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let nonce_file = dir.path().join("nonce");
+        let listener = Listener::bind(
+            format!(
+                "nonce-tcp:host=127.0.0.1,port=0,noncefile={}",
+                nonce_file.display()
+            )
+            .as_str(),
+        )
+        .await?;
+        let address = listener.address().clone();
+        assert_eq!(std::fs::read(&nonce_file)?.len(), super::NONCE_LEN);
+
+        let (server, client) = futures_util::try_join!(
+            listener.accept(),
+            Builder::address(address.clone())?.p2p().build(),
+        )?;
+        assert_eq!(server.server_guid(), client.server_guid());
+
+        // A client not sending the right nonce is rejected.
+        let port = match address.transport() {
+            Transport::Tcp(tcp) => tcp.port(),
+            _ => panic!("unexpected transport: {address}"),
+        };
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port))?;
+        std::io::Write::write_all(&mut stream, &[0; super::NONCE_LEN])?;
+        assert!(matches!(listener.accept().await, Err(Error::Handshake(_))));
+
+        drop(listener);
+        assert!(!nonce_file.exists());
+
+        Ok(())
+    }
+}
@@ -8,8 +8,12 @@ use std::{
     io::{self, ErrorKind},
     num::NonZeroU32,
     pin::Pin,
-    sync::{Arc, OnceLock, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock, Weak,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, info_span, instrument, trace, trace_span, warn, Instrument};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
@@ -19,6 +23,7 @@ use futures_core::Future;
 use futures_lite::StreamExt;
 
 use crate::{
+    abstractions::maybe_timeout,
     async_lock::{Mutex, Semaphore, SemaphorePermit},
     fdo::{ConnectionCredentials, ReleaseNameReply, RequestNameFlags, RequestNameReply},
     is_flatpak,
@@ -38,21 +43,46 @@ use socket_reader::SocketReader;
 
 pub mod handshake;
 pub use handshake::AuthMechanism;
+
+#[cfg(feature = "p2p")]
+pub mod listener;
 use handshake::Authenticated;
+#[cfg(feature = "p2p")]
+pub use listener::Listener;
+
+mod reconnect;
+use reconnect::{Latest, Reconnector};
+
+mod state;
+use state::StateChanges;
+pub use state::{StateChange, StateChangeStream};
+
+mod stats;
+pub use stats::{MessageStats, QueueStats, Stats};
+
+mod tap;
+pub use tap::Direction;
+pub(crate) use tap::Tap;
+
+pub mod capture;
 
 const DEFAULT_MAX_QUEUED: usize = 64;
 const DEFAULT_MAX_METHOD_RETURN_QUEUED: usize = 8;
+const DEFAULT_MAX_STATE_CHANGES_QUEUED: usize = 8;
 
 /// Inner state shared by Connection and WeakConnection
 #[derive(Debug)]
 pub(crate) struct ConnectionInner {
-    server_guid: OwnedGuid,
+    server_guid: Latest<OwnedGuid>,
     #[cfg(unix)]
     cap_unix_fd: bool,
     #[cfg(feature = "p2p")]
     bus_conn: bool,
-    unique_name: OnceLock<OwnedUniqueName>,
-    registered_names: Mutex<HashMap<WellKnownName<'static>, NameStatus>>,
+    #[cfg(feature = "p2p")]
+    peer_auth_identity: Option<String>,
+    unique_name: Latest<OwnedUniqueName>,
+    registered_names: Mutex<HashMap<WellKnownName<'static>, RegisteredName>>,
+    method_timeout: Option<Duration>,
 
     activity_event: Arc<Event>,
     socket_write: Mutex<Box<dyn socket::WriteHalf>>,
@@ -62,7 +92,18 @@ pub(crate) struct ConnectionInner {
 
     // Socket reader task
     #[allow(unused)]
-    socket_reader_task: OnceLock<Task<()>>,
+    socket_reader_task: OnceLock<Task<Error>>,
+    // The task supervising the socket reader task, if the connection is to be re-established.
+    #[allow(unused)]
+    reconnection_task: OnceLock<Task<()>>,
+    auto_reconnect: bool,
+    // Set once the connection is closed through `Connection::close`.
+    closed: AtomicBool,
+
+    state_changes: StateChanges,
+
+    stats: Arc<stats::Counters>,
+    taps: Arc<tap::Taps>,
 
     pub(crate) msg_receiver: InactiveReceiver<Result<Message>>,
     pub(crate) method_return_receiver: InactiveReceiver<Result<Message>>,
@@ -76,6 +117,12 @@ pub(crate) struct ConnectionInner {
     drop_event: Event,
 }
 
+impl ConnectionInner {
+    fn emit_state_change(&self, change: StateChange) {
+        self.state_changes.emit(change);
+    }
+}
+
 impl Drop for ConnectionInner {
     fn drop(&mut self) {
         // Notify anyone waiting that the connection is going away. Since we're being dropped, it's
@@ -218,6 +265,7 @@ pub struct Connection {
 pub(crate) struct PendingMethodCall {
     stream: Option<MessageStream>,
     serial: NonZeroU32,
+    pending_reply: Option<stats::PendingReply>,
 }
 
 impl Future for PendingMethodCall {
@@ -260,12 +308,14 @@ impl OrderedFuture for PendingMethodCall {
                             _ => continue,
                         };
                         this.stream = None;
+                        this.pending_reply = None;
                         return Poll::Ready(Some((ordering, res)));
                     }
                     Poll::Ready(PollResult::Item {
                         data: Err(e),
                         ordering,
                     }) => {
+                        this.pending_reply = None;
                         return Poll::Ready(Some((ordering, Err(e))));
                     }
 
@@ -290,11 +340,23 @@ impl Connection {
         if !msg.data().fds().is_empty() && !self.inner.cap_unix_fd {
             return Err(Error::Unsupported);
         }
+        // Replies to intercepted calls are sent once the interceptors are done with them.
+        if let Some(server) = self.inner.object_server.get() {
+            if server.divert_reply(msg) {
+                return Ok(());
+            }
+        }
 
         self.inner.activity_event.notify(usize::MAX);
         let mut write = self.inner.socket_write.lock().await;
 
-        write.send_message(msg).await
+        // Account for the message before the peer can possibly react to it.
+        self.inner.stats.record_outgoing(msg);
+        write.send_message(msg).await?;
+        drop(write);
+        self.inner.taps.message(msg, Direction::Outgoing);
+
+        Ok(())
     }
 
     /// Send a method call.
@@ -303,6 +365,9 @@ impl Connection {
     ///
     /// On successful reply, an `Ok(Message)` is returned. On error, an `Err` is returned. D-Bus
     /// error replies are returned as [`Error::MethodError`].
+    ///
+    /// If a default method timeout was set through [`Builder::method_timeout`] and no reply is
+    /// received in time, [`Error::Timeout`] is returned.
     pub async fn call_method<'d, 'p, 'i, 'm, D, P, I, M, B>(
         &self,
         destination: Option<D>,
@@ -322,19 +387,97 @@ impl Connection {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        self.call_method_raw(
+        self.call_method_with_timeout(
             destination,
             path,
             interface,
             method_name,
-            BitFlags::empty(),
+            self.method_timeout(),
             body,
         )
-        .await?
-        .expect("no reply")
         .await
     }
 
+    /// Send a method call, with the given reply timeout.
+    ///
+    /// This is the same as [`Connection::call_method`], except that `timeout` is used instead of
+    /// the default method timeout of the connection. If `timeout` is `None`, the call will wait
+    /// for the reply indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// If no reply is received within `timeout`, [`Error::Timeout`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # zbus::block_on(async {
+    /// use std::time::Duration;
+    /// use zbus::Connection;
+    ///
+    /// let connection = Connection::session().await?;
+    ///
+    /// let reply_body = connection
+    ///     .call_method_with_timeout(
+    ///         Some("org.freedesktop.DBus"),
+    ///         "/org/freedesktop/DBus",
+    ///         Some("org.freedesktop.DBus"),
+    ///         "GetId",
+    ///         Some(Duration::from_secs(5)),
+    ///         &(),
+    ///     )
+    ///     .await?
+    ///     .body();
+    ///
+    /// let id: &str = reply_body.deserialize()?;
+    /// println!("Unique ID of the bus: {}", id);
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn call_method_with_timeout<'d, 'p, 'i, 'm, D, P, I, M, B>(
+        &self,
+        destination: Option<D>,
+        path: P,
+        interface: Option<I>,
+        method_name: M,
+        timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<Message>
+    where
+        D: TryInto<BusName<'d>>,
+        P: TryInto<ObjectPath<'p>>,
+        I: TryInto<InterfaceName<'i>>,
+        M: TryInto<MemberName<'m>>,
+        D::Error: Into<Error>,
+        P::Error: Into<Error>,
+        I::Error: Into<Error>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        let reply = self
+            .call_method_raw(
+                destination,
+                path,
+                interface,
+                method_name,
+                BitFlags::empty(),
+                body,
+            )
+            .await?
+            .expect("no reply");
+
+        maybe_timeout(reply, timeout).await
+    }
+
+    /// The default timeout for method call replies.
+    ///
+    /// This is `None` unless set through [`Builder::method_timeout`]. When it's `None`, method
+    /// calls made on this connection wait indefinitely for replies.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner.method_timeout
+    }
+
     /// Send a method call.
     ///
     /// Send the given message, which must be a method call, over the connection and return an
@@ -368,7 +511,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut builder = Message::method_call(path, method_name)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             builder = builder.sender(sender)?
         }
         if let Some(destination) = destination {
@@ -394,7 +537,11 @@ impl Connection {
         if flags.contains(Flags::NoReplyExpected) {
             Ok(None)
         } else {
-            Ok(Some(PendingMethodCall { stream, serial }))
+            Ok(Some(PendingMethodCall {
+                stream,
+                serial,
+                pending_reply: Some(self.inner.stats.pending_reply()),
+            }))
         }
     }
 
@@ -423,7 +570,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut b = Message::signal(path, interface, signal_name)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             b = b.sender(sender)?;
         }
         if let Some(destination) = destination {
@@ -445,7 +592,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut b = Message::method_return(call)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             b = b.sender(sender)?;
         }
         let m = b.build(body)?;
@@ -470,7 +617,7 @@ impl Connection {
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut b = Message::error(call, error_name)?;
-        if let Some(sender) = self.unique_name() {
+        if let Some(sender) = self.current_unique_name() {
             b = b.sender(sender)?;
         }
         let m = b.build(body)?;
@@ -621,14 +768,23 @@ impl Connection {
         // doesn't end up accessing the name entry before it's inserted.
         let mut names = self.inner.registered_names.lock().await;
 
-        match names.get(&well_known_name) {
+        match names.get(&well_known_name).map(|name| &name.status) {
             Some(NameStatus::Owner(_)) => return Ok(RequestNameReply::AlreadyOwner),
             Some(NameStatus::Queued(_)) => return Ok(RequestNameReply::InQueue),
             None => (),
         }
 
         if !self.is_bus() {
-            names.insert(well_known_name.to_owned(), NameStatus::Owner(None));
+            names.insert(
+                well_known_name.to_owned(),
+                RegisteredName {
+                    status: NameStatus::Owner(None),
+                    flags,
+                },
+            );
+            drop(names);
+            self.inner
+                .emit_state_change(StateChange::NameAcquired(well_known_name.into()));
 
             return Ok(RequestNameReply::PrimaryOwner);
         }
@@ -679,6 +835,9 @@ impl Connection {
                                         well_known_name
                                     );
                                     inner.registered_names.lock().await.remove(&well_known_name);
+                                    inner.emit_state_change(StateChange::NameLost(
+                                        well_known_name.clone().into(),
+                                    ));
 
                                     break;
                                 }
@@ -721,11 +880,15 @@ impl Connection {
                                 Some(signal) => match signal {
                                     Ok(_) => {
                                         let mut names = inner.registered_names.lock().await;
-                                        if let Some(status) = names.get_mut(&well_known_name) {
+                                        if let Some(name) = names.get_mut(&well_known_name) {
                                             let task = name_lost_fut.map(|fut| {
                                                 inner.executor.spawn(fut, &lost_task_name)
                                             });
-                                            *status = NameStatus::Owner(task);
+                                            name.status = NameStatus::Owner(task);
+                                            drop(names);
+                                            inner.emit_state_change(StateChange::NameAcquired(
+                                                well_known_name.clone().into(),
+                                            ));
 
                                             break;
                                         }
@@ -755,8 +918,15 @@ impl Connection {
             }
             RequestNameReply::Exists => return Err(Error::NameTaken),
         };
-
-        names.insert(well_known_name.to_owned(), status);
+        let acquired = matches!(status, NameStatus::Owner(_));
+
+        names.insert(well_known_name.to_owned(), RegisteredName { status, flags });
+        // Only notify once the name is registered, for the ownership to be reflected.
+        drop(names);
+        if acquired {
+            self.inner
+                .emit_state_change(StateChange::NameAcquired(well_known_name.to_owned().into()));
+        }
 
         Ok(reply)
     }
@@ -777,25 +947,33 @@ impl Connection {
         let well_known_name: WellKnownName<'w> = well_known_name.try_into().map_err(Into::into)?;
         let mut names = self.inner.registered_names.lock().await;
         // FIXME: Should be possible to avoid cloning/allocation here
-        if names.remove(&well_known_name.to_owned()).is_none() {
-            return Ok(false);
+        let owned = match names.remove(&well_known_name.to_owned()) {
+            Some(name) => matches!(name.status, NameStatus::Owner(_)),
+            None => return Ok(false),
         };
 
-        if !self.is_bus() {
-            return Ok(true);
+        let released = if self.is_bus() {
+            self.call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "ReleaseName",
+                &well_known_name,
+            )
+            .await?
+            .body()
+            .deserialize::<ReleaseNameReply>()?
+                == ReleaseNameReply::Released
+        } else {
+            true
+        };
+        drop(names);
+        if owned && released {
+            self.inner
+                .emit_state_change(StateChange::NameLost(well_known_name.to_owned().into()));
         }
 
-        self.call_method(
-            Some("org.freedesktop.DBus"),
-            "/org/freedesktop/DBus",
-            Some("org.freedesktop.DBus"),
-            "ReleaseName",
-            &well_known_name,
-        )
-        .await?
-        .body()
-        .deserialize::<ReleaseNameReply>()
-        .map(|r| r == ReleaseNameReply::Released)
+        Ok(released)
     }
 
     /// Check if `self` is a connection to a message bus.
@@ -813,11 +991,45 @@ impl Connection {
         }
     }
 
+    /// The identity the peer authenticated as.
+    ///
+    /// This is only set on the server side of a peer-to-peer connection, if the authentication
+    /// mechanism has the notion of an identity. For the built-in `EXTERNAL` and `DBUS_COOKIE_SHA1`
+    /// mechanisms, this is the user ID (the SID on Windows) of the peer. For custom mechanisms,
+    /// this is the identity returned through [`ServerStep::Accept`].
+    ///
+    /// This will always return `None` when the `p2p` feature is disabled.
+    ///
+    /// [`ServerStep::Accept`]: handshake::ServerStep::Accept
+    pub fn peer_auth_identity(&self) -> Option<&str> {
+        #[cfg(feature = "p2p")]
+        {
+            self.inner.peer_auth_identity.as_deref()
+        }
+        #[cfg(not(feature = "p2p"))]
+        {
+            None
+        }
+    }
+
     /// The unique name of the connection, if set/applicable.
     ///
     /// The unique name is assigned by the message bus or set manually using
     /// [`Connection::set_unique_name`].
+    ///
+    /// The bus assigns a new unique name each time the connection is re-established (see
+    /// [`Builder::auto_reconnect`]). This is always the first one, while
+    /// [`Connection::current_unique_name`] gives the current one.
     pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
+        self.inner.unique_name.first()
+    }
+
+    /// The current unique name of the connection, if set/applicable.
+    ///
+    /// This is the same as [`Connection::unique_name`], unless the connection was re-established
+    /// (see [`Builder::auto_reconnect`]), in which case this is the name assigned by the bus on the
+    /// last reconnection.
+    pub fn current_unique_name(&self) -> Option<OwnedUniqueName> {
         self.inner.unique_name.get()
     }
 
@@ -852,9 +1064,87 @@ impl Connection {
         self.inner.msg_receiver.clone().set_capacity(max);
     }
 
+    /// A snapshot of the statistics of the connection.
+    ///
+    /// This includes counts of the messages sent and received, the number of replies pending, the
+    /// state of the incoming message queues, the match rules and the names owned. To expose these
+    /// on the bus, see [`crate::fdo::DebugStats`].
+    pub async fn stats(&self) -> Stats {
+        let queues = self
+            .inner
+            .msg_senders
+            .lock()
+            .await
+            .iter()
+            .map(|(rule, sender)| QueueStats {
+                match_rule: rule.clone(),
+                len: sender.len(),
+                capacity: sender.capacity(),
+            })
+            .collect();
+        let match_rules = self
+            .inner
+            .subscriptions
+            .lock()
+            .await
+            .keys()
+            .cloned()
+            .collect();
+        let names = self
+            .inner
+            .registered_names
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, name)| matches!(name.status, NameStatus::Owner(_)))
+            .map(|(name, _)| name.clone().into())
+            .collect();
+
+        self.inner.stats.snapshot(queues, match_rules, names)
+    }
+
+    /// Capture all the messages sent and received from now on, to `writer`.
+    ///
+    /// The messages are written in the pcapng format, with their timestamp and direction, as
+    /// they're sent and received. A capture can be inspected with Wireshark, or replayed with a
+    /// [`capture::Reader`]. The capture lasts as long as the connection, unless writing fails.
+    ///
+    /// The messages are written by a dedicated thread, so a slow `writer` doesn't hold up the
+    /// connection, though the messages queue up in memory in the meantime. `writer` is flushed
+    /// whenever the thread catches up, and dropped along with the connection. Note that file
+    /// descriptors passed along messages are not captured.
+    pub fn capture<W>(&self, writer: W) -> Result<()>
+    where
+        W: std::io::Write + Send + 'static,
+    {
+        let writer = capture::Writer::new(writer)?;
+        self.add_tap(Arc::new(writer));
+
+        Ok(())
+    }
+
+    /// Observe all the messages sent and received from now on.
+    pub(crate) fn add_tap(&self, tap: Arc<dyn Tap>) {
+        self.inner.taps.add(tap);
+    }
+
     /// The server's GUID.
+    ///
+    /// If the connection was re-established (see [`Builder::auto_reconnect`]), this is still the
+    /// GUID of the first server, while [`Connection::current_server_guid`] gives the GUID of the
+    /// current one.
     pub fn server_guid(&self) -> &OwnedGuid {
-        &self.inner.server_guid
+        // SAFETY: The GUID is set on creation.
+        self.inner.server_guid.first().unwrap()
+    }
+
+    /// The GUID of the server the connection is currently connected to.
+    ///
+    /// This is the same as [`Connection::server_guid`], unless the connection was re-established
+    /// (see [`Builder::auto_reconnect`]).
+    pub fn current_server_guid(&self) -> OwnedGuid {
+        // SAFETY: The GUID is set on creation.
+        self.inner.server_guid.get().unwrap()
     }
 
     /// The underlying executor.
@@ -949,7 +1239,11 @@ impl Connection {
                     let mut stream = match weak_conn.upgrade() {
                         Some(conn) => {
                             let mut builder = MatchRule::builder().msg_type(Type::MethodCall);
-                            if let Some(unique_name) = conn.unique_name() {
+                            // The unique name changes on reconnection, so it's checked for each
+                            // call below instead.
+                            if let Some(unique_name) =
+                                conn.unique_name().filter(|_| !conn.inner.auto_reconnect)
+                            {
                                 builder = builder.destination(&**unique_name).expect("unique name");
                             }
                             let rule = builder.build();
@@ -982,6 +1276,20 @@ impl Connection {
                     }) {
                         if let Some(conn) = weak_conn.upgrade() {
                             let hdr = msg.header();
+                            if conn.inner.auto_reconnect {
+                                if let (Some(BusName::Unique(dest)), Some(unique_name)) =
+                                    (hdr.destination(), conn.current_unique_name())
+                                {
+                                    if *dest != *unique_name {
+                                        trace!(
+                                            "Got a method call for a different destination: {}",
+                                            dest
+                                        );
+
+                                        continue;
+                                    }
+                                }
+                            }
                             // If we're connected to a bus, skip the destination check as the
                             // server will only send us method calls destined to us.
                             if !conn.is_bus() {
@@ -1126,6 +1434,8 @@ impl Connection {
         auth: Authenticated,
         #[allow(unused)] bus_connection: bool,
         executor: Executor<'static>,
+        method_timeout: Option<Duration>,
+        auto_reconnect: bool,
     ) -> Result<Self> {
         #[cfg(unix)]
         let cap_unix_fd = auth.cap_unix_fd;
@@ -1157,25 +1467,41 @@ impl Connection {
         let msg_senders = Arc::new(Mutex::new(msg_senders));
         let subscriptions = Mutex::new(HashMap::new());
 
+        let server_guid = Latest::new();
+        server_guid
+            .set_initial(auth.server_guid)
+            .expect("server GUID already set");
+
         let connection = Self {
             inner: Arc::new(ConnectionInner {
                 activity_event: Arc::new(Event::new()),
                 socket_write: Mutex::new(auth.socket_write),
-                server_guid: auth.server_guid,
+                server_guid,
                 #[cfg(unix)]
                 cap_unix_fd,
                 #[cfg(feature = "p2p")]
                 bus_conn: bus_connection,
-                unique_name: OnceLock::new(),
+                #[cfg(feature = "p2p")]
+                peer_auth_identity: auth.peer_auth_identity,
+                unique_name: Latest::new(),
                 subscriptions,
                 object_server: OnceLock::new(),
                 object_server_dispatch_task: OnceLock::new(),
                 executor,
                 socket_reader_task: OnceLock::new(),
+                reconnection_task: OnceLock::new(),
+                auto_reconnect,
+                closed: AtomicBool::new(false),
+                // Only the latest state changes are of interest, so old ones are dropped if not
+                // consumed.
+                state_changes: StateChanges::new(DEFAULT_MAX_STATE_CHANGES_QUEUED),
+                stats: Arc::new(stats::Counters::default()),
+                taps: Default::default(),
                 msg_senders,
                 msg_receiver,
                 method_return_receiver,
                 registered_names: Mutex::new(HashMap::new()),
+                method_timeout,
                 drop_event: Event::new(),
             }),
         };
@@ -1197,6 +1523,35 @@ impl Connection {
         Builder::system()?.build().await
     }
 
+    /// Receive changes of the connection state.
+    ///
+    /// Only the changes happening after this call are received.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use zbus::{block_on, connection::{Builder, StateChange}};
+    /// use futures_util::StreamExt;
+    ///
+    /// # block_on(async {
+    /// let conn = Builder::session()?.auto_reconnect(true).build().await?;
+    /// let mut changes = conn.receive_state_changes();
+    /// while let Some(change) = changes.next().await {
+    ///     match change {
+    ///         StateChange::Disconnected { reason } => println!("Disconnected: {reason}"),
+    ///         StateChange::Reconnected => {
+    ///             println!("Reconnected as {:?}", conn.current_unique_name())
+    ///         }
+    ///         _ => (),
+    ///     }
+    /// }
+    /// #     Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    pub fn receive_state_changes(&self) -> StateChangeStream {
+        self.inner.state_changes.subscribe()
+    }
+
     /// Return a listener, notified on various connection activity.
     ///
     /// This function is meant for the caller to implement idle or timeout on inactivity.
@@ -1211,7 +1566,8 @@ impl Connection {
     ///
     /// # Caveats
     ///
-    /// Currently `unix_group_ids` and `linux_security_label` fields are not populated.
+    /// Currently `unix_group_ids`, `process_fd` and `linux_security_label` fields are only
+    /// populated on Linux.
     pub async fn peer_credentials(&self) -> io::Result<ConnectionCredentials> {
         self.inner
             .socket_write
@@ -1223,8 +1579,10 @@ impl Connection {
 
     /// Close the connection.
     ///
-    /// After this call, all reading and writing operations will fail.
+    /// After this call, all reading and writing operations will fail. If automatic reconnection is
+    /// enabled (see [`Builder::auto_reconnect`]), the connection is not re-established.
     pub async fn close(self) -> Result<()> {
+        self.inner.closed.store(true, Ordering::Release);
         self.inner.activity_event.notify(usize::MAX);
         self.inner
             .socket_write
@@ -1284,33 +1642,42 @@ impl Connection {
         listener.await;
     }
 
+    /// Start the socket reader task.
+    ///
+    /// If `reconnector` is given, the reader is supervised by a task re-establishing the connection
+    /// each time it's lost.
     pub(crate) fn init_socket_reader(
         &self,
         socket_read: Box<dyn socket::ReadHalf>,
         already_read: Vec<u8>,
         #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
+        reconnector: Option<Reconnector>,
     ) {
         let inner = &self.inner;
-        inner
-            .socket_reader_task
-            .set(
-                SocketReader::new(
-                    socket_read,
-                    inner.msg_senders.clone(),
-                    already_read,
-                    #[cfg(unix)]
-                    already_received_fds,
-                    inner.activity_event.clone(),
-                )
-                .spawn(&inner.executor),
-            )
-            .expect("Attempted to set `socket_reader_task` twice");
+        let reader = SocketReader::new(
+            socket_read,
+            already_read,
+            #[cfg(unix)]
+            already_received_fds,
+            inner,
+        )
+        .spawn(&inner.executor);
+        match reconnector {
+            Some(reconnector) => inner
+                .reconnection_task
+                .set(reconnector.spawn(self, reader))
+                .expect("Attempted to set `reconnection_task` twice"),
+            None => inner
+                .socket_reader_task
+                .set(reader)
+                .expect("Attempted to set `socket_reader_task` twice"),
+        }
     }
 
     fn set_unique_name_(&self, name: OwnedUniqueName) {
         self.inner
             .unique_name
-            .set(name)
+            .set_initial(name)
             // programmer (probably our) error if this fails.
             .expect("unique name already set");
     }
@@ -1334,6 +1701,12 @@ impl WeakConnection {
     pub fn upgrade(&self) -> Option<Connection> {
         self.inner.upgrade().map(|inner| Connection { inner })
     }
+
+    /// Whether `self` and `other` refer to the same connection.
+    #[cfg(feature = "p2p")]
+    pub fn ptr_eq(&self, other: &WeakConnection) -> bool {
+        self.inner.ptr_eq(&other.inner)
+    }
 }
 
 impl From<&Connection> for WeakConnection {
@@ -1344,6 +1717,13 @@ impl From<&Connection> for WeakConnection {
     }
 }
 
+#[derive(Debug)]
+struct RegisteredName {
+    status: NameStatus,
+    // The flags the name was requested with, to request it again on reconnection.
+    flags: BitFlags<RequestNameFlags>,
+}
+
 #[derive(Debug)]
 enum NameStatus {
     // The task waits for name lost signal if owner allows replacement.
@@ -1533,6 +1913,248 @@ mod tests {
         // The method call should have been allowed to finish properly.
         done_listener.await;
     }
+
+    #[test]
+    #[timeout(15000)]
+    fn state_changes() {
+        crate::utils::block_on(async {
+            let name = "org.zbus.StateChanges";
+            let conn = Connection::session().await.unwrap();
+            let mut changes = conn.receive_state_changes();
+            conn.request_name_with_flags(name, RequestNameFlags::AllowReplacement.into())
+                .await
+                .unwrap();
+            match changes.next().await.unwrap() {
+                StateChange::NameAcquired(acquired) => assert_eq!(acquired, name),
+                change => panic!("unexpected state change: {change:?}"),
+            }
+
+            let other = Connection::session().await.unwrap();
+            other
+                .request_name_with_flags(name, RequestNameFlags::ReplaceExisting.into())
+                .await
+                .unwrap();
+            match changes.next().await.unwrap() {
+                StateChange::NameLost(lost) => assert_eq!(lost, name),
+                change => panic!("unexpected state change: {change:?}"),
+            }
+
+            // The name is registered by the time it's reported as acquired.
+            let released = "org.zbus.StateChanges.Released";
+            conn.request_name(released).await.unwrap();
+            match changes.next().await.unwrap() {
+                StateChange::NameAcquired(acquired) => {
+                    assert_eq!(acquired, released);
+                    let reply = conn
+                        .request_name_with_flags(released, BitFlags::empty())
+                        .await
+                        .unwrap();
+                    assert_eq!(reply, RequestNameReply::AlreadyOwner);
+                }
+                change => panic!("unexpected state change: {change:?}"),
+            }
+            assert!(conn.release_name(released).await.unwrap());
+            match changes.next().await.unwrap() {
+                StateChange::NameLost(lost) => assert_eq!(lost, released),
+                change => panic!("unexpected state change: {change:?}"),
+            }
+
+            conn.clone().close().await.unwrap();
+            assert!(matches!(
+                changes.next().await.unwrap(),
+                StateChange::Disconnected { .. }
+            ));
+        });
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn stats() {
+        crate::utils::block_on(async {
+            let path = "/org/zbus/Stats";
+            let service = Builder::session()
+                .unwrap()
+                .serve_at(path, crate::fdo::DebugStats::default())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            // The handshake is not accounted for.
+            let stats = service.stats().await;
+            assert_eq!(stats.incoming().method_calls(), 0);
+            assert!(stats.queues().iter().any(|q| q.match_rule().is_none()));
+
+            let client = Connection::session().await.unwrap();
+            let proxy = crate::fdo::StatsProxy::builder(&client)
+                .destination(service.unique_name().unwrap())
+                .unwrap()
+                .path(path)
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let conn_stats = proxy
+                .get_connection_stats(service.unique_name().unwrap().into())
+                .await
+                .unwrap();
+            assert_eq!(conn_stats.unique_name(), service.unique_name());
+            assert_eq!(conn_stats.serial(), Some(1));
+            assert!(conn_stats.incoming_messages().unwrap() >= 2);
+            let calls = u64::try_from(&conn_stats.rest()["IncomingMethodCalls"]).unwrap();
+            assert!(calls >= 1);
+            let rules = proxy.get_all_match_rules().await.unwrap();
+            assert_eq!(
+                rules[service.unique_name().unwrap()],
+                service.stats().await.match_rules()
+            );
+            assert!(proxy
+                .get_connection_stats(client.unique_name().unwrap().into())
+                .await
+                .is_err());
+
+            let stats = service.stats().await;
+            assert_eq!(stats.incoming().method_calls(), 3);
+            assert_eq!(stats.outgoing().method_returns(), 2);
+            assert_eq!(stats.outgoing().errors(), 1);
+            assert!(stats.outgoing().bytes() > 0);
+            assert_eq!(stats.pending_replies(), 0);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn auto_reconnect() {
+        use std::{
+            io::{BufRead, BufReader},
+            process::{Child, Command, Stdio},
+        };
+
+        // A dbus-daemon, killed on drop.
+        struct Bus(Child);
+
+        impl Drop for Bus {
+            fn drop(&mut self) {
+                self.0.kill().unwrap();
+                self.0.wait().unwrap();
+            }
+        }
+
+        // Start a dbus-daemon on `socket`, returning it once it's ready.
+        fn start_bus(socket: &std::path::Path) -> Bus {
+            let _ = std::fs::remove_file(socket);
+            let mut bus = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .arg(format!("--address=unix:path={}", socket.display()))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("failed to start dbus-daemon");
+            let mut address = String::new();
+            BufReader::new(bus.stdout.as_mut().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+
+            Bus(bus)
+        }
+
+        crate::utils::block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let socket = dir.path().join("bus");
+            let address = format!("unix:path={}", socket.display());
+            let bus = start_bus(&socket);
+
+            let name = "org.zbus.AutoReconnect";
+            let conn = Builder::address(address.as_str())
+                .unwrap()
+                .auto_reconnect(true)
+                .reconnect_delay(Duration::from_millis(10), Duration::from_millis(100))
+                .name(name)
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let rule = MatchRule::builder()
+                .msg_type(Type::Signal)
+                .interface(name)
+                .unwrap()
+                .member("Ping")
+                .unwrap()
+                .build();
+            let mut signals = MessageStream::for_match_rule(rule, &conn, None)
+                .await
+                .unwrap();
+            let mut changes = conn.receive_state_changes();
+            // Start the object server.
+            conn.object_server();
+            let first_name = conn.unique_name().unwrap().clone();
+            let first_guid = conn.server_guid().clone();
+
+            drop(bus);
+            assert!(matches!(
+                changes.next().await.unwrap(),
+                StateChange::Disconnected { .. }
+            ));
+            // Calls fail while disconnected.
+            let dbus = DBusProxy::new(&conn).await.unwrap();
+            assert!(dbus.get_id().await.is_err());
+
+            let _bus = start_bus(&socket);
+            assert!(matches!(
+                changes.next().await.unwrap(),
+                StateChange::Authenticated
+            ));
+            match changes.next().await.unwrap() {
+                StateChange::NameAcquired(acquired) => assert_eq!(acquired, name),
+                change => panic!("unexpected state change: {change:?}"),
+            }
+            assert!(matches!(
+                changes.next().await.unwrap(),
+                StateChange::Reconnected
+            ));
+
+            // The name is owned again and the match rule added again.
+            let other = Builder::address(address.as_str())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let owner = DBusProxy::new(&other)
+                .await
+                .unwrap()
+                .get_name_owner(name.try_into().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(owner, conn.current_unique_name().unwrap());
+            // The first name and GUID are still around.
+            assert_eq!(conn.unique_name(), Some(&first_name));
+            assert_eq!(conn.server_guid(), &first_guid);
+            assert_ne!(conn.current_server_guid(), first_guid);
+            other
+                .emit_signal(None::<()>, "/org/zbus/AutoReconnect", name, "Ping", &())
+                .await
+                .unwrap();
+            let signal = signals.next().await.unwrap().unwrap();
+            assert_eq!(
+                signal.header().sender().unwrap(),
+                other.unique_name().unwrap()
+            );
+
+            // Method calls work again, both ways.
+            dbus.get_id().await.unwrap();
+            crate::fdo::PeerProxy::builder(&other)
+                .destination(owner)
+                .unwrap()
+                .path("/")
+                .unwrap()
+                .build()
+                .await
+                .unwrap()
+                .ping()
+                .await
+                .unwrap();
+        });
+    }
 }
 
 #[cfg(feature = "p2p")]
@@ -1722,6 +2344,169 @@ mod p2p_tests {
         )
     }
 
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn p2p_name_state_changes() {
+        use futures_util::StreamExt;
+
+        use super::StateChange;
+
+        crate::utils::block_on(async {
+            let (conn, _peer) = unix_p2p_pipe().await.unwrap();
+            let mut changes = conn.receive_state_changes();
+            let name = "org.zbus.P2PStateChanges";
+
+            conn.request_name(name).await.unwrap();
+            match changes.next().await.unwrap() {
+                StateChange::NameAcquired(acquired) => assert_eq!(acquired, name),
+                change => panic!("unexpected state change: {change:?}"),
+            }
+            assert!(conn.release_name(name).await.unwrap());
+            match changes.next().await.unwrap() {
+                StateChange::NameLost(lost) => assert_eq!(lost, name),
+                change => panic!("unexpected state change: {change:?}"),
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_p2p_auth_identity() {
+        crate::utils::block_on(async {
+            let (client, server) = unix_p2p_pipe().await.unwrap();
+
+            assert_eq!(
+                server.peer_auth_identity(),
+                Some(nix::unistd::Uid::effective().to_string().as_str())
+            );
+            assert_eq!(client.peer_auth_identity(), None);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_p2p_authorization() {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        async fn connect(
+            configure: impl FnOnce(Builder<'static>) -> Builder<'static>,
+        ) -> Result<(Connection, Connection)> {
+            let (p0, p1) = UnixStream::pair().unwrap();
+            let server = configure(Builder::unix_stream(p0).server(Guid::generate())?.p2p());
+
+            futures_util::try_join!(Builder::unix_stream(p1).p2p().build(), server.build())
+        }
+
+        crate::utils::block_on(async {
+            let uid = nix::unistd::Uid::effective().as_raw();
+
+            let (_client, _server) = connect(|b| {
+                b.allowed_unix_user_ids([uid])
+                    .authorize(
+                        |creds| async move { creds.process_id() == Some(std::process::id()) },
+                    )
+            })
+            .await
+            .unwrap();
+
+            let err = connect(|b| b.allowed_unix_user_ids([uid + 1]))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, crate::Error::Handshake(_)), "{err}");
+
+            let err = connect(|b| b.authorize(|_| async { false }))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, crate::Error::Handshake(_)), "{err}");
+
+            // The callback gets the groups of the client too.
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            {
+                let seen = std::sync::Arc::new(std::sync::Mutex::new(None));
+                let seen_ = seen.clone();
+                let (_client, _server) = connect(move |b| {
+                    b.authorize(move |creds| {
+                        *seen_.lock().unwrap() = Some(creds);
+                        async { true }
+                    })
+                })
+                .await
+                .unwrap();
+
+                let creds = seen.lock().unwrap().take().unwrap();
+                let gids = creds.unix_group_ids().unwrap();
+                assert!(gids.contains(&nix::unistd::Gid::effective().as_raw()));
+                assert!(gids.windows(2).all(|w| w[0] < w[1]), "{gids:?}");
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn address_list_fallback() {
+        crate::utils::block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let missing1 = dir.path().join("missing1");
+            let missing2 = dir.path().join("missing2");
+            let listener =
+                super::Listener::bind(format!("unix:dir={}", dir.path().display()).as_str())
+                    .await
+                    .unwrap();
+
+            // The first address can't be connected to, so the second one is used.
+            let addresses = format!("unix:path={};{}", missing1.display(), listener.address());
+            let (client, _server) = futures_util::try_join!(
+                Builder::address(addresses.as_str()).unwrap().p2p().build(),
+                listener.accept(),
+            )
+            .unwrap();
+            assert_eq!(client.server_guid(), listener.guid());
+
+            // The first address can be connected to but rejects the client, so the second one is
+            // used.
+            let rejecting =
+                super::Listener::builder(format!("unix:dir={}", dir.path().display()).as_str())
+                    .unwrap()
+                    .authorize(|_| async { false })
+                    .build()
+                    .await
+                    .unwrap();
+            let addresses = format!("{};{}", rejecting.address(), listener.address());
+            let (client, _rejected, _server) = futures_util::join!(
+                Builder::address(addresses.as_str()).unwrap().p2p().build(),
+                rejecting.accept(),
+                listener.accept(),
+            );
+            assert_eq!(client.unwrap().server_guid(), listener.guid());
+
+            // Errors for all addresses are reported.
+            let addresses = format!(
+                "unix:path={};unix:path={}",
+                missing1.display(),
+                missing2.display()
+            );
+            let err = Builder::address(addresses.as_str())
+                .unwrap()
+                .p2p()
+                .build()
+                .await
+                .unwrap_err();
+            match err {
+                crate::Error::Address(e) => {
+                    assert!(e.contains("missing1") && e.contains("missing2"), "{e}")
+                }
+                e => panic!("unexpected error: {e}"),
+            }
+        });
+    }
+
     #[cfg(any(
         all(feature = "vsock", not(feature = "tokio")),
         feature = "tokio-vsock"
@@ -0,0 +1,189 @@
+use std::{
+    cmp::min,
+    sync::{atomic::Ordering, OnceLock, PoisonError, RwLock},
+    time::Duration,
+};
+
+use tracing::{debug, instrument, trace, warn, Instrument};
+
+use crate::{abstractions::sleep, message::Type, Error, Result, Task};
+
+use super::{socket_reader::SocketReader, Builder, Connection, StateChange, WeakConnection};
+
+/// The default minimum delay between reconnection attempts.
+pub(crate) const DEFAULT_MIN_DELAY: Duration = Duration::from_millis(100);
+/// The default maximum delay between reconnection attempts.
+pub(crate) const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Everything needed to connect again to the bus.
+#[derive(Debug)]
+pub(crate) struct Reconnector {
+    // The builder to connect again with, see `Builder::reconnection`.
+    template: Builder<'static>,
+    min_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Reconnector {
+    pub fn new(template: Builder<'static>, min_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            template,
+            min_delay,
+            max_delay,
+        }
+    }
+
+    /// Spawn the task that re-establishes the connection, each time `reader` stops.
+    pub fn spawn(self, conn: &Connection, reader: Task<Error>) -> Task<()> {
+        let weak_conn = WeakConnection::from(conn);
+        let task_name = "reconnection task";
+
+        conn.executor().spawn(
+            self.supervise(weak_conn, reader)
+                .instrument(tracing::info_span!("{}", task_name)),
+            task_name,
+        )
+    }
+
+    async fn supervise(self, weak_conn: WeakConnection, mut reader: Task<Error>) {
+        loop {
+            let reason = (&mut reader).await;
+            match weak_conn.upgrade() {
+                Some(conn) if !conn.inner.closed.load(Ordering::Acquire) => {
+                    debug!("Connection lost: {reason}")
+                }
+                _ => return,
+            }
+
+            let mut delay = self.min_delay;
+            reader = loop {
+                sleep(delay).await;
+                let conn = match weak_conn.upgrade() {
+                    Some(conn) => conn,
+                    None => return,
+                };
+                match self.reconnect(&conn).await {
+                    Ok(reader) => break reader,
+                    Err(e) => {
+                        debug!("Failed to reconnect: {e}");
+                        delay = min(delay * 2, self.max_delay);
+                    }
+                }
+            };
+
+            if let Some(conn) = weak_conn.upgrade() {
+                conn.inner.emit_state_change(StateChange::Reconnected);
+            }
+        }
+    }
+
+    #[instrument(skip(self, conn))]
+    async fn reconnect(&self, conn: &Connection) -> Result<Task<Error>> {
+        // SAFETY: The template is always created from an address.
+        let mut builder = self.template.reconnection().unwrap();
+        let mut auth = builder.connect(true).await?;
+        trace!("Reconnected to the bus");
+        conn.inner.emit_state_change(StateChange::Authenticated);
+
+        // SAFETY: `Authenticated` is always built with these fields set to `Some`.
+        let socket_read = auth.socket_read.take().unwrap();
+        let unique_name = auth.unique_name.take().unwrap();
+        let inner = &conn.inner;
+        *inner.socket_write.lock().await = auth.socket_write;
+        inner.server_guid.set(auth.server_guid);
+        inner.unique_name.set(unique_name);
+
+        // Start reading before anything else, so we receive replies to the calls below.
+        let reader = SocketReader::new(
+            socket_read,
+            auth.already_received_bytes,
+            #[cfg(unix)]
+            auth.already_received_fds,
+            inner,
+        )
+        .spawn(&inner.executor);
+
+        let rules: Vec<_> = inner
+            .subscriptions
+            .lock()
+            .await
+            .keys()
+            .filter(|rule| rule.msg_type().unwrap_or(Type::Signal) == Type::Signal)
+            .cloned()
+            .collect();
+        for rule in rules {
+            conn.call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "AddMatch",
+                &rule,
+            )
+            .await?;
+        }
+
+        let names: Vec<_> = inner
+            .registered_names
+            .lock()
+            .await
+            .drain()
+            .map(|(name, registered)| (name, registered.flags))
+            .collect();
+        for (name, flags) in names {
+            if let Err(e) = conn.request_name_with_flags(&name, flags).await {
+                warn!("Failed to request name `{name}` again: {e}");
+            }
+        }
+
+        Ok(reader)
+    }
+}
+
+/// A value that can be replaced.
+///
+/// This is used for connection properties that change on reconnection (e.g. the unique name). The
+/// first value is kept for the lifetime of `self`, so the API can give out references to it, while
+/// readers of the current value get a clone of it. `T` is expected to be cheap to clone (e.g.
+/// reference counted).
+#[derive(Debug)]
+pub(crate) struct Latest<T> {
+    first: OnceLock<T>,
+    current: RwLock<Option<T>>,
+}
+
+impl<T: Clone> Latest<T> {
+    pub fn new() -> Self {
+        Self {
+            first: OnceLock::new(),
+            current: RwLock::new(None),
+        }
+    }
+
+    /// The first value, if any.
+    pub fn first(&self) -> Option<&T> {
+        self.first.get()
+    }
+
+    /// The current value, if any.
+    pub fn get(&self) -> Option<T> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Set the initial value.
+    ///
+    /// Returns `Err(value)` if a value is already set.
+    pub fn set_initial(&self, value: T) -> std::result::Result<(), T> {
+        self.first.set(value.clone())?;
+        self.set(value);
+
+        Ok(())
+    }
+
+    /// Replace the current value.
+    pub fn set(&self, value: T) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Some(value);
+    }
+}
@@ -333,15 +333,44 @@ fn get_unix_peer_creds_blocking(fd: RawFd) -> io::Result<crate::fdo::ConnectionC
 
     #[cfg(any(target_os = "android", target_os = "linux"))]
     {
-        use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
-
-        getsockopt(&fd, PeerCredentials)
-            .map(|creds| {
-                crate::fdo::ConnectionCredentials::default()
-                    .set_process_id(creds.pid() as _)
-                    .set_unix_user_id(creds.uid())
-            })
-            .map_err(|e| e.into())
+        use nix::{
+            libc::{SO_PEERGROUPS, SO_PEERSEC},
+            sys::socket::{getsockopt, sockopt::PeerCredentials},
+        };
+
+        let creds = getsockopt(&fd, PeerCredentials)?;
+        let mut credentials = crate::fdo::ConnectionCredentials::default()
+            .set_process_id(creds.pid() as _)
+            .set_unix_user_id(creds.uid());
+
+        // The other credentials depend on the kernel version and the LSMs in use, so they're left
+        // out if they can't be retrieved.
+        //
+        // SO_PEERGROUPS only gives the supplementary groups.
+        if let Ok(mut gids) = getsockopt_vec::<u32>(fd, SO_PEERGROUPS) {
+            gids.push(creds.gid());
+            gids.sort_unstable();
+            gids.dedup();
+            for gid in gids {
+                credentials = credentials.add_unix_group_id(gid);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let Ok(pidfd) = getsockopt(&fd, nix::sys::socket::sockopt::PeerPidfd) {
+            credentials = credentials.set_process_fd(pidfd.into());
+        }
+        if let Ok(mut label) = getsockopt_vec::<u8>(fd, SO_PEERSEC) {
+            // Some LSMs include the trailing zero byte, others don't.
+            if label.last() == Some(&0) {
+                label.pop();
+            }
+            if !label.is_empty() {
+                label.push(0);
+                credentials = credentials.set_linux_security_label(label);
+            }
+        }
+
+        Ok(credentials)
     }
 
     #[cfg(any(
@@ -359,6 +388,45 @@ fn get_unix_peer_creds_blocking(fd: RawFd) -> io::Result<crate::fdo::ConnectionC
     }
 }
 
+/// Get the value of the variable-length `SOL_SOCKET` `option`, as an array of `T`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn getsockopt_vec<T: Copy + Default>(
+    fd: BorrowedFd<'_>,
+    option: nix::libc::c_int,
+) -> io::Result<Vec<T>> {
+    use nix::libc::{getsockopt, socklen_t, ERANGE, SOL_SOCKET};
+    use std::mem::size_of;
+
+    let mut buf = vec![T::default(); 64];
+    loop {
+        let buf_len = buf.len() * size_of::<T>();
+        let mut len = buf_len as socklen_t;
+        let res = unsafe {
+            getsockopt(
+                fd.as_raw_fd(),
+                SOL_SOCKET,
+                option,
+                buf.as_mut_ptr().cast(),
+                &mut len,
+            )
+        };
+        if res == 0 {
+            buf.truncate(len as usize / size_of::<T>());
+
+            return Ok(buf);
+        }
+
+        let err = io::Error::last_os_error();
+        // The buffer is too small, and `len` was set to the size needed.
+        if err.raw_os_error() == Some(ERANGE) && len as usize > buf_len {
+            buf.resize((len as usize).div_ceil(size_of::<T>()), T::default());
+            continue;
+        }
+
+        return Err(err);
+    }
+}
+
 // Send 0 byte as a separate SCM_CREDS message.
 #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
 async fn send_zero_byte(fd: &impl AsRawFd) -> io::Result<usize> {
@@ -4,10 +4,16 @@ use event_listener::Event;
 use tracing::{debug, instrument, trace};
 
 use crate::{
-    async_lock::Mutex, connection::MsgBroadcaster, Executor, Message, OwnedMatchRule, Task,
+    async_lock::Mutex, connection::MsgBroadcaster, message::Type, Error, Executor, Message,
+    OwnedMatchRule, Task,
 };
 
-use super::socket::ReadHalf;
+use super::{
+    socket::ReadHalf,
+    stats::Counters,
+    tap::{Direction, Taps},
+    ConnectionInner, StateChange, StateChanges,
+};
 
 #[derive(Debug)]
 pub(crate) struct SocketReader {
@@ -18,34 +24,43 @@ pub(crate) struct SocketReader {
     already_received_fds: Vec<std::os::fd::OwnedFd>,
     prev_seq: u64,
     activity_event: Arc<Event>,
+    state_changes: StateChanges,
+    stats: Arc<Counters>,
+    taps: Arc<Taps>,
+    // If the connection is to be re-established, the streams must outlive this reader.
+    auto_reconnect: bool,
 }
 
 impl SocketReader {
     pub fn new(
         socket: Box<dyn ReadHalf>,
-        senders: Arc<Mutex<HashMap<Option<OwnedMatchRule>, MsgBroadcaster>>>,
         already_received_bytes: Vec<u8>,
         #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
-        activity_event: Arc<Event>,
+        conn: &ConnectionInner,
     ) -> Self {
         Self {
             socket,
-            senders,
+            senders: conn.msg_senders.clone(),
             already_received_bytes,
             #[cfg(unix)]
             already_received_fds,
             prev_seq: 0,
-            activity_event,
+            activity_event: conn.activity_event.clone(),
+            state_changes: conn.state_changes.clone(),
+            stats: conn.stats.clone(),
+            taps: conn.taps.clone(),
+            auto_reconnect: conn.auto_reconnect,
         }
     }
 
-    pub fn spawn(self, executor: &Executor<'_>) -> Task<()> {
+    /// Spawn the reader task, which resolves to the error that stopped it.
+    pub fn spawn(self, executor: &Executor<'_>) -> Task<Error> {
         executor.spawn(self.receive_msg(), "socket reader")
     }
 
     // Keep receiving messages and put them on the queue.
     #[instrument(name = "socket reader", skip(self))]
-    async fn receive_msg(mut self) {
+    async fn receive_msg(mut self) -> Error {
         loop {
             trace!("Waiting for message on the socket..");
             let msg = self.read_socket().await;
@@ -56,18 +71,29 @@ impl SocketReader {
 
             let mut senders = self.senders.lock().await;
             for (rule, sender) in &*senders {
-                if let Ok(msg) = &msg {
-                    if let Some(rule) = rule.as_ref() {
-                        match rule.matches(msg) {
-                            Ok(true) => (),
-                            Ok(false) => continue,
-                            Err(e) => {
-                                debug!("Error matching message against rule: {:?}", e);
+                match &msg {
+                    Ok(msg) => {
+                        if let Some(rule) = rule.as_ref() {
+                            match rule.matches(msg) {
+                                Ok(true) => (),
+                                Ok(false) => continue,
+                                Err(e) => {
+                                    debug!("Error matching message against rule: {:?}", e);
 
-                                continue;
+                                    continue;
+                                }
                             }
                         }
                     }
+                    // Only pending method calls are interested in the error, other streams will
+                    // continue once reconnected.
+                    Err(_) if self.auto_reconnect => {
+                        let msg_type = rule.as_ref().and_then(|rule| rule.msg_type());
+                        if !matches!(msg_type, Some(Type::MethodReturn | Type::Error)) {
+                            continue;
+                        }
+                    }
+                    Err(_) => (),
                 }
 
                 if let Err(e) = sender.broadcast_direct(msg.clone()).await {
@@ -89,11 +115,15 @@ impl SocketReader {
             }
             trace!("Broadcasted to all streams: {:?}", msg);
 
-            if msg.is_err() {
-                senders.clear();
+            if let Err(e) = msg {
+                if !self.auto_reconnect {
+                    senders.clear();
+                }
                 trace!("Socket reading task stopped");
+                self.state_changes
+                    .emit(StateChange::Disconnected { reason: e.clone() });
 
-                return;
+                return e;
             }
         }
     }
@@ -112,6 +142,8 @@ impl SocketReader {
             )
             .await?;
         self.prev_seq = seq;
+        self.stats.record_incoming(&msg);
+        self.taps.message(&msg, Direction::Incoming);
 
         Ok(msg)
     }
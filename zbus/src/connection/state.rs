@@ -0,0 +1,247 @@
+use async_broadcast::{broadcast, InactiveReceiver, Receiver, Sender};
+use futures_core::{ready, Stream};
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+use crate::{names::OwnedWellKnownName, Error};
+
+/// A change in the state of a [`Connection`].
+///
+/// Use [`Connection::receive_state_changes`] to be notified of these.
+///
+/// [`Connection`]: super::Connection
+/// [`Connection::receive_state_changes`]: super::Connection::receive_state_changes
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum StateChange {
+    /// The connection was authenticated with the peer.
+    ///
+    /// Since a connection is only created once authenticated, this is only emitted when the
+    /// connection is re-established (see [`Builder::auto_reconnect`]), before the names are
+    /// requested again.
+    ///
+    /// [`Builder::auto_reconnect`]: super::Builder::auto_reconnect
+    Authenticated,
+    /// A well-known name was acquired, through [`Connection::request_name`] or alike.
+    ///
+    /// If the name was queued, this is emitted once it's eventually acquired.
+    ///
+    /// [`Connection::request_name`]: super::Connection::request_name
+    NameAcquired(OwnedWellKnownName),
+    /// A well-known name owned by the connection was lost.
+    ///
+    /// This happens when the name is released through [`Connection::release_name`], or taken
+    /// over by another peer. The latter can only happen for names requested with
+    /// [`RequestNameFlags::AllowReplacement`].
+    ///
+    /// [`Connection::release_name`]: super::Connection::release_name
+    /// [`RequestNameFlags::AllowReplacement`]: crate::fdo::RequestNameFlags::AllowReplacement
+    NameLost(OwnedWellKnownName),
+    /// The connection to the peer was lost.
+    ///
+    /// This happens when the peer closes the connection (e.g. the bus disconnected us), on I/O
+    /// errors or after [`Connection::close`]. If automatic reconnection is enabled (see
+    /// [`Builder::auto_reconnect`]), attempts to reconnect start right after this. Otherwise, the
+    /// connection can not be used anymore.
+    ///
+    /// [`Connection::close`]: super::Connection::close
+    /// [`Builder::auto_reconnect`]: super::Builder::auto_reconnect
+    Disconnected {
+        /// The error that caused the disconnection.
+        reason: Error,
+    },
+    /// The connection was re-established, after being [`StateChange::Disconnected`].
+    ///
+    /// By then, the well-known names owned by the connection have been requested again and the
+    /// match rules re-added. Note that the connection is assigned a new unique name by the bus.
+    Reconnected,
+}
+
+/// The channel state changes are emitted through.
+///
+/// The channel only keeps the latest changes, but the last disconnection is also kept aside, so
+/// subscribers that didn't keep up still get it.
+#[derive(Clone, Debug)]
+pub(crate) struct StateChanges {
+    sender: Sender<(u64, StateChange)>,
+    receiver: InactiveReceiver<(u64, StateChange)>,
+    emitted: Arc<Mutex<Emitted>>,
+}
+
+#[derive(Debug, Default)]
+struct Emitted {
+    // The serial number of the last change.
+    serial: u64,
+    // The last disconnection, with its serial number.
+    disconnection: Option<(u64, Error)>,
+}
+
+impl StateChanges {
+    /// Create a channel keeping up to `capacity` changes for each subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (mut sender, receiver) = broadcast(capacity);
+        sender.set_overflow(true);
+        sender.set_await_active(false);
+
+        Self {
+            sender,
+            receiver: receiver.deactivate(),
+            emitted: Arc::default(),
+        }
+    }
+
+    pub fn emit(&self, change: StateChange) {
+        // Changes are numbered and sent under the lock, so they're received in order.
+        let mut emitted = self.emitted.lock().unwrap_or_else(PoisonError::into_inner);
+        emitted.serial += 1;
+        if let StateChange::Disconnected { reason } = &change {
+            emitted.disconnection = Some((emitted.serial, reason.clone()));
+        }
+        // An error only means nobody is listening.
+        let _ = self.sender.try_broadcast((emitted.serial, change));
+    }
+
+    /// A stream of the changes emitted from now on.
+    pub fn subscribe(&self) -> StateChangeStream {
+        let emitted = self.emitted.lock().unwrap_or_else(PoisonError::into_inner);
+
+        StateChangeStream {
+            receiver: self.receiver.activate_cloned(),
+            emitted: self.emitted.clone(),
+            serial: emitted.serial,
+            pending: None,
+        }
+    }
+}
+
+/// A [`Stream`] of [`StateChange`]s, created by [`Connection::receive_state_changes`].
+///
+/// If the stream is not polled for a while, the oldest changes are dropped to make room for newer
+/// ones. [`StateChange::Disconnected`] is the exception: the last one is always received, in order.
+///
+/// [`Connection::receive_state_changes`]: super::Connection::receive_state_changes
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct StateChangeStream {
+    receiver: Receiver<(u64, StateChange)>,
+    emitted: Arc<Mutex<Emitted>>,
+    // The serial number of the last change received.
+    serial: u64,
+    // The change received right after a dropped disconnection, to be yielded after it.
+    pending: Option<(u64, StateChange)>,
+}
+
+impl Stream for StateChangeStream {
+    type Item = StateChange;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let next = match this.pending.take() {
+            Some(next) => Some(next),
+            None => ready!(Pin::new(&mut this.receiver).poll_next(cx)),
+        };
+
+        // A disconnection older than the next change, or than the end of the stream, but newer
+        // than the last change received was dropped.
+        let next_serial = next.as_ref().map_or(u64::MAX, |(serial, _)| *serial);
+        let dropped = this
+            .emitted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .disconnection
+            .as_ref()
+            .filter(|(serial, _)| (this.serial + 1..next_serial).contains(serial))
+            .map(|(serial, reason)| (*serial, reason.clone()));
+        if let Some((serial, reason)) = dropped {
+            this.serial = serial;
+            this.pending = next;
+
+            return Poll::Ready(Some(StateChange::Disconnected { reason }));
+        }
+
+        Poll::Ready(next.map(|(serial, change)| {
+            this.serial = serial;
+
+            change
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::{StateChange, StateChanges};
+    use crate::Error;
+
+    fn name_acquired(i: usize) -> StateChange {
+        StateChange::NameAcquired(format!("org.zbus.Name{i}").try_into().unwrap())
+    }
+
+    #[test]
+    fn dropped_disconnection() {
+        crate::utils::block_on(async {
+            let changes = StateChanges::new(8);
+            let mut stream = changes.subscribe();
+
+            // Fill the queue, disconnect and reconnect while the stream isn't polled, so the
+            // disconnection is dropped from the queue.
+            for i in 0..8 {
+                changes.emit(name_acquired(i));
+            }
+            changes.emit(StateChange::Disconnected {
+                reason: Error::Failure("disconnected".into()),
+            });
+            changes.emit(StateChange::Authenticated);
+            for i in 0..8 {
+                changes.emit(name_acquired(i));
+            }
+            changes.emit(StateChange::Reconnected);
+
+            match stream.next().await.unwrap() {
+                StateChange::Disconnected { reason } => {
+                    assert_eq!(reason.to_string(), "disconnected")
+                }
+                change => panic!("unexpected state change: {change:?}"),
+            }
+            for i in 1..8 {
+                match stream.next().await.unwrap() {
+                    StateChange::NameAcquired(name) => {
+                        assert_eq!(name, format!("org.zbus.Name{i}").as_str())
+                    }
+                    change => panic!("unexpected state change: {change:?}"),
+                }
+            }
+            assert!(matches!(
+                stream.next().await.unwrap(),
+                StateChange::Reconnected
+            ));
+
+            // The disconnection is received once, and only by streams subscribed before it.
+            let late = changes.subscribe();
+            changes.emit(StateChange::Disconnected {
+                reason: Error::Failure("disconnected again".into()),
+            });
+            for _ in 0..8 {
+                changes.emit(StateChange::Authenticated);
+            }
+            drop(changes);
+            for mut stream in [stream, late] {
+                assert!(matches!(
+                    stream.next().await.unwrap(),
+                    StateChange::Disconnected { .. }
+                ));
+                for _ in 0..8 {
+                    assert!(matches!(
+                        stream.next().await.unwrap(),
+                        StateChange::Authenticated
+                    ));
+                }
+                assert!(stream.next().await.is_none());
+            }
+        });
+    }
+}
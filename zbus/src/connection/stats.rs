@@ -0,0 +1,231 @@
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::{
+    message::{Message, Type},
+    names::OwnedWellKnownName,
+    OwnedMatchRule,
+};
+
+/// A snapshot of the statistics of a [`Connection`].
+///
+/// Use [`Connection::stats`] to get one. The counters are maintained over the whole lifetime of the
+/// connection, including reconnections.
+///
+/// [`Connection`]: super::Connection
+/// [`Connection::stats`]: super::Connection::stats
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub(crate) incoming: MessageStats,
+    pub(crate) outgoing: MessageStats,
+    pub(crate) pending_replies: usize,
+    pub(crate) queues: Vec<QueueStats>,
+    pub(crate) match_rules: Vec<OwnedMatchRule>,
+    pub(crate) names: Vec<OwnedWellKnownName>,
+}
+
+impl Stats {
+    /// Statistics of the messages received.
+    pub fn incoming(&self) -> &MessageStats {
+        &self.incoming
+    }
+
+    /// Statistics of the messages sent.
+    pub fn outgoing(&self) -> &MessageStats {
+        &self.outgoing
+    }
+
+    /// The number of method calls sent, for which the reply is still awaited.
+    pub fn pending_replies(&self) -> usize {
+        self.pending_replies
+    }
+
+    /// The incoming message queues, one for each distinct match rule of the existing message
+    /// streams, plus the main (unfiltered) queue.
+    pub fn queues(&self) -> &[QueueStats] {
+        &self.queues
+    }
+
+    /// The match rules added on the bus for the existing message streams.
+    pub fn match_rules(&self) -> &[OwnedMatchRule] {
+        &self.match_rules
+    }
+
+    /// The well-known names owned by the connection.
+    pub fn names(&self) -> &[OwnedWellKnownName] {
+        &self.names
+    }
+}
+
+/// Statistics of the messages sent or received by a connection.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageStats {
+    pub(crate) messages: u64,
+    pub(crate) bytes: u64,
+    pub(crate) fds: u64,
+    pub(crate) method_calls: u64,
+    pub(crate) method_returns: u64,
+    pub(crate) errors: u64,
+    pub(crate) signals: u64,
+}
+
+impl MessageStats {
+    /// The total number of messages.
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    /// The total size of the messages, in bytes.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The total number of file descriptors passed along with the messages.
+    pub fn fds(&self) -> u64 {
+        self.fds
+    }
+
+    /// The number of method call messages.
+    pub fn method_calls(&self) -> u64 {
+        self.method_calls
+    }
+
+    /// The number of method return messages.
+    pub fn method_returns(&self) -> u64 {
+        self.method_returns
+    }
+
+    /// The number of error messages.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// The number of signal messages.
+    pub fn signals(&self) -> u64 {
+        self.signals
+    }
+}
+
+/// Statistics of an incoming message queue.
+#[derive(Clone, Debug)]
+pub struct QueueStats {
+    pub(crate) match_rule: Option<OwnedMatchRule>,
+    pub(crate) len: usize,
+    pub(crate) capacity: usize,
+}
+
+impl QueueStats {
+    /// The match rule of the messages in the queue, or `None` for the main (unfiltered) queue.
+    pub fn match_rule(&self) -> Option<&OwnedMatchRule> {
+        self.match_rule.as_ref()
+    }
+
+    /// The number of messages in the queue, not yet consumed by all of its streams.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The capacity of the queue.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// The counters behind [`Stats`], updated as messages are sent and received.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    incoming: MessageCounters,
+    outgoing: MessageCounters,
+    pending_replies: AtomicUsize,
+}
+
+impl Counters {
+    pub fn record_incoming(&self, msg: &Message) {
+        self.incoming.record(msg);
+    }
+
+    pub fn record_outgoing(&self, msg: &Message) {
+        self.outgoing.record(msg);
+    }
+
+    /// Count a pending reply, until the returned guard is dropped.
+    pub fn pending_reply(self: &Arc<Self>) -> PendingReply {
+        self.pending_replies.fetch_add(1, Ordering::Relaxed);
+
+        PendingReply(self.clone())
+    }
+
+    pub fn snapshot(
+        &self,
+        queues: Vec<QueueStats>,
+        match_rules: Vec<OwnedMatchRule>,
+        names: Vec<OwnedWellKnownName>,
+    ) -> Stats {
+        Stats {
+            incoming: self.incoming.snapshot(),
+            outgoing: self.outgoing.snapshot(),
+            pending_replies: self.pending_replies.load(Ordering::Relaxed),
+            queues,
+            match_rules,
+            names,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MessageCounters {
+    messages: AtomicU64,
+    bytes: AtomicU64,
+    fds: AtomicU64,
+    method_calls: AtomicU64,
+    method_returns: AtomicU64,
+    errors: AtomicU64,
+    signals: AtomicU64,
+}
+
+impl MessageCounters {
+    fn record(&self, msg: &Message) {
+        let data = msg.data();
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+        #[cfg(unix)]
+        self.fds
+            .fetch_add(data.fds().len() as u64, Ordering::Relaxed);
+        let counter = match msg.message_type() {
+            Type::MethodCall => &self.method_calls,
+            Type::MethodReturn => &self.method_returns,
+            Type::Error => &self.errors,
+            Type::Signal => &self.signals,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> MessageStats {
+        MessageStats {
+            messages: self.messages.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            fds: self.fds.load(Ordering::Relaxed),
+            method_calls: self.method_calls.load(Ordering::Relaxed),
+            method_returns: self.method_returns.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            signals: self.signals.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A guard counting a pending reply in [`Counters`], as long as it's alive.
+#[derive(Debug)]
+pub(crate) struct PendingReply(Arc<Counters>);
+
+impl Drop for PendingReply {
+    fn drop(&mut self) {
+        self.0.pending_replies.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,41 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
+
+use crate::Message;
+
+/// The direction of a message, from the point of view of a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The message was received from the peer.
+    Incoming,
+    /// The message was sent to the peer.
+    Outgoing,
+}
+
+/// An observer of all the messages going through a connection.
+pub(crate) trait Tap: Debug + Send + Sync {
+    /// Called for each message, once it's been received or sent.
+    ///
+    /// This is called from the socket reader and the senders, so it must not block.
+    fn message(&self, msg: &Message, direction: Direction);
+}
+
+/// The taps of a connection.
+#[derive(Debug, Default)]
+pub(crate) struct Taps {
+    taps: RwLock<Vec<Arc<dyn Tap>>>,
+}
+
+impl Taps {
+    pub(crate) fn add(&self, tap: Arc<dyn Tap>) {
+        self.taps.write().expect("poisoned lock").push(tap);
+    }
+
+    pub(crate) fn message(&self, msg: &Message, direction: Direction) {
+        for tap in &*self.taps.read().expect("poisoned lock") {
+            tap.message(msg, direction);
+        }
+    }
+}
@@ -60,6 +60,10 @@ pub enum Error {
     InvalidSerial,
     /// The given interface already exists at the given path.
     InterfaceExists(InterfaceName<'static>, ObjectPath<'static>),
+    /// No reply was received for a method call within the configured timeout.
+    ///
+    /// When converted into a [`fdo::Error`], this becomes [`fdo::Error::NoReply`].
+    Timeout,
 }
 
 impl PartialEq for Error {
@@ -85,6 +89,7 @@ impl PartialEq for Error {
             (Error::InputOutput(_), Self::InputOutput(_)) => false,
             (Self::Failure(s1), Self::Failure(s2)) => s1 == s2,
             (Self::InterfaceExists(s1, s2), Self::InterfaceExists(o1, o2)) => s1 == o1 && s2 == o2,
+            (Self::Timeout, Self::Timeout) => true,
             (_, _) => false,
         }
     }
@@ -114,6 +119,7 @@ impl error::Error for Error {
             Error::MissingParameter(_) => None,
             Error::InvalidSerial => None,
             Error::InterfaceExists(_, _) => None,
+            Error::Timeout => None,
         }
     }
 }
@@ -149,6 +155,7 @@ impl fmt::Display for Error {
             }
             Error::InvalidSerial => write!(f, "Serial number in the message header is 0"),
             Error::InterfaceExists(i, p) => write!(f, "Interface `{i}` already exists at `{p}`"),
+            Error::Timeout => write!(f, "Timed out waiting for a method reply"),
         }
     }
 }
@@ -179,6 +186,7 @@ impl Clone for Error {
             Error::MissingParameter(p) => Error::MissingParameter(p),
             Error::InvalidSerial => Error::InvalidSerial,
             Error::InterfaceExists(i, p) => Error::InterfaceExists(i.clone(), p.clone()),
+            Error::Timeout => Error::Timeout,
         }
     }
 }
@@ -27,7 +27,8 @@ impl Introspectable {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
         let node = root
-            .get_child(path)
+            .resolve_child(path)
+            .await
             .ok_or_else(|| Error::UnknownObject(format!("Unknown object '{path}'")))?;
 
         Ok(node.introspect().await)
@@ -13,6 +13,8 @@ pub(crate) use introspectable::Introspectable;
 pub use introspectable::IntrospectableProxy;
 
 pub(crate) mod monitoring;
+#[cfg(feature = "p2p")]
+pub use monitoring::Monitoring;
 pub use monitoring::MonitoringProxy;
 
 pub(crate) mod object_manager;
@@ -32,7 +34,7 @@ pub use properties::{
 };
 
 pub(crate) mod stats;
-pub use stats::StatsProxy;
+pub use stats::{DebugStats, StatsProxy};
 
 #[cfg(test)]
 mod tests {
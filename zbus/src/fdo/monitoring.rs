@@ -32,3 +32,242 @@ pub trait Monitoring {
     /// [`MessageStream`]: https://docs.rs/zbus/latest/zbus/struct.MessageStream.html
     fn become_monitor(self, match_rules: &[crate::MatchRule<'_>], flags: u32) -> super::Result<()>;
 }
+
+#[cfg(feature = "p2p")]
+pub use server::Monitoring;
+
+#[cfg(feature = "p2p")]
+mod server {
+    use std::sync::{Arc, Mutex, Weak};
+
+    use async_broadcast::{broadcast, Sender, TrySendError};
+    use tracing::{debug, trace};
+
+    use crate::{
+        connection::{Direction, Tap, WeakConnection},
+        fdo::{Error, Result},
+        interface, Connection, Message, OwnedMatchRule,
+    };
+
+    /// The maximum number of messages queued for a monitor before the oldest ones are dropped.
+    const MAX_QUEUED: usize = 64;
+
+    /// Service-side implementation of the [`org.freedesktop.DBus.Monitoring`][link] interface, for
+    /// peer-to-peer servers.
+    ///
+    /// This lets the peers of a p2p server become monitors, as if the server were a message bus:
+    /// once a peer has called `BecomeMonitor`, copies of the messages sent and received by the
+    /// server connections observed by this hub are forwarded to it, if they match any of its match
+    /// rules (or any message, if it gave no rule). As with a message bus, the messages exchanged
+    /// with the monitors themselves aren't forwarded, and a monitor isn't expected to send any more
+    /// messages. A monitor that doesn't keep up with the traffic misses the oldest messages.
+    ///
+    /// As with the reference message bus, only the peers running as the same user as the server,
+    /// or as root, are allowed to become monitors. Others get an `AccessDenied` error, including
+    /// the peers whose user is unknown (e.g. connected over TCP on Unix).
+    ///
+    /// All the clones of a `Monitoring` share the same monitors, so the same hub is typically set
+    /// on all the server connections through [`connection::Builder::monitoring`] or
+    /// [`listener::Builder::monitoring`], which serve it at `/org/freedesktop/DBus` and make the
+    /// connection observed.
+    ///
+    /// This type is only available when the `p2p` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use zbus::{connection::Listener, fdo::Monitoring};
+    /// # use zbus::block_on;
+    ///
+    /// # block_on(async {
+    /// let listener = Listener::builder("unix:runtime=yes")?
+    ///     .monitoring(Monitoring::new())?
+    ///     .build()
+    ///     .await?;
+    ///
+    /// // `dbus-monitor --address` works against the listener's address now.
+    /// let mut incoming = listener.incoming();
+    /// while let Some(conn) = incoming.next().await {
+    ///     // Keep the connection around..
+    /// #   drop(conn);
+    /// }
+    /// #     Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// [link]: https://dbus.freedesktop.org/doc/dbus-specification.html#bus-messages-become-monitor
+    /// [`connection::Builder::monitoring`]: crate::connection::Builder::monitoring
+    /// [`listener::Builder::monitoring`]: crate::connection::listener::Builder::monitoring
+    #[derive(Clone, Debug, Default)]
+    pub struct Monitoring {
+        monitors: Arc<Monitors>,
+    }
+
+    impl Monitoring {
+        /// Create a monitoring hub, without any monitor.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Forward the messages sent and received by `conn` from now on to the monitors.
+        ///
+        /// There is no need to call this for the connections built with
+        /// [`connection::Builder::monitoring`].
+        ///
+        /// [`connection::Builder::monitoring`]: crate::connection::Builder::monitoring
+        pub fn observe(&self, conn: &Connection) {
+            conn.add_tap(Arc::new(MonitoredConnection {
+                conn: conn.into(),
+                monitors: Arc::downgrade(&self.monitors),
+            }));
+        }
+
+        /// The number of monitors currently connected.
+        pub fn monitor_count(&self) -> usize {
+            let mut monitors = self.monitors.monitors.lock().expect("poisoned lock");
+            monitors.retain(|m| !m.sender.is_closed());
+
+            monitors.len()
+        }
+    }
+
+    #[interface(
+        name = "org.freedesktop.DBus.Monitoring",
+        introspection_docs = false,
+        spawn = false
+    )]
+    impl Monitoring {
+        /// Convert the calling peer into a monitor, receiving copies of the messages matching
+        /// `match_rules` (or all messages, if empty).
+        async fn become_monitor(
+            &self,
+            match_rules: Vec<OwnedMatchRule>,
+            _flags: u32,
+            #[zbus(connection)] conn: &Connection,
+        ) -> Result<()> {
+            authorize(conn).await?;
+
+            let (mut sender, mut receiver) = broadcast::<Message>(MAX_QUEUED);
+            sender.set_overflow(true);
+            let monitor = conn.clone();
+            conn.executor()
+                .spawn(
+                    async move {
+                        while let Ok(msg) = receiver.recv_direct().await {
+                            if let Err(e) = monitor.send(&msg).await {
+                                debug!("Failed to forward a message to a monitor: {e}");
+
+                                break;
+                            }
+                        }
+                    },
+                    "monitor",
+                )
+                .detach();
+
+            trace!("Peer became a monitor, with rules {match_rules:?}");
+            self.monitors
+                .monitors
+                .lock()
+                .expect("poisoned lock")
+                .push(Monitor {
+                    conn: conn.into(),
+                    match_rules,
+                    sender,
+                });
+
+            Ok(())
+        }
+    }
+
+    // Check that the peer of `conn` runs as the same user as us, or as root.
+    async fn authorize(conn: &Connection) -> Result<()> {
+        let credentials = conn
+            .peer_credentials()
+            .await
+            .map_err(|e| Error::Failed(format!("Failed to get the peer credentials: {e}")))?;
+
+        #[cfg(unix)]
+        let allowed = {
+            let uid = nix::unistd::Uid::effective().as_raw();
+
+            matches!(credentials.unix_user_id(), Some(id) if id == 0 || id == uid)
+        };
+        #[cfg(windows)]
+        let allowed = {
+            let sid = crate::win32::ProcessToken::open(None)
+                .and_then(|token| token.sid())
+                .map_err(|e| Error::Failed(format!("Failed to get the process SID: {e}")))?;
+
+            credentials.windows_sid() == Some(&sid)
+        };
+
+        if !allowed {
+            debug!("Denied `BecomeMonitor` to peer with {credentials:?}");
+
+            return Err(Error::AccessDenied(
+                "Only the same user as the server, or root, can become a monitor".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[derive(Debug, Default)]
+    struct Monitors {
+        monitors: Mutex<Vec<Monitor>>,
+    }
+
+    impl Monitors {
+        fn forward(&self, source: &WeakConnection, msg: &Message) {
+            let mut monitors = self.monitors.lock().expect("poisoned lock");
+            // The messages of the monitors are not monitored.
+            if monitors.iter().any(|m| m.conn.ptr_eq(source)) {
+                return;
+            }
+
+            monitors.retain(|monitor| {
+                if !monitor.matches(msg) {
+                    return true;
+                }
+
+                !matches!(
+                    monitor.sender.try_broadcast(msg.clone()),
+                    Err(TrySendError::Closed(_))
+                )
+            });
+        }
+    }
+
+    #[derive(Debug)]
+    struct Monitor {
+        conn: WeakConnection,
+        match_rules: Vec<OwnedMatchRule>,
+        sender: Sender<Message>,
+    }
+
+    impl Monitor {
+        fn matches(&self, msg: &Message) -> bool {
+            self.match_rules.is_empty()
+                || self
+                    .match_rules
+                    .iter()
+                    .any(|rule| rule.matches(msg).unwrap_or(false))
+        }
+    }
+
+    #[derive(Debug)]
+    struct MonitoredConnection {
+        conn: WeakConnection,
+        monitors: Weak<Monitors>,
+    }
+
+    impl Tap for MonitoredConnection {
+        fn message(&self, msg: &Message, _direction: Direction) {
+            if let Some(monitors) = self.monitors.upgrade() {
+                monitors.forward(&self.conn, msg);
+            }
+        }
+    }
+}
@@ -54,7 +54,8 @@ impl ObjectManager {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
         let node = root
-            .get_child(path)
+            .resolve_child(path)
+            .await
             .ok_or_else(|| Error::UnknownObject(format!("Unknown object '{path}'")))?;
 
         node.get_managed_objects(server, connection).await
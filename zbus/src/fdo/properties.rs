@@ -34,7 +34,8 @@ impl Properties {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
         let iface = root
-            .get_child(path)
+            .resolve_child(path)
+            .await
             .and_then(|node| node.interface_lock(interface_name.as_ref()))
             .ok_or_else(|| {
                 Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
@@ -68,7 +69,8 @@ impl Properties {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
         let iface = root
-            .get_child(path)
+            .resolve_child(path)
+            .await
             .and_then(|node| node.interface_lock(interface_name.as_ref()))
             .ok_or_else(|| {
                 Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
@@ -89,7 +91,10 @@ impl Properties {
                 )));
             }
             zbus::object_server::DispatchResult::Async(f) => {
-                return f.await.map_err(Into::into);
+                return f.await.map_err(|e| match e {
+                    zbus::Error::FDO(e) => *e,
+                    e => e.into(),
+                });
             }
         }
         let res = iface
@@ -124,7 +129,8 @@ impl Properties {
         let path = header.path().ok_or(crate::Error::MissingField)?;
         let root = server.root().read().await;
         let iface = root
-            .get_child(path)
+            .resolve_child(path)
+            .await
             .and_then(|node| node.interface_lock(interface_name.as_ref()))
             .ok_or_else(|| {
                 Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
@@ -8,8 +8,8 @@ use std::collections::HashMap;
 use zbus_names::{BusName, OwnedUniqueName};
 use zvariant::{as_value::optional, OwnedValue, Type};
 
-use super::Result;
-use crate::proxy;
+use super::{Error, Result};
+use crate::{interface, proxy, Connection, OwnedMatchRule};
 
 /// Proxy for the [`org.freedesktop.DBus.Debug.Stats`][link] interface.
 ///
@@ -270,3 +270,124 @@ impl ConnectionStats {
         &self.rest
     }
 }
+
+/// Service-side implementation of the [`org.freedesktop.DBus.Debug.Stats`][link] interface.
+///
+/// This exposes the statistics of the connection it's served on (see [`Connection::stats`]), as if
+/// it were the only connection of a bus. It's not served by default, you need to add it to the
+/// [`ObjectServer`](crate::ObjectServer) yourself:
+///
+/// ```no_run
+/// # use zbus::{block_on, connection::Builder, fdo::DebugStats};
+/// #
+/// # block_on(async {
+/// let _conn = Builder::session()?
+///     .serve_at("/org/zbus/MyService", DebugStats::default())?
+///     .build()
+///     .await?;
+/// #     Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// Besides the counters defined by the specification, the connection stats include the number of
+/// messages of each type (e.g. `IncomingMethodCalls` or `OutgoingSignals`) and the number of
+/// `PendingReplies`.
+///
+/// [link]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-bus-debug-stats-interface
+#[derive(Debug, Default)]
+pub struct DebugStats {
+    serial: u32,
+}
+
+#[interface(name = "org.freedesktop.DBus.Debug.Stats", introspection_docs = false)]
+impl DebugStats {
+    /// Get statistics about the connection, as the only one of the bus.
+    async fn get_stats(&mut self, #[zbus(connection)] conn: &Connection) -> Stats {
+        let stats = conn.stats().await;
+
+        Stats {
+            serial: Some(self.next_serial()),
+            active_connections: Some(1),
+            incomplete_connections: Some(0),
+            match_rules: Some(saturating_u32(stats.match_rules().len())),
+            bus_names: Some(saturating_u32(stats.names().len())),
+            ..Default::default()
+        }
+    }
+
+    /// Get statistics about the connection, identified by its unique name or by a well-known name
+    /// it owns.
+    async fn get_connection_stats(
+        &mut self,
+        name: BusName<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<ConnectionStats> {
+        let stats = conn.stats().await;
+        let is_ours = match &name {
+            BusName::Unique(name) => conn.unique_name().is_some_and(|n| n == name),
+            BusName::WellKnown(name) => stats.names().iter().any(|n| n == name),
+        };
+        if !is_ours {
+            return Err(Error::NameHasNoOwner(format!(
+                "Unknown connection `{name}`"
+            )));
+        }
+
+        let (incoming, outgoing) = (stats.incoming(), stats.outgoing());
+        let mut rest = HashMap::new();
+        for (direction, counts) in [("Incoming", incoming), ("Outgoing", outgoing)] {
+            for (kind, count) in [
+                ("MethodCalls", counts.method_calls()),
+                ("MethodReturns", counts.method_returns()),
+                ("Errors", counts.errors()),
+                ("Signals", counts.signals()),
+            ] {
+                rest.insert(format!("{direction}{kind}"), count.into());
+            }
+        }
+        rest.insert(
+            "PendingReplies".to_string(),
+            (stats.pending_replies() as u64).into(),
+        );
+
+        Ok(ConnectionStats {
+            serial: Some(self.next_serial()),
+            unique_name: conn.unique_name().cloned(),
+            match_rules: Some(saturating_u32(stats.match_rules().len())),
+            bus_names: Some(saturating_u32(stats.names().len())),
+            incoming_messages: Some(saturating_u32(incoming.messages())),
+            outgoing_messages: Some(saturating_u32(outgoing.messages())),
+            incoming_bytes: Some(saturating_u32(incoming.bytes())),
+            outgoing_bytes: Some(saturating_u32(outgoing.bytes())),
+            incoming_fds: Some(saturating_u32(incoming.fds())),
+            outgoing_fds: Some(saturating_u32(outgoing.fds())),
+            rest,
+            ..Default::default()
+        })
+    }
+
+    /// List the match rules added by the connection, keyed by its unique name.
+    async fn get_all_match_rules(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+    ) -> HashMap<OwnedUniqueName, Vec<OwnedMatchRule>> {
+        let stats = conn.stats().await;
+
+        conn.unique_name()
+            .map(|name| (name.clone(), stats.match_rules().to_vec()))
+            .into_iter()
+            .collect()
+    }
+}
+
+impl DebugStats {
+    fn next_serial(&mut self) -> u32 {
+        self.serial = self.serial.wrapping_add(1);
+
+        self.serial
+    }
+}
+
+fn saturating_u32<T: TryInto<u32>>(value: T) -> u32 {
+    value.try_into().unwrap_or(u32::MAX)
+}
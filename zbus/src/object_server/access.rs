@@ -0,0 +1,426 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock, RwLock, Weak},
+};
+
+use futures_lite::StreamExt;
+use tracing::{debug, info_span, trace, Instrument};
+use zbus_names::{
+    InterfaceName, MemberName, OwnedInterfaceName, OwnedMemberName, OwnedUniqueName, UniqueName,
+};
+use zvariant::{ObjectPath, OwnedObjectPath, Value};
+
+use crate::{
+    async_lock::Mutex,
+    fdo::{self, ConnectionCredentials},
+    message::{Header, Message},
+    Connection, MatchRule, Result, Task,
+};
+
+type Callback =
+    dyn Fn(AccessRequest) -> Pin<Box<dyn Future<Output = fdo::Result<()>> + Send>> + Send + Sync;
+
+/// What a caller attempts to access, as part of an [`AccessRequest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Access {
+    /// Call a method of the interface.
+    Method(OwnedMemberName),
+    /// Get the value of a property of the interface, through `org.freedesktop.DBus.Properties`.
+    GetProperty(String),
+    /// Set the value of a property of the interface, through `org.freedesktop.DBus.Properties`.
+    SetProperty(String),
+    /// Get the values of all the properties of the interface, through
+    /// `org.freedesktop.DBus.Properties`.
+    GetAllProperties,
+}
+
+impl Access {
+    /// What the method call `msg` accesses, along with the interface being accessed.
+    pub(crate) fn from_call(
+        msg: &Message,
+        interface: &InterfaceName<'_>,
+        member: &MemberName<'_>,
+    ) -> Result<(OwnedInterfaceName, Self)> {
+        if *interface != "org.freedesktop.DBus.Properties" {
+            return Ok((
+                interface.to_owned().into(),
+                Access::Method(member.to_owned().into()),
+            ));
+        }
+
+        let body = msg.body();
+        let access = match member.as_str() {
+            "Get" => {
+                let (interface, property): (InterfaceName<'_>, &str) = body.deserialize()?;
+                (interface.into(), Access::GetProperty(property.to_owned()))
+            }
+            "Set" => {
+                let (interface, property, _): (InterfaceName<'_>, &str, Value<'_>) =
+                    body.deserialize()?;
+                (interface.into(), Access::SetProperty(property.to_owned()))
+            }
+            "GetAll" => {
+                let interface: InterfaceName<'_> = body.deserialize()?;
+                (interface.into(), Access::GetAllProperties)
+            }
+            _ => (
+                interface.to_owned().into(),
+                Access::Method(member.to_owned().into()),
+            ),
+        };
+
+        Ok(access)
+    }
+}
+
+/// A request to access an interface, checked by the access policy of an [`ObjectServer`].
+///
+/// See [`ObjectServer::set_access_policy`] for details.
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::set_access_policy`]: crate::ObjectServer::set_access_policy
+#[derive(Debug)]
+pub struct AccessRequest {
+    message: Message,
+    path: OwnedObjectPath,
+    interface: OwnedInterfaceName,
+    access: Access,
+    credentials: Arc<ConnectionCredentials>,
+}
+
+impl AccessRequest {
+    /// The method call message of the caller.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// The header of the method call message.
+    pub fn header(&self) -> Header<'_> {
+        self.message.header()
+    }
+
+    /// The path of the object being accessed.
+    pub fn path(&self) -> ObjectPath<'_> {
+        self.path.as_ref()
+    }
+
+    /// The interface being accessed.
+    ///
+    /// For accesses to properties, this is the interface of the property, not
+    /// `org.freedesktop.DBus.Properties`.
+    pub fn interface(&self) -> InterfaceName<'_> {
+        self.interface.as_ref()
+    }
+
+    /// What is being accessed.
+    pub fn access(&self) -> &Access {
+        &self.access
+    }
+
+    /// The credentials of the caller.
+    ///
+    /// On a bus connection, these are the credentials the bus provides for the sender of the
+    /// message. On a peer-to-peer connection, these are the credentials of the peer. Which
+    /// credentials are available depends on the platform and the socket type.
+    pub fn credentials(&self) -> &Arc<ConnectionCredentials> {
+        &self.credentials
+    }
+}
+
+/// An access policy, as set through [`crate::ObjectServer::set_access_policy`].
+#[derive(Clone)]
+pub(crate) struct AccessPolicy(Arc<Callback>);
+
+impl AccessPolicy {
+    pub fn new<F, Fut>(policy: F) -> Self
+    where
+        F: Fn(AccessRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        Self(Arc::new(move |request| Box::pin(policy(request))))
+    }
+}
+
+impl fmt::Debug for AccessPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessPolicy").finish_non_exhaustive()
+    }
+}
+
+/// The access policy of an `ObjectServer`, along with the credentials of the callers.
+#[derive(Default)]
+pub(crate) struct AccessControl {
+    policy: RwLock<Option<AccessPolicy>>,
+    // The credentials of the callers on a bus.
+    credentials: Arc<Mutex<CredentialsCache>>,
+    // The credentials of the peer on a peer-to-peer connection.
+    peer_credentials: OnceLock<Arc<ConnectionCredentials>>,
+    // Removes the credentials of the callers leaving the bus from `credentials`.
+    invalidation_task: OnceLock<Task<()>>,
+}
+
+impl AccessControl {
+    pub fn set_policy(&self, policy: AccessPolicy) {
+        *self.policy.write().expect("poisoned lock") = Some(policy);
+    }
+
+    pub fn remove_policy(&self) {
+        *self.policy.write().expect("poisoned lock") = None;
+    }
+
+    /// Check the method call `msg` against the policy, if any.
+    pub async fn check(&self, connection: &Connection, msg: &Message) -> fdo::Result<()> {
+        let policy = match self.policy.read().expect("poisoned lock").clone() {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+
+        let hdr = msg.header();
+        let (path, interface, member) = match (hdr.path(), hdr.interface(), hdr.member()) {
+            (Some(path), Some(interface), Some(member)) => (path, interface, member),
+            // Let the dispatcher report the missing fields.
+            _ => return Ok(()),
+        };
+        let (interface, access) = Access::from_call(msg, interface, member)?;
+
+        let credentials = self
+            .credentials(connection, hdr.sender())
+            .await
+            .map_err(|e| {
+                debug!("Failed to get the credentials of the caller: {e}");
+                fdo::Error::AccessDenied("Failed to get the credentials of the caller".into())
+            })?;
+        let request = AccessRequest {
+            message: msg.clone(),
+            path: path.to_owned().into(),
+            interface,
+            access,
+            credentials,
+        };
+        trace!("Checking {request:?}");
+
+        (policy.0)(request).await
+    }
+
+    async fn credentials(
+        &self,
+        connection: &Connection,
+        sender: Option<&UniqueName<'_>>,
+    ) -> Result<Arc<ConnectionCredentials>> {
+        let sender = match sender.filter(|_| connection.is_bus()) {
+            Some(sender) => sender,
+            None => {
+                if let Some(credentials) = self.peer_credentials.get() {
+                    return Ok(credentials.clone());
+                }
+                let credentials = Arc::new(connection.peer_credentials().await?);
+
+                return Ok(self.peer_credentials.get_or_init(|| credentials).clone());
+            }
+        };
+
+        let generation = {
+            let cache = self.credentials.lock().await;
+            if let Some(credentials) = cache.credentials.get(sender.as_str()) {
+                return Ok(credentials.clone());
+            }
+
+            cache.generation
+        };
+        self.watch_name_owners(connection).await?;
+        let credentials: ConnectionCredentials = connection
+            .call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "GetConnectionCredentials",
+                sender,
+            )
+            .await?
+            .body()
+            .deserialize()?;
+        let credentials = Arc::new(credentials);
+        self.credentials
+            .lock()
+            .await
+            .insert(sender, credentials.clone(), generation);
+
+        Ok(credentials)
+    }
+
+    // Start the task removing the credentials of the callers, once they leave the bus.
+    async fn watch_name_owners(&self, connection: &Connection) -> Result<()> {
+        if self.invalidation_task.get().is_some() {
+            return Ok(());
+        }
+
+        // Only the names without a new owner.
+        let rule = MatchRule::fdo_signal_builder("NameOwnerChanged")
+            .arg(2, "")?
+            .build();
+        // The task is owned by the connection (through the object server), so it must not keep a
+        // strong reference to the connection or to us. The receiver doesn't and ends along with
+        // the connection.
+        let mut stream = connection.add_match(rule.into(), None).await?;
+        let credentials = Arc::downgrade(&self.credentials);
+        let task_name = "credentials cache invalidation";
+        let task = connection.executor().spawn(
+            async move {
+                while let Some(msg) = stream.next().await {
+                    let msg = match msg {
+                        Ok(msg) => msg,
+                        Err(e) => {
+                            debug!("Failed to receive `NameOwnerChanged` signal: {e}");
+                            continue;
+                        }
+                    };
+                    let body = msg.body();
+                    let Ok((name, _, _)) = body.deserialize::<(&str, &str, &str)>() else {
+                        continue;
+                    };
+                    match Weak::upgrade(&credentials) {
+                        Some(credentials) => credentials.lock().await.remove(name),
+                        None => break,
+                    }
+                }
+            }
+            .instrument(info_span!("{}", task_name)),
+            task_name,
+        );
+        let _ = self.invalidation_task.set(task);
+
+        Ok(())
+    }
+}
+
+/// The credentials of the callers on a bus, by unique name.
+#[derive(Debug, Default)]
+struct CredentialsCache {
+    credentials: HashMap<OwnedUniqueName, Arc<ConnectionCredentials>>,
+    // Incremented whenever a caller leaves the bus.
+    generation: u64,
+}
+
+impl CredentialsCache {
+    // Cache the `credentials` of `name`, requested at `generation`.
+    //
+    // If a caller left the bus in the meantime, `name` may be that caller, and its removal would
+    // have been processed already. So the credentials are not cached then, to not keep them around
+    // forever.
+    fn insert(
+        &mut self,
+        name: &UniqueName<'_>,
+        credentials: Arc<ConnectionCredentials>,
+        generation: u64,
+    ) {
+        if generation == self.generation {
+            self.credentials.insert(name.to_owned().into(), credentials);
+        }
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.credentials.remove(name);
+        self.generation += 1;
+    }
+}
+
+impl fmt::Debug for AccessControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessControl")
+            .field("policy", &self.policy.read().expect("poisoned lock"))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::{abstractions::sleep, connection::Builder, fdo::DBusProxy};
+
+    #[test]
+    fn cache_generations() {
+        let name = UniqueName::from_static_str(":1.42").unwrap();
+        let credentials = Arc::new(ConnectionCredentials::default());
+        let mut cache = CredentialsCache::default();
+
+        // A caller left the bus while the credentials were requested.
+        let generation = cache.generation;
+        cache.remove(":1.41");
+        cache.insert(&name, credentials.clone(), generation);
+        assert!(cache.credentials.is_empty());
+
+        cache.insert(&name, credentials, cache.generation);
+        assert!(cache.credentials.contains_key(name.as_str()));
+        cache.remove(name.as_str());
+        assert!(cache.credentials.is_empty());
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn credentials_cache() {
+        crate::block_on(async {
+            let service = Builder::session()
+                .unwrap()
+                .access_policy(|_| async { Ok(()) })
+                .build()
+                .await
+                .unwrap();
+            let service_name = service.unique_name().unwrap().clone();
+            let server = service.object_server();
+
+            let client = Connection::session().await.unwrap();
+            let client_name = client.unique_name().unwrap().clone();
+            client
+                .call_method(
+                    Some(&service_name),
+                    "/",
+                    Some("org.freedesktop.DBus.Introspectable"),
+                    "Introspect",
+                    &(),
+                )
+                .await
+                .unwrap();
+            let cached = server
+                .access
+                .credentials
+                .lock()
+                .await
+                .credentials
+                .contains_key(&client_name);
+            assert!(cached);
+
+            // The credentials are dropped once the caller leaves the bus.
+            drop(client);
+            while server
+                .access
+                .credentials
+                .lock()
+                .await
+                .credentials
+                .contains_key(&client_name)
+            {
+                sleep(Duration::from_millis(10)).await;
+            }
+
+            // The invalidation task doesn't keep the connection alive.
+            let dbus = DBusProxy::new(&Connection::session().await.unwrap())
+                .await
+                .unwrap();
+            let mut stream = dbus
+                .receive_name_owner_changed_with_args(&[(0, service_name.as_str()), (2, "")])
+                .await
+                .unwrap();
+            drop(service);
+            stream.next().await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,794 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use zbus_names::{InterfaceName, MemberName, UniqueName};
+use zbus_xml::{Annotation, Arg, ArgDirection, PropertyAccess};
+use zvariant::{ObjectPath, OwnedValue, Signature, StructureBuilder, Value};
+
+use crate::{
+    fdo,
+    message::{Flags, Header, Message},
+    object_server::SignalEmitter,
+    Connection, Error, ObjectServer, Result,
+};
+
+use super::{ArcInterface, DispatchResult, Interface};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type MethodHandler = Arc<
+    dyn Fn(DynamicContext, Vec<OwnedValue>) -> BoxFuture<fdo::Result<Vec<OwnedValue>>>
+        + Send
+        + Sync,
+>;
+type Getter = Arc<dyn Fn(DynamicContext) -> BoxFuture<fdo::Result<OwnedValue>> + Send + Sync>;
+type Setter = Arc<dyn Fn(DynamicContext, OwnedValue) -> BoxFuture<fdo::Result<()>> + Send + Sync>;
+
+const EMITS_CHANGED_SIGNAL: &str = "org.freedesktop.DBus.Property.EmitsChangedSignal";
+
+/// An interface defined at runtime, from its introspection description.
+///
+/// This is an alternative to the [`crate::interface`] macro, for interfaces only known at runtime
+/// (e.g. read from a configuration file). Use [`DynamicInterface::builder`] to create one, by
+/// providing the description of the interface and an async closure for each of its methods and
+/// properties. Then register it with [`ObjectServer::at_dynamic`].
+///
+/// The bodies of incoming method calls and property values are validated against the signatures
+/// declared in the description, before they're passed to the closures. So are the values returned
+/// by the closures.
+///
+/// `DynamicInterface` is cheap to clone. Keep a clone around to emit the signals of the interface
+/// from outside of its closures, through [`DynamicInterface::emit_signal`].
+///
+/// This type is only available when the `dynamic-interface` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// use zbus::{object_server::DynamicInterface, zvariant::Str, Connection};
+///
+/// # async_io::block_on(async {
+/// let xml = r#"
+///   <node>
+///     <interface name="org.myservice.Greeter">
+///       <method name="Greet">
+///         <arg name="name" type="s" direction="in"/>
+///         <arg type="s" direction="out"/>
+///       </method>
+///       <property name="Greetings" type="u" access="read"/>
+///     </interface>
+///   </node>
+/// "#;
+/// let node = zbus_xml::Node::from_reader(xml.as_bytes())?;
+/// let iface = DynamicInterface::builder(node.interfaces()[0].clone())
+///     .method("Greet", |_ctxt, args| async move {
+///         let name: &str = args[0].downcast_ref().map_err(zbus::Error::from)?;
+///
+///         Ok(vec![Str::from(format!("Hello {name}!")).into()])
+///     })
+///     .getter("Greetings", |_ctxt| async { Ok(42u32.into()) })
+///     .build()?;
+///
+/// let connection = Connection::session().await?;
+/// connection
+///     .object_server()
+///     .at_dynamic("/org/myservice/Greeter", iface)
+///     .await?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`ObjectServer::at_dynamic`]: crate::ObjectServer::at_dynamic
+#[derive(Clone)]
+pub struct DynamicInterface {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    description: zbus_xml::Interface<'static>,
+    methods: HashMap<String, MethodHandler>,
+    getters: HashMap<String, Getter>,
+    setters: HashMap<String, Setter>,
+}
+
+impl DynamicInterface {
+    /// Create a builder for an interface with the given description.
+    pub fn builder(description: zbus_xml::Interface<'static>) -> DynamicInterfaceBuilder {
+        DynamicInterfaceBuilder {
+            description,
+            methods: HashMap::new(),
+            getters: HashMap::new(),
+            setters: HashMap::new(),
+        }
+    }
+
+    /// The name of the interface.
+    pub fn name(&self) -> InterfaceName<'_> {
+        self.inner.description.name()
+    }
+
+    /// The description of the interface.
+    pub fn description(&self) -> &zbus_xml::Interface<'static> {
+        &self.inner.description
+    }
+
+    /// Emit a signal of the interface.
+    ///
+    /// # Errors
+    ///
+    /// If the interface doesn't declare the signal, an `Error::FDO` error with
+    /// [`fdo::Error::UnknownMethod`] is returned. If `args` don't match the arguments declared by
+    /// the signal, an `Error::Variant` error with [`zvariant::Error::SignatureMismatch`] is
+    /// returned.
+    pub async fn emit_signal(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        signal_name: &str,
+        args: Vec<Value<'_>>,
+    ) -> Result<()> {
+        let signal = self
+            .inner
+            .description
+            .signals()
+            .iter()
+            .find(|s| s.name() == signal_name)
+            .ok_or_else(|| fdo::Error::UnknownMethod(format!("Unknown signal '{signal_name}'")))?;
+        let values: Vec<_> = args.iter().collect();
+        check_values(&values, signal.args().iter())?;
+
+        match body(args)? {
+            Some(body) => emitter.emit(self.name(), signal_name, &body).await,
+            None => emitter.emit(self.name(), signal_name, &()).await,
+        }
+    }
+
+    /// Turn the interface into the registration to add to an object, keyed by its name.
+    pub(crate) fn into_arc_interface(self) -> (InterfaceName<'static>, ArcInterface) {
+        let registered = Registered {
+            name: self.name().to_owned(),
+            iface: self,
+        };
+
+        (registered.name.clone(), ArcInterface::new(registered))
+    }
+
+    fn property(&self, name: &str) -> Option<&zbus_xml::Property<'_>> {
+        self.inner
+            .description
+            .properties()
+            .iter()
+            .find(|p| p.name() == name)
+    }
+
+    fn context(
+        &self,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> DynamicContext {
+        DynamicContext {
+            iface: self.clone(),
+            connection: connection.clone(),
+            emitter: emitter.clone().into_owned(),
+            sender: header.and_then(|h| h.sender()).map(|s| s.to_owned()),
+            message: None,
+        }
+    }
+
+    async fn get(&self, name: &str, ctxt: DynamicContext) -> fdo::Result<OwnedValue> {
+        let getter = self.inner.getters.get(name).ok_or_else(|| {
+            fdo::Error::NotSupported(format!("Property '{name}' is not readable"))
+        })?;
+        let value = getter(ctxt).await?;
+        // SAFETY: Getters are only registered for declared properties.
+        let expected = self.property(name).unwrap().ty().inner();
+        if !matches_property(&value, expected) {
+            return Err(fdo::Error::Failed(format!(
+                "Property '{name}' has the signature '{}' instead of '{expected}'",
+                value.value_signature(),
+            )));
+        }
+
+        Ok(value)
+    }
+
+    async fn set(&self, name: &str, value: &Value<'_>, ctxt: DynamicContext) -> fdo::Result<()> {
+        // SAFETY: The caller ensures the property is declared.
+        let property = self.property(name).unwrap();
+        let setter = self.inner.setters.get(name).ok_or_else(|| {
+            fdo::Error::PropertyReadOnly(format!("Property '{name}' is read-only"))
+        })?;
+        let expected = property.ty().inner();
+        if !matches_property(value, expected) {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Property '{name}' expects the signature '{expected}', got '{}'",
+                value.value_signature(),
+            )));
+        }
+        let value = OwnedValue::try_from(value).map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        let emitter = ctxt.emitter.clone();
+        let emits_changed = annotation(property.annotations(), EMITS_CHANGED_SIGNAL);
+        let (changed, invalidated) = match emits_changed.unwrap_or("true") {
+            "true" => {
+                let value = value
+                    .try_clone()
+                    .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+                (HashMap::from([(name, Value::from(value))]), vec![])
+            }
+            "invalidates" => (HashMap::new(), vec![name]),
+            _ => {
+                return setter(ctxt, value).await;
+            }
+        };
+        setter(ctxt, value).await?;
+        fdo::Properties::properties_changed(&emitter, self.name(), changed, invalidated.into())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn call(&self, connection: &Connection, msg: &Message) -> fdo::Result<Vec<OwnedValue>> {
+        let hdr = msg.header();
+        // SAFETY: The message is only dispatched to us for a declared method.
+        let name = hdr.member().unwrap().as_str();
+        let method = self
+            .inner
+            .description
+            .methods()
+            .iter()
+            .find(|m| m.name() == name)
+            .unwrap();
+        let in_args = method
+            .args()
+            .iter()
+            .filter(|a| a.direction() != Some(ArgDirection::Out));
+        // Parsed the same way as the signature of the message body, so a single structure argument
+        // and the same fields as separate arguments compare equal, as they do on the wire.
+        let expected = Signature::try_from(signature(in_args.clone()).as_str())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let body = msg.body();
+        if *body.signature() != expected {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Method '{name}' expects the signature '{}', got '{}'",
+                expected.to_string_no_parens(),
+                body.signature().to_string_no_parens(),
+            )));
+        }
+        let args = match in_args.count() {
+            0 => vec![],
+            // The body is deserialized as a structure of its arguments, so a single structure
+            // argument needs to be kept whole rather than split into its fields.
+            1 if matches!(expected, Signature::Structure(_)) => {
+                let arg: zvariant::Structure<'_> = body.deserialize()?;
+                let arg = OwnedValue::try_from(Value::from(arg))
+                    .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+                vec![arg]
+            }
+            _ => {
+                let args: zvariant::Structure<'_> = body.deserialize()?;
+                args.into_fields()
+                    .into_iter()
+                    .map(OwnedValue::try_from)
+                    .collect::<zvariant::Result<_>>()
+                    .map_err(|e| fdo::Error::Failed(e.to_string()))?
+            }
+        };
+
+        let ctxt = DynamicContext {
+            iface: self.clone(),
+            connection: connection.clone(),
+            emitter: SignalEmitter::new(connection, hdr.path().unwrap().to_owned())?,
+            sender: hdr.sender().map(|s| s.to_owned()),
+            message: Some(msg.clone()),
+        };
+        // SAFETY: `DynamicInterfaceBuilder::build` ensures all methods have a handler.
+        let handler = &self.inner.methods[name];
+        let ret = handler(ctxt, args).await?;
+        let out_args = method
+            .args()
+            .iter()
+            .filter(|a| a.direction() == Some(ArgDirection::Out));
+        check_values(&ret, out_args).map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(ret)
+    }
+}
+
+impl fmt::Debug for DynamicInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicInterface")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A builder for [`DynamicInterface`].
+///
+/// Each method declared by the interface needs a handler, and each of its properties needs a
+/// getter and/or setter, depending on their access.
+#[must_use]
+pub struct DynamicInterfaceBuilder {
+    description: zbus_xml::Interface<'static>,
+    methods: HashMap<String, MethodHandler>,
+    getters: HashMap<String, Getter>,
+    setters: HashMap<String, Setter>,
+}
+
+impl DynamicInterfaceBuilder {
+    /// Set the handler of a method.
+    ///
+    /// The handler is given the input arguments of the call and returns the output arguments.
+    pub fn method<F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        F: Fn(DynamicContext, Vec<OwnedValue>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<Vec<OwnedValue>>> + Send + 'static,
+    {
+        let handler: MethodHandler = Arc::new(move |ctxt, args| Box::pin(handler(ctxt, args)));
+        self.methods.insert(name.to_owned(), handler);
+
+        self
+    }
+
+    /// Set the getter of a property.
+    pub fn getter<F, Fut>(mut self, name: &str, getter: F) -> Self
+    where
+        F: Fn(DynamicContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<OwnedValue>> + Send + 'static,
+    {
+        let getter: Getter = Arc::new(move |ctxt| Box::pin(getter(ctxt)));
+        self.getters.insert(name.to_owned(), getter);
+
+        self
+    }
+
+    /// Set the setter of a property.
+    ///
+    /// Unless the property is annotated otherwise, the `PropertiesChanged` signal is emitted on
+    /// your behalf, once the setter returns successfully.
+    pub fn setter<F, Fut>(mut self, name: &str, setter: F) -> Self
+    where
+        F: Fn(DynamicContext, OwnedValue) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        let setter: Setter = Arc::new(move |ctxt, value| Box::pin(setter(ctxt, value)));
+        self.setters.insert(name.to_owned(), setter);
+
+        self
+    }
+
+    /// Build the interface, consuming the builder.
+    ///
+    /// # Errors
+    ///
+    /// An `Error::Failure` error is returned if the handlers don't match the description: a
+    /// declared method without a handler, a readable (or writable) property without a getter (or
+    /// setter), or a handler for an undeclared method or property.
+    pub fn build(self) -> Result<DynamicInterface> {
+        let iface = self.description.name();
+        let methods = self.description.methods();
+        let properties = self.description.properties();
+
+        if let Some(method) = methods
+            .iter()
+            .find(|m| !self.methods.contains_key(m.name().as_str()))
+        {
+            return Err(Error::Failure(format!(
+                "No handler for method `{}` of `{iface}`",
+                method.name()
+            )));
+        }
+        if let Some(property) = properties.iter().find(|p| {
+            (p.access().read() && !self.getters.contains_key(p.name().as_str()))
+                || (p.access().write() && !self.setters.contains_key(p.name().as_str()))
+        }) {
+            return Err(Error::Failure(format!(
+                "Missing getter or setter for property `{}` of `{iface}`",
+                property.name()
+            )));
+        }
+        if let Some(name) = self
+            .methods
+            .keys()
+            .find(|name| !methods.iter().any(|m| m.name() == name.as_str()))
+        {
+            return Err(Error::Failure(format!("`{iface}` has no method `{name}`")));
+        }
+        let accessors = self
+            .getters
+            .keys()
+            .map(|name| (name, PropertyAccess::Read))
+            .chain(
+                self.setters
+                    .keys()
+                    .map(|name| (name, PropertyAccess::Write)),
+            );
+        for (name, access) in accessors {
+            let declared = properties.iter().any(|p| {
+                p.name() == name.as_str()
+                    && match access {
+                        PropertyAccess::Write => p.access().write(),
+                        _ => p.access().read(),
+                    }
+            });
+            if !declared {
+                return Err(Error::Failure(format!(
+                    "`{iface}` has no {} property `{name}`",
+                    match access {
+                        PropertyAccess::Write => "writable",
+                        _ => "readable",
+                    }
+                )));
+            }
+        }
+
+        Ok(DynamicInterface {
+            inner: Arc::new(Inner {
+                description: self.description,
+                methods: self.methods,
+                getters: self.getters,
+                setters: self.setters,
+            }),
+        })
+    }
+}
+
+impl fmt::Debug for DynamicInterfaceBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicInterfaceBuilder")
+            .field("name", &self.description.name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The context of a call to a [`DynamicInterface`] closure.
+#[derive(Clone, Debug)]
+pub struct DynamicContext {
+    iface: DynamicInterface,
+    connection: Connection,
+    emitter: SignalEmitter<'static>,
+    sender: Option<UniqueName<'static>>,
+    message: Option<Message>,
+}
+
+impl DynamicContext {
+    /// The interface the closure belongs to.
+    pub fn interface(&self) -> &DynamicInterface {
+        &self.iface
+    }
+
+    /// The connection the object is served on.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// The path of the object.
+    pub fn path(&self) -> &ObjectPath<'static> {
+        self.emitter.path()
+    }
+
+    /// A signal emitter for the object.
+    pub fn signal_emitter(&self) -> &SignalEmitter<'static> {
+        &self.emitter
+    }
+
+    /// The sender of the message that triggered the call, if any.
+    ///
+    /// This is `None` for property getters not being called as part of D-Bus communication, as
+    /// for the `InterfacesAdded` signal of [`fdo::ObjectManager`].
+    pub fn sender(&self) -> Option<&UniqueName<'static>> {
+        self.sender.as_ref()
+    }
+
+    /// The method call message, for method handlers.
+    pub fn message(&self) -> Option<&Message> {
+        self.message.as_ref()
+    }
+
+    /// Emit a signal of the interface from the object.
+    ///
+    /// See [`DynamicInterface::emit_signal`] for details.
+    pub async fn emit_signal(&self, signal_name: &str, args: Vec<Value<'_>>) -> Result<()> {
+        self.iface
+            .emit_signal(&self.emitter, signal_name, args)
+            .await
+    }
+}
+
+// The `Interface` implementation of a `DynamicInterface`.
+//
+// This is kept private and only created through `DynamicInterface::into_arc_interface`, which
+// registers it under the name stored here rather than the one returned by `Interface::name`.
+struct Registered {
+    name: InterfaceName<'static>,
+    iface: DynamicInterface,
+}
+
+#[async_trait]
+impl Interface for Registered {
+    // `Interface::name` has no access to the registration, so all dynamic interfaces share this
+    // name at the type level. Their real name is the one stored on each registration.
+    fn name() -> InterfaceName<'static> {
+        InterfaceName::from_static_str_unchecked("org.zbus.DynamicInterface")
+    }
+
+    async fn get(
+        &self,
+        property_name: &str,
+        _server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<OwnedValue>> {
+        self.iface.property(property_name)?;
+        let ctxt = self.iface.context(connection, header, emitter);
+
+        Some(self.iface.get(property_name, ctxt).await)
+    }
+
+    async fn get_all(
+        &self,
+        _server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>> {
+        let mut values = HashMap::new();
+        for property in self.iface.description().properties() {
+            if !property.access().read() {
+                continue;
+            }
+            let name = property.name();
+            let ctxt = self.iface.context(connection, header, emitter);
+            let value = self.iface.get(&name, ctxt).await?;
+            values.insert(name.to_string(), value);
+        }
+
+        Ok(values)
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        _server: &'call ObjectServer,
+        connection: &'call Connection,
+        header: Option<&'call Header<'_>>,
+        emitter: &'call SignalEmitter<'_>,
+    ) -> DispatchResult<'call> {
+        if self.iface.property(property_name).is_none() {
+            return DispatchResult::NotFound;
+        }
+        let ctxt = self.iface.context(connection, header, emitter);
+
+        DispatchResult::Async(Box::pin(async move {
+            self.iface
+                .set(property_name, value, ctxt)
+                .await
+                .map_err(Into::into)
+        }))
+    }
+
+    async fn set_mut(
+        &mut self,
+        _property_name: &str,
+        _value: &Value<'_>,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<()>> {
+        None
+    }
+
+    fn call<'call>(
+        &'call self,
+        _server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        let declared = self
+            .iface
+            .description()
+            .methods()
+            .iter()
+            .any(|m| m.name() == name);
+        if !declared {
+            return DispatchResult::NotFound;
+        }
+
+        DispatchResult::Async(Box::pin(async move {
+            let hdr = msg.header();
+            let ret = self.iface.call(connection, msg).await.and_then(|values| {
+                body(values.into_iter().map(Value::from).collect()).map_err(Into::into)
+            });
+            if hdr.primary().flags().contains(Flags::NoReplyExpected) {
+                return Ok(());
+            }
+
+            match ret {
+                Ok(Some(body)) => connection.reply(&hdr, &body).await,
+                Ok(None) => connection.reply(&hdr, &()).await,
+                Err(e) => connection.reply_dbus_error(&hdr, e).await,
+            }
+        }))
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        _server: &'call ObjectServer,
+        _connection: &'call Connection,
+        _msg: &'call Message,
+        _name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        DispatchResult::NotFound
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        let iface = self.iface.description();
+        writeln!(
+            writer,
+            r#"{:indent$}<interface name="{}">"#,
+            "",
+            iface.name(),
+            indent = level
+        )
+        .unwrap();
+        let level = level + 2;
+
+        for method in iface.methods() {
+            writeln!(
+                writer,
+                r#"{:indent$}<method name="{}">"#,
+                "",
+                method.name(),
+                indent = level
+            )
+            .unwrap();
+            for arg in method.args() {
+                let direction = match arg.direction() {
+                    Some(ArgDirection::Out) => "out",
+                    _ => "in",
+                };
+                write_arg(writer, arg, Some(direction), level + 2);
+            }
+            write_annotations(writer, method.annotations(), level + 2);
+            writeln!(writer, "{:indent$}</method>", "", indent = level).unwrap();
+        }
+
+        for signal in iface.signals() {
+            writeln!(
+                writer,
+                r#"{:indent$}<signal name="{}">"#,
+                "",
+                signal.name(),
+                indent = level
+            )
+            .unwrap();
+            for arg in signal.args() {
+                write_arg(writer, arg, None, level + 2);
+            }
+            write_annotations(writer, signal.annotations(), level + 2);
+            writeln!(writer, "{:indent$}</signal>", "", indent = level).unwrap();
+        }
+
+        for property in iface.properties() {
+            let access = match property.access() {
+                PropertyAccess::Read => "read",
+                PropertyAccess::Write => "write",
+                PropertyAccess::ReadWrite => "readwrite",
+            };
+            write!(
+                writer,
+                r#"{:indent$}<property name="{}" type="{}" access="{access}""#,
+                "",
+                property.name(),
+                property.ty().inner(),
+                indent = level
+            )
+            .unwrap();
+            if property.annotations().is_empty() {
+                writeln!(writer, "/>").unwrap();
+            } else {
+                writeln!(writer, ">").unwrap();
+                write_annotations(writer, property.annotations(), level + 2);
+                writeln!(writer, "{:indent$}</property>", "", indent = level).unwrap();
+            }
+        }
+
+        write_annotations(writer, iface.annotations(), level);
+        writeln!(writer, "{:indent$}</interface>", "", indent = level - 2).unwrap();
+    }
+}
+
+// The signature of the given arguments, as in a message header.
+fn signature<'a>(args: impl Iterator<Item = &'a Arg>) -> String {
+    args.map(|a| a.ty().inner().to_string()).collect()
+}
+
+// Whether `value` matches the declared `ty` of a property. Any value matches a variant property.
+fn matches_property(value: &Value<'_>, ty: &Signature) -> bool {
+    *ty == Signature::Variant || value.value_signature() == ty
+}
+
+// Check that `values` match the signatures of `args`.
+fn check_values<'a, 'v, V>(values: &[V], args: impl Iterator<Item = &'a Arg>) -> Result<()>
+where
+    V: std::ops::Deref<Target = Value<'v>>,
+{
+    let expected = signature(args);
+    let actual: String = values
+        .iter()
+        .map(|v| v.value_signature().to_string())
+        .collect();
+    if actual != expected {
+        let actual = Signature::try_from(format!("({actual})").as_str())?;
+
+        return Err(zvariant::Error::SignatureMismatch(actual, format!("`{expected}`")).into());
+    }
+
+    Ok(())
+}
+
+// The message body for the given arguments, or `None` if there are none.
+fn body(args: Vec<Value<'_>>) -> Result<Option<zvariant::Structure<'_>>> {
+    if args.is_empty() {
+        return Ok(None);
+    }
+
+    args.into_iter()
+        .fold(StructureBuilder::new(), StructureBuilder::append_field)
+        .build()
+        .map(Some)
+        .map_err(Into::into)
+}
+
+fn annotation<'a>(annotations: &'a [Annotation], name: &str) -> Option<&'a str> {
+    annotations
+        .iter()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+fn write_arg(writer: &mut dyn Write, arg: &Arg, direction: Option<&str>, level: usize) {
+    write!(writer, "{:indent$}<arg ", "", indent = level).unwrap();
+    if let Some(name) = arg.name() {
+        write!(writer, r#"name="{name}" "#).unwrap();
+    }
+    write!(writer, r#"type="{}""#, arg.ty().inner()).unwrap();
+    if let Some(direction) = direction {
+        write!(writer, r#" direction="{direction}""#).unwrap();
+    }
+    writeln!(writer, "/>").unwrap();
+}
+
+fn write_annotations(writer: &mut dyn Write, annotations: &[Annotation], level: usize) {
+    for annotation in annotations {
+        writeln!(
+            writer,
+            r#"{:indent$}<annotation name="{}" value="{}"/>"#,
+            "",
+            annotation.name(),
+            escape(annotation.value()),
+            indent = level
+        )
+        .unwrap();
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,337 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    num::NonZeroU32,
+    pin::Pin,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use async_trait::async_trait;
+use zbus_names::{InterfaceName, MemberName, OwnedInterfaceName};
+use zvariant::{ObjectPath, OwnedObjectPath};
+
+use crate::{
+    fdo,
+    message::{Header, Message, Type},
+    Connection,
+};
+
+use super::Access;
+
+/// A middleware wrapping the method calls dispatched by an [`ObjectServer`].
+///
+/// Register it with [`ObjectServer::add_interceptor`] to implement cross-cutting behavior, such as
+/// logging, metrics, rate limiting or error translation, without touching the interfaces
+/// themselves. All the method calls are intercepted, including those to the standard interfaces
+/// (e.g. property accesses through `org.freedesktop.DBus.Properties` or introspection).
+///
+/// An interceptor can either pass the call on to the rest of the chain through [`Next::run`],
+/// and then observe or replace the resulting reply, or short-circuit the call by returning a reply
+/// or an error right away.
+///
+/// # Example
+///
+/// Logging the calls, along with their duration:
+///
+/// ```no_run
+/// # use std::error::Error;
+/// use std::time::Instant;
+///
+/// use async_trait::async_trait;
+/// use zbus::{
+///     fdo,
+///     message::Message,
+///     object_server::{Interceptor, MethodCall, Next},
+///     Connection,
+/// };
+///
+/// struct Logger;
+///
+/// #[async_trait]
+/// impl Interceptor for Logger {
+///     async fn intercept(
+///         &self,
+///         call: &MethodCall,
+///         next: Next<'_>,
+///     ) -> fdo::Result<Option<Message>> {
+///         let start = Instant::now();
+///         let reply = next.run().await;
+///         println!(
+///             "{}.{} on {} took {:?}",
+///             call.interface(),
+///             call.member(),
+///             call.path(),
+///             start.elapsed(),
+///         );
+///
+///         reply
+///     }
+/// }
+///
+/// # async_io::block_on(async {
+/// let connection = Connection::session().await?;
+/// connection.object_server().add_interceptor(Logger);
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::add_interceptor`]: crate::ObjectServer::add_interceptor
+#[async_trait]
+pub trait Interceptor: Send + Sync + 'static {
+    /// Intercept the method `call`.
+    ///
+    /// Returns the reply to send back to the caller, if any. If an error is returned, it's sent
+    /// back to the caller instead.
+    async fn intercept(&self, call: &MethodCall, next: Next<'_>) -> fdo::Result<Option<Message>>;
+}
+
+impl fmt::Debug for dyn Interceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interceptor").finish_non_exhaustive()
+    }
+}
+
+/// A method call, as seen by an [`Interceptor`].
+#[derive(Debug)]
+pub struct MethodCall {
+    message: Message,
+    connection: Connection,
+    path: OwnedObjectPath,
+    interface: OwnedInterfaceName,
+    access: Access,
+}
+
+impl MethodCall {
+    pub(crate) fn new(
+        connection: &Connection,
+        msg: &Message,
+        path: &ObjectPath<'_>,
+        interface: &InterfaceName<'_>,
+        member: &MemberName<'_>,
+    ) -> Self {
+        // A malformed call to the `Properties` interface is reported as such by the interface.
+        let (interface, access) = Access::from_call(msg, interface, member).unwrap_or_else(|_| {
+            (
+                interface.to_owned().into(),
+                Access::Method(member.to_owned().into()),
+            )
+        });
+
+        Self {
+            message: msg.clone(),
+            connection: connection.clone(),
+            path: path.to_owned().into(),
+            interface,
+            access,
+        }
+    }
+
+    /// The method call message.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// The header of the method call message.
+    pub fn header(&self) -> Header<'_> {
+        self.message.header()
+    }
+
+    /// The connection the call was received on.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// The path of the called object.
+    pub fn path(&self) -> ObjectPath<'_> {
+        self.path.as_ref()
+    }
+
+    /// The called interface.
+    ///
+    /// For accesses to properties, this is the interface of the property, not
+    /// `org.freedesktop.DBus.Properties`.
+    pub fn interface(&self) -> InterfaceName<'_> {
+        self.interface.as_ref()
+    }
+
+    /// The called method, as found in the header of the message.
+    pub fn member(&self) -> MemberName<'_> {
+        // SAFETY: Calls without a member are rejected before being intercepted.
+        self.message.header().member().unwrap().to_owned()
+    }
+
+    /// What the call accesses.
+    pub fn access(&self) -> &Access {
+        &self.access
+    }
+}
+
+type Endpoint<'a> = dyn Fn() -> Pin<Box<dyn Future<Output = fdo::Result<Option<Message>>> + Send + 'a>>
+    + Send
+    + Sync
+    + 'a;
+
+/// The rest of the interceptor chain, ending with the dispatch of the call to its interface.
+pub struct Next<'a> {
+    chain: &'a [Arc<dyn Interceptor>],
+    call: &'a MethodCall,
+    endpoint: &'a Endpoint<'a>,
+}
+
+impl Next<'_> {
+    /// Pass the call on to the rest of the chain.
+    ///
+    /// Returns the reply to the call, as the interface would send it, if any. Note that errors
+    /// returned by the methods of the interfaces come as error replies, while errors occurring
+    /// before the call reaches an interface (e.g. the object doesn't exist) come as an `Err`.
+    ///
+    /// If a method sends its reply on its own, after it returns, the reply is not seen here and
+    /// `None` is returned.
+    pub async fn run(self) -> fdo::Result<Option<Message>> {
+        match self.chain.split_first() {
+            Some((interceptor, chain)) => {
+                let next = Next {
+                    chain,
+                    call: self.call,
+                    endpoint: self.endpoint,
+                };
+
+                interceptor.intercept(self.call, next).await
+            }
+            None => (self.endpoint)().await,
+        }
+    }
+}
+
+impl fmt::Debug for Next<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next")
+            .field("remaining", &self.chain.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// The destination and the serial of the call a reply is for.
+type ReplyKey = (Option<String>, NonZeroU32);
+
+/// The interceptors of an `ObjectServer`, along with the replies of the calls going through them.
+#[derive(Default)]
+pub(crate) struct Interceptors {
+    chain: RwLock<Vec<Arc<dyn Interceptor>>>,
+    // The replies sent by the interfaces to the calls being intercepted, diverted from
+    // `Connection::send`.
+    replies: Mutex<HashMap<ReplyKey, Option<Message>>>,
+}
+
+impl Interceptors {
+    pub fn add(&self, interceptor: Arc<dyn Interceptor>) {
+        self.chain.write().expect("poisoned lock").push(interceptor);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chain.read().expect("poisoned lock").is_empty()
+    }
+
+    /// Run `call` through the chain, with `dispatch` dispatching it to its interface.
+    pub async fn run<F, Fut>(&self, call: &MethodCall, dispatch: F) -> fdo::Result<Option<Message>>
+    where
+        F: Fn() -> Fut + Send + Sync,
+        Fut: Future<Output = fdo::Result<()>> + Send,
+    {
+        let chain = self.chain.read().expect("poisoned lock").clone();
+        let endpoint = || -> Pin<Box<dyn Future<Output = _> + Send + '_>> {
+            Box::pin(async {
+                let capture = ReplyCapture::new(self, &call.header());
+                dispatch().await?;
+
+                Ok(capture.take())
+            })
+        };
+        let next = Next {
+            chain: &chain,
+            call,
+            endpoint: &endpoint,
+        };
+
+        next.run().await
+    }
+
+    /// Keep `msg` aside if it's the reply to a call being intercepted.
+    ///
+    /// Returns `true` if `msg` was kept, in which case it must not be sent.
+    pub fn divert(&self, msg: &Message) -> bool {
+        if !matches!(msg.message_type(), Type::MethodReturn | Type::Error) {
+            return false;
+        }
+        let mut replies = self.replies.lock().expect("poisoned lock");
+        if replies.is_empty() {
+            return false;
+        }
+
+        let hdr = msg.header();
+        let serial = match hdr.reply_serial() {
+            Some(serial) => serial,
+            None => return false,
+        };
+        match replies.get_mut(&(hdr.destination().map(|d| d.to_string()), serial)) {
+            Some(reply @ None) => {
+                *reply = Some(msg.clone());
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interceptors")
+            .field("len", &self.chain.read().expect("poisoned lock").len())
+            .finish_non_exhaustive()
+    }
+}
+
+// Diverts the reply to a call for as long as it's alive.
+struct ReplyCapture<'i> {
+    interceptors: &'i Interceptors,
+    key: ReplyKey,
+}
+
+impl<'i> ReplyCapture<'i> {
+    fn new(interceptors: &'i Interceptors, call: &Header<'_>) -> Self {
+        let key = (
+            call.sender().map(|s| s.to_string()),
+            call.primary().serial_num(),
+        );
+        interceptors
+            .replies
+            .lock()
+            .expect("poisoned lock")
+            .insert(key.clone(), None);
+
+        Self { interceptors, key }
+    }
+
+    fn take(self) -> Option<Message> {
+        self.interceptors
+            .replies
+            .lock()
+            .expect("poisoned lock")
+            .get_mut(&self.key)
+            .and_then(Option::take)
+    }
+}
+
+impl Drop for ReplyCapture<'_> {
+    fn drop(&mut self) {
+        self.interceptors
+            .replies
+            .lock()
+            .expect("poisoned lock")
+            .remove(&self.key);
+    }
+}
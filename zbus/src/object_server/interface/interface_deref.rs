@@ -1,9 +1,13 @@
 use std::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
-use crate::async_lock::{RwLockReadGuard, RwLockWriteGuard};
+use crate::{
+    async_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+    object_server::PropertyTracker,
+};
 
 use super::Interface;
 
@@ -28,6 +32,8 @@ where
 pub struct InterfaceDerefMut<'d, I> {
     pub(super) iface: RwLockWriteGuard<'d, dyn Interface>,
     pub(super) phantom: PhantomData<I>,
+    // Set if the interface tracks its property changes.
+    pub(super) tracking: Option<(PropertyTracker, Arc<RwLock<dyn Interface>>)>,
 }
 
 impl<I> Deref for InterfaceDerefMut<'_, I>
@@ -49,3 +55,23 @@ where
         self.iface.downcast_mut::<I>().unwrap()
     }
 }
+
+impl<I> Drop for InterfaceDerefMut<'_, I> {
+    fn drop(&mut self) {
+        let Some((tracker, lock)) = self.tracking.take() else {
+            return;
+        };
+
+        // The interface can only be read again once the write lock is released, after this.
+        let executor = tracker.emitter().connection().executor().clone();
+        executor
+            .spawn(
+                async move {
+                    let iface = lock.read().await;
+                    tracker.finish(&*iface).await;
+                },
+                "property change tracking",
+            )
+            .detach();
+    }
+}
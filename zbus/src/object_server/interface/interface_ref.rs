@@ -1,7 +1,10 @@
 use std::{marker::PhantomData, sync::Arc};
 
 use super::{Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter};
-use crate::async_lock::RwLock;
+use crate::{
+    async_lock::RwLock,
+    object_server::{PropertiesBatch, PropertyTracker},
+};
 
 /// Wrapper over an interface, along with its corresponding `SignalEmitter`
 /// instance. A reference to the underlying interface may be obtained via
@@ -88,10 +91,14 @@ where
         iface
             .downcast_mut::<I>()
             .expect("Unexpected interface type");
+        let tracking = PropertyTracker::start(&*iface, self.emitter.clone())
+            .await
+            .map(|tracker| (tracker, self.lock.clone()));
 
         InterfaceDerefMut {
             iface,
             phantom: PhantomData,
+            tracking,
         }
     }
 
@@ -99,6 +106,14 @@ where
         &self.emitter
     }
 
+    /// Start a batch of property changes on the interface's object.
+    ///
+    /// This is a shortcut for `self.signal_emitter().batch()`. See [`PropertiesBatch`] for
+    /// details.
+    pub fn batch(&self) -> PropertiesBatch<'static> {
+        self.emitter.batch()
+    }
+
     #[deprecated(since = "0.5.0", note = "Please use `signal_emitter` instead.")]
     pub fn signal_context(&self) -> &SignalEmitter<'static> {
         &self.emitter
@@ -20,7 +20,7 @@ use crate::{
     async_lock::RwLock,
     fdo,
     message::{self, Header, Message},
-    object_server::SignalEmitter,
+    object_server::{PropertySnapshot, SignalEmitter},
     Connection, ObjectServer,
 };
 
@@ -71,6 +71,16 @@ pub trait Interface: Any + Send + Sync {
         emitter: &SignalEmitter<'_>,
     ) -> fdo::Result<HashMap<String, OwnedValue>>;
 
+    /// Take a snapshot of the properties whose changes are tracked automatically.
+    ///
+    /// Returns `None` if the property changes of the interface aren't tracked, which is the
+    /// default.
+    async fn property_snapshot(&self, emitter: &SignalEmitter<'_>) -> Option<PropertySnapshot> {
+        let _ = emitter;
+
+        None
+    }
+
     /// Set a property value.
     ///
     /// Return [`DispatchResult::NotFound`] if the property doesn't exist, or
@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use event_listener::Event;
+use zbus_names::{OwnedUniqueName, UniqueName};
+
+/// The limits on the method calls an [`ObjectServer`] handles concurrently.
+///
+/// These only apply to the calls handled in their own task, i.e. to the interfaces for which
+/// [`Interface::spawn_tasks_for_methods`] returns `true` (the default), including the standard
+/// interfaces (e.g `org.freedesktop.DBus.Properties`). The other calls are handled one after the
+/// other anyway, and neither count towards the limits nor are held back by them. Neither do the
+/// calls to unknown objects or interfaces, which are replied to with an error right away.
+///
+/// The calls exceeding the limits are checked against them before a task is spawned for them, so
+/// rejected calls don't cost a task. See [`Overflow`] for what happens to them.
+///
+/// Set them with [`ObjectServer::set_call_limits`] or [`Builder::call_limits`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::{error::Error, num::NonZeroUsize};
+/// use zbus::{
+///     object_server::{CallLimits, Overflow},
+///     Connection,
+/// };
+///
+/// # async_io::block_on(async {
+/// let connection = Connection::session().await?;
+/// // At most 64 calls in flight, of which 4 per caller. Queue up to 8 calls per caller above
+/// // that, and reject the others.
+/// let limits = CallLimits::new()
+///     .max_calls(NonZeroUsize::new(64).unwrap())
+///     .max_calls_per_sender(NonZeroUsize::new(4).unwrap())
+///     .overflow(Overflow::Queue)
+///     .max_queued_per_sender(8);
+/// connection.object_server().set_call_limits(limits);
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::set_call_limits`]: crate::ObjectServer::set_call_limits
+/// [`Builder::call_limits`]: crate::connection::Builder::call_limits
+/// [`Interface::spawn_tasks_for_methods`]: super::Interface::spawn_tasks_for_methods
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallLimits {
+    max_calls: Option<NonZeroUsize>,
+    max_calls_per_sender: Option<NonZeroUsize>,
+    max_queued: Option<usize>,
+    max_queued_per_sender: Option<usize>,
+    overflow: Overflow,
+}
+
+impl CallLimits {
+    /// Create limits that don't limit anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of calls in flight, from all callers.
+    pub fn max_calls(mut self, max: NonZeroUsize) -> Self {
+        self.max_calls = Some(max);
+
+        self
+    }
+
+    /// Set the maximum number of calls in flight, from a single caller.
+    ///
+    /// Callers are identified by their unique name on the bus. On a peer-to-peer connection, this
+    /// is the same as [`CallLimits::max_calls`].
+    pub fn max_calls_per_sender(mut self, max: NonZeroUsize) -> Self {
+        self.max_calls_per_sender = Some(max);
+
+        self
+    }
+
+    /// Set the maximum number of calls waiting for calls in flight to complete, from all callers.
+    ///
+    /// Only relevant with [`Overflow::Queue`]. Defaults to the value of
+    /// [`CallLimits::max_calls`], if set.
+    pub fn max_queued(mut self, max: usize) -> Self {
+        self.max_queued = Some(max);
+
+        self
+    }
+
+    /// Set the maximum number of calls waiting for calls in flight to complete, from a single
+    /// caller.
+    ///
+    /// Only relevant with [`Overflow::Queue`]. Defaults to the value of
+    /// [`CallLimits::max_calls_per_sender`], if set.
+    pub fn max_queued_per_sender(mut self, max: usize) -> Self {
+        self.max_queued_per_sender = Some(max);
+
+        self
+    }
+
+    /// Set what happens to the calls exceeding the limits.
+    ///
+    /// The default is [`Overflow::Reject`].
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+
+        self
+    }
+
+    fn allow(&self, counts: Counts, sender_counts: Counts) -> bool {
+        within(self.max_calls.map(NonZeroUsize::get), counts.calls)
+            && within(
+                self.max_calls_per_sender.map(NonZeroUsize::get),
+                sender_counts.calls,
+            )
+    }
+
+    fn allow_queued(&self, counts: Counts, sender_counts: Counts) -> bool {
+        let max_queued = self.max_queued.or(self.max_calls.map(NonZeroUsize::get));
+        let max_queued_per_sender = self
+            .max_queued_per_sender
+            .or(self.max_calls_per_sender.map(NonZeroUsize::get));
+
+        self.overflow == Overflow::Queue
+            && within(max_queued, counts.queued)
+            && within(max_queued_per_sender, sender_counts.queued)
+    }
+}
+
+fn within(max: Option<usize>, count: usize) -> bool {
+    max.map_or(true, |max| count < max)
+}
+
+/// What happens to the method calls exceeding the [`CallLimits`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Overflow {
+    /// Reply with an `org.freedesktop.DBus.Error.LimitsExceeded` error.
+    #[default]
+    Reject,
+    /// Wait for calls in flight to complete before handling the call.
+    ///
+    /// The call waits in its own task, so the object server keeps dispatching the other calls in
+    /// the meantime. The number of waiting calls is bounded by [`CallLimits::max_queued`] and
+    /// [`CallLimits::max_queued_per_sender`], and the calls above those are rejected as with
+    /// [`Overflow::Reject`].
+    Queue,
+}
+
+/// Keeps track of the calls in flight, against the `CallLimits` of an `ObjectServer`.
+#[derive(Debug, Default)]
+pub(crate) struct CallLimiter {
+    state: Arc<Mutex<State>>,
+    // Notified each time a call completes or the limits change.
+    released: Arc<Event>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    limits: CallLimits,
+    counts: Counts,
+    sender_counts: HashMap<Option<OwnedUniqueName>, Counts>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Counts {
+    calls: usize,
+    queued: usize,
+}
+
+impl State {
+    fn sender_counts(&self, sender: &Option<OwnedUniqueName>) -> Counts {
+        self.sender_counts.get(sender).copied().unwrap_or_default()
+    }
+
+    fn update(&mut self, sender: &Option<OwnedUniqueName>, update: impl Fn(&mut Counts)) {
+        update(&mut self.counts);
+        let mut sender_counts = self.sender_counts(sender);
+        update(&mut sender_counts);
+        if sender_counts == Counts::default() {
+            self.sender_counts.remove(sender);
+        } else {
+            self.sender_counts.insert(sender.clone(), sender_counts);
+        }
+    }
+}
+
+impl CallLimiter {
+    pub fn set_limits(&self, limits: CallLimits) {
+        self.state.lock().expect("poisoned lock").limits = limits;
+        self.released.notify(usize::MAX);
+    }
+
+    /// Check a call from `sender` against the limits.
+    ///
+    /// Returns `None` if the call exceeds the limits, and is to be rejected. Otherwise, the call
+    /// is accounted for until the returned admission, and then the permit it gives, is dropped.
+    pub fn admit(&self, sender: Option<&UniqueName<'_>>) -> Option<Admission> {
+        let sender: Option<OwnedUniqueName> = sender.map(|s| s.to_owned().into());
+        let mut state = self.state.lock().expect("poisoned lock");
+        let sender_counts = state.sender_counts(&sender);
+        if state.limits.allow(state.counts, sender_counts) {
+            state.update(&sender, |counts| counts.calls += 1);
+
+            Some(Admission::Admitted(self.permit(sender)))
+        } else if state.limits.allow_queued(state.counts, sender_counts) {
+            state.update(&sender, |counts| counts.queued += 1);
+
+            Some(Admission::Queued(QueuedCall {
+                limiter: CallLimiter {
+                    state: self.state.clone(),
+                    released: self.released.clone(),
+                },
+                sender,
+            }))
+        } else {
+            None
+        }
+    }
+
+    fn permit(&self, sender: Option<OwnedUniqueName>) -> CallPermit {
+        CallPermit {
+            state: self.state.clone(),
+            released: self.released.clone(),
+            sender,
+        }
+    }
+}
+
+/// A call admitted by a [`CallLimiter`].
+#[derive(Debug)]
+pub(crate) enum Admission {
+    /// The call can be handled right away.
+    Admitted(CallPermit),
+    /// The call has to wait for calls in flight to complete.
+    Queued(QueuedCall),
+}
+
+impl Admission {
+    /// Wait until the call can be handled.
+    ///
+    /// Returns `None` if the limits changed in the meantime, and the call is to be rejected.
+    pub async fn permit(self) -> Option<CallPermit> {
+        match self {
+            Admission::Admitted(permit) => Some(permit),
+            Admission::Queued(queued) => queued.permit().await,
+        }
+    }
+}
+
+/// Accounts for a call waiting for calls in flight to complete, until dropped.
+#[derive(Debug)]
+pub(crate) struct QueuedCall {
+    limiter: CallLimiter,
+    sender: Option<OwnedUniqueName>,
+}
+
+impl QueuedCall {
+    async fn permit(self) -> Option<CallPermit> {
+        loop {
+            // Listen before checking, so we don't miss a release in between.
+            let listener = self.limiter.released.listen();
+            {
+                let mut state = self.limiter.state.lock().expect("poisoned lock");
+                let sender_counts = state.sender_counts(&self.sender);
+                if state.limits.allow(state.counts, sender_counts) {
+                    // The call stops being accounted for as queued once `self` is dropped.
+                    state.update(&self.sender, |counts| counts.calls += 1);
+
+                    return Some(self.limiter.permit(self.sender.clone()));
+                }
+                if state.limits.overflow == Overflow::Reject {
+                    return None;
+                }
+            }
+
+            listener.await;
+        }
+    }
+}
+
+impl Drop for QueuedCall {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state.lock().expect("poisoned lock");
+        state.update(&self.sender, |counts| counts.queued -= 1);
+    }
+}
+
+/// Accounts for a call in flight, until dropped.
+#[derive(Debug)]
+pub(crate) struct CallPermit {
+    state: Arc<Mutex<State>>,
+    released: Arc<Event>,
+    sender: Option<OwnedUniqueName>,
+}
+
+impl Drop for CallPermit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        state.update(&self.sender, |counts| counts.calls -= 1);
+        drop(state);
+
+        self.released.notify(usize::MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use zbus_names::UniqueName;
+
+    use super::{Admission, CallLimiter, CallLimits, Overflow};
+    use crate::utils::block_on;
+
+    #[test]
+    fn bounded_queue() {
+        let limiter = CallLimiter::default();
+        limiter.set_limits(
+            CallLimits::new()
+                .max_calls_per_sender(NonZeroUsize::new(1).unwrap())
+                .overflow(Overflow::Queue),
+        );
+        let sender = UniqueName::from_static_str_unchecked(":1.1");
+        let other = UniqueName::from_static_str_unchecked(":1.2");
+
+        let admitted = limiter.admit(Some(&sender)).unwrap();
+        assert!(matches!(admitted, Admission::Admitted(_)));
+        // The queue of a sender defaults to its limit of calls in flight.
+        let queued = limiter.admit(Some(&sender)).unwrap();
+        assert!(matches!(queued, Admission::Queued(_)));
+        assert!(limiter.admit(Some(&sender)).is_none());
+        assert!(matches!(
+            limiter.admit(Some(&other)),
+            Some(Admission::Admitted(_))
+        ));
+
+        // Once the call in flight completes, the queued one takes its place.
+        drop(admitted);
+        let permit = block_on(queued.permit()).unwrap();
+        assert!(matches!(
+            limiter.admit(Some(&sender)),
+            Some(Admission::Queued(_))
+        ));
+
+        // Rejecting the overflow also rejects the calls already queued.
+        let queued = limiter.admit(Some(&sender)).unwrap();
+        limiter.set_limits(CallLimits::new().max_calls_per_sender(NonZeroUsize::new(1).unwrap()));
+        assert!(block_on(queued.permit()).is_none());
+        assert!(limiter.admit(Some(&sender)).is_none());
+
+        drop(permit);
+        assert!(limiter.state.lock().unwrap().sender_counts.is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 //! The object server API.
 
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{collections::HashMap, future::Future, marker::PhantomData, sync::Arc};
 use tracing::{debug, instrument, trace, trace_span, Instrument};
 
 use zbus_names::InterfaceName;
@@ -30,6 +30,39 @@ pub use dispatch_notifier::ResponseDispatchNotifier;
 mod node;
 pub(crate) use node::Node;
 
+#[cfg(feature = "dynamic-interface")]
+mod dynamic;
+#[cfg(feature = "dynamic-interface")]
+pub use dynamic::{DynamicContext, DynamicInterface, DynamicInterfaceBuilder};
+
+mod access;
+pub use access::{Access, AccessRequest};
+pub(crate) use access::{AccessControl, AccessPolicy};
+
+mod limits;
+pub(crate) use limits::CallLimiter;
+pub use limits::{CallLimits, Overflow};
+
+mod object_builder;
+pub use object_builder::ObjectBuilder;
+
+mod interceptor;
+pub(crate) use interceptor::Interceptors;
+pub use interceptor::{Interceptor, MethodCall, Next};
+
+mod properties_batch;
+pub use properties_batch::PropertiesBatch;
+pub(crate) use properties_batch::{Changes, Coalescer};
+
+mod property_tracking;
+pub use property_tracking::PropertySnapshot;
+#[doc(hidden)]
+pub use property_tracking::PropertyTracker;
+
+mod subtree;
+pub(crate) use subtree::Subtree;
+pub use subtree::{SubtreeHandler, SubtreeObject};
+
 /// An object server, holding server-side D-Bus objects & interfaces.
 ///
 /// Object servers hold interfaces on various object paths, and expose them over D-Bus.
@@ -91,6 +124,10 @@ pub(crate) use node::Node;
 pub struct ObjectServer {
     conn: WeakConnection,
     root: Arc<RwLock<Node>>,
+    access: Arc<AccessControl>,
+    interceptors: Arc<Interceptors>,
+    limiter: Arc<CallLimiter>,
+    coalescer: Arc<Coalescer>,
 }
 
 impl ObjectServer {
@@ -101,6 +138,10 @@ impl ObjectServer {
             root: Arc::new(RwLock::new(Node::new(
                 "/".try_into().expect("zvariant bug"),
             ))),
+            access: Default::default(),
+            interceptors: Default::default(),
+            limiter: Default::default(),
+            coalescer: Default::default(),
         }
     }
 
@@ -142,40 +183,102 @@ impl ObjectServer {
         let node = node.unwrap();
         let added = node.add_arc_interface(name.clone(), arc_iface);
         if added {
-            if name == ObjectManager::name() {
-                // Just added an object manager. Need to signal all managed objects under it.
-                let emitter = SignalEmitter::new(&self.connection(), path)?;
-                let objects = node.get_managed_objects(self, &self.connection()).await?;
-                for (path, owned_interfaces) in objects {
-                    let interfaces = owned_interfaces
-                        .iter()
-                        .map(|(i, props)| {
-                            let props = props
-                                .iter()
-                                .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
-                                .collect::<Result<_>>();
-                            Ok((i.into(), props?))
-                        })
-                        .collect::<Result<_>>()?;
-                    ObjectManager::interfaces_added(&emitter, path.into(), interfaces).await?;
-                }
-            } else if let Some(manager_path) = manager_path {
-                let emitter = SignalEmitter::new(&self.connection(), manager_path.clone())?;
-                let mut interfaces = HashMap::new();
-                let owned_props = node
-                    .get_properties(self, &self.connection(), name.clone())
-                    .await?;
-                let props = owned_props
+            self.emit_interfaces_added(node, &path, manager_path, &[name])
+                .await?;
+        }
+
+        Ok(added)
+    }
+
+    /// Register an object with several interfaces at once.
+    ///
+    /// See [`ObjectBuilder`] for details.
+    pub fn add_object<'p, P>(&self, path: P) -> Result<ObjectBuilder<'_>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+
+        Ok(ObjectBuilder::new(self, path.into_owned()))
+    }
+
+    async fn add_interfaces(
+        &self,
+        path: ObjectPath<'_>,
+        interfaces: HashMap<InterfaceName<'static>, ArcInterface>,
+    ) -> Result<()> {
+        let mut root = self.root().write().await;
+        if let Some(node) = root.get_child(&path) {
+            if let Some(name) = interfaces.keys().find(|name| node.has_interface(name)) {
+                return Err(Error::InterfaceExists(name.clone(), path.into_owned()));
+            }
+        }
+
+        let (node, manager_path) = root.get_child_mut(&path, true);
+        let node = node.unwrap();
+        let names: Vec<_> = interfaces.keys().cloned().collect();
+        for (name, iface) in interfaces {
+            node.add_arc_interface(name, iface);
+        }
+
+        self.emit_interfaces_added(node, &path, manager_path, &names)
+            .await
+    }
+
+    // Emit the `InterfacesAdded` signals for the interfaces just added to `node`.
+    async fn emit_interfaces_added(
+        &self,
+        node: &Node,
+        path: &ObjectPath<'_>,
+        manager_path: Option<ObjectPath<'_>>,
+        names: &[InterfaceName<'static>],
+    ) -> Result<()> {
+        let conn = self.connection();
+        if names.contains(&ObjectManager::name()) {
+            // Just added an object manager. Need to signal all managed objects under it.
+            let emitter = SignalEmitter::new(&conn, path.clone())?;
+            let objects = node.get_managed_objects(self, &conn).await?;
+            for (path, owned_interfaces) in objects {
+                let interfaces = owned_interfaces
                     .iter()
-                    .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                    .map(|(i, props)| {
+                        let props = props
+                            .iter()
+                            .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                            .collect::<Result<_>>();
+                        Ok((i.into(), props?))
+                    })
                     .collect::<Result<_>>()?;
-                interfaces.insert(name, props);
-
-                ObjectManager::interfaces_added(&emitter, path, interfaces).await?;
+                ObjectManager::interfaces_added(&emitter, path.into(), interfaces).await?;
             }
         }
 
-        Ok(added)
+        let manager_path = match manager_path {
+            Some(manager_path) => manager_path,
+            None => return Ok(()),
+        };
+        let mut owned_interfaces = Vec::new();
+        for name in names.iter().filter(|name| **name != ObjectManager::name()) {
+            let owned_props = node.get_properties(self, &conn, name.clone()).await?;
+            owned_interfaces.push((name, owned_props));
+        }
+        if owned_interfaces.is_empty() {
+            return Ok(());
+        }
+        let interfaces = owned_interfaces
+            .iter()
+            .map(|(name, owned_props)| {
+                let props = owned_props
+                    .iter()
+                    .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                    .collect::<Result<_>>()?;
+                Ok(((*name).clone(), props))
+            })
+            .collect::<Result<_>>()?;
+        let emitter = SignalEmitter::new(&conn, manager_path)?;
+
+        ObjectManager::interfaces_added(&emitter, path.clone(), interfaces).await
     }
 
     /// Unregister a D-Bus [`Interface`] at a given path.
@@ -189,31 +292,274 @@ impl ObjectServer {
         P::Error: Into<Error>,
     {
         let path = path.try_into().map_err(Into::into)?;
+
+        self.remove_interface(path, I::name()).await
+    }
+
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// This is the equivalent of [`ObjectServer::at`] for interfaces defined at runtime.
+    ///
+    /// If the interface already exists at this path, returns false.
+    ///
+    /// This method is only available when the `dynamic-interface` feature is enabled.
+    #[cfg(feature = "dynamic-interface")]
+    pub async fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let (name, iface) = iface.into_arc_interface();
+        self.add_arc_interface(path, name, iface).await
+    }
+
+    /// Unregister the [`DynamicInterface`] with the given name at a given path.
+    ///
+    /// This is the equivalent of [`ObjectServer::remove`] for interfaces defined at runtime.
+    ///
+    /// This method is only available when the `dynamic-interface` feature is enabled.
+    #[cfg(feature = "dynamic-interface")]
+    pub async fn remove_dynamic<'p, 'i, P, I>(&self, path: P, name: I) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        I: TryInto<InterfaceName<'i>>,
+        I::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let name = name.try_into().map_err(Into::into)?;
+
+        self.remove_interface(path, name.into_owned()).await
+    }
+
+    async fn remove_interface(
+        &self,
+        path: ObjectPath<'_>,
+        name: InterfaceName<'static>,
+    ) -> Result<bool> {
         let mut root = self.root.write().await;
         let (node, manager_path) = root.get_child_mut(&path, false);
         let node = node.ok_or(Error::InterfaceNotFound)?;
-        if !node.remove_interface(I::name()) {
+        if !node.remove_interface(name.clone()) {
             return Err(Error::InterfaceNotFound);
         }
         if let Some(manager_path) = manager_path {
             let ctxt = SignalEmitter::new(&self.connection(), manager_path.clone())?;
-            ObjectManager::interfaces_removed(&ctxt, path.clone(), (&[I::name()]).into()).await?;
+            ObjectManager::interfaces_removed(&ctxt, path.clone(), (&[name]).into()).await?;
         }
         if node.is_empty() {
-            let mut path_parts = path.rsplit('/').filter(|i| !i.is_empty());
-            let last_part = path_parts.next().unwrap();
-            let ppath = ObjectPath::from_string_unchecked(
-                path_parts.fold(String::new(), |a, p| format!("/{p}{a}")),
-            );
-            root.get_child_mut(&ppath, false)
-                .0
-                .unwrap()
-                .remove_node(last_part);
+            Self::remove_node(&mut root, &path);
             return Ok(true);
         }
         Ok(false)
     }
 
+    /// Unregister all the interfaces of the object at a given path, at once.
+    ///
+    /// This is the counterpart of [`ObjectServer::add_object`]: a single `InterfacesRemoved`
+    /// signal of [`ObjectManager`] is emitted for all the interfaces. Unless it still has child
+    /// objects, the object is destroyed as well. Returns whether the object was destroyed.
+    ///
+    /// # Errors
+    ///
+    /// If there is no object at the given path, an `Error::InterfaceNotFound` error is returned.
+    pub async fn remove_object<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let (node, manager_path) = root.get_child_mut(&path, false);
+        let node = node.ok_or(Error::InterfaceNotFound)?;
+        let names = node.remove_interfaces();
+        if names.is_empty() {
+            return Err(Error::InterfaceNotFound);
+        }
+        if let Some(manager_path) = manager_path {
+            let ctxt = SignalEmitter::new(&self.connection(), manager_path.clone())?;
+            ObjectManager::interfaces_removed(&ctxt, path.clone(), names.into()).await?;
+        }
+        if node.is_empty() && !node.has_children() {
+            Self::remove_node(&mut root, &path);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Register a [`SubtreeHandler`] serving the objects under the given path.
+    ///
+    /// The handler resolves, on demand, the objects under `path` that are not registered through
+    /// [`ObjectServer::at`]. See [`SubtreeHandler`] for details.
+    ///
+    /// If a handler is already registered at this path, returns false.
+    pub async fn subtree_at<'p, P, H>(&self, path: P, handler: H) -> Result<bool>
+    where
+        H: SubtreeHandler,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let mut root = self.root().write().await;
+        let node = root.get_child_mut(&path, true).0.unwrap();
+        if node.has_subtree() {
+            return Ok(false);
+        }
+        node.set_subtree(Some(Subtree(Arc::new(handler))));
+
+        Ok(true)
+    }
+
+    /// Unregister the [`SubtreeHandler`] at the given path.
+    ///
+    /// If there are no interfaces or child objects left at that path, destroys the object as well.
+    /// Returns whether the object was destroyed.
+    ///
+    /// # Errors
+    ///
+    /// If no handler is registered at the given path, an [`fdo::Error::UnknownObject`] error is
+    /// returned.
+    pub async fn remove_subtree<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let no_subtree = || {
+            Error::from(fdo::Error::UnknownObject(format!(
+                "No subtree handler at '{path}'"
+            )))
+        };
+        let node = root.get_child_mut(&path, false).0.ok_or_else(no_subtree)?;
+        if node.set_subtree(None).is_none() {
+            return Err(no_subtree());
+        }
+        if node.is_empty() && !node.has_children() && path.as_str() != "/" {
+            Self::remove_node(&mut root, &path);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Set the access policy of the server.
+    ///
+    /// Once set, `policy` is called for each method call dispatched by the server, including those
+    /// to the standard interfaces (e.g `org.freedesktop.DBus.Properties`), with an
+    /// [`AccessRequest`] describing the call and the credentials of the caller. The call is only
+    /// dispatched if `policy` returns `Ok(())`. Otherwise, the returned error (typically
+    /// [`fdo::Error::AccessDenied`]) is sent back to the caller.
+    ///
+    /// The policy is consulted before the interface is locked, and before any failure to find the
+    /// object or the interface is reported, so denied callers can't tell which objects exist. For
+    /// the methods dispatched in their own task (the default, see the `spawn` attribute of
+    /// [`crate::interface`]), the policy is consulted from that task, so that requesting the
+    /// credentials of a new caller doesn't hold up the dispatch of other calls. The other calls,
+    /// including those to unknown objects, are checked in turn as they're dispatched. The calls to
+    /// the methods of `org.freedesktop.DBus.Properties` are presented as accesses to the property
+    /// itself (see [`Access`]). Note that `GetManagedObjects` of
+    /// `org.freedesktop.DBus.ObjectManager` returns the properties of all the managed objects, so
+    /// you may want to restrict it as well.
+    ///
+    /// On a bus connection, the credentials of the callers are requested from the bus and cached
+    /// until they leave the bus.
+    ///
+    /// Any previously set policy is replaced.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use zbus::{fdo, object_server::Access, Connection};
+    ///
+    /// # async_io::block_on(async {
+    /// let connection = Connection::session().await?;
+    /// // Only allow root to set properties.
+    /// connection.object_server().set_access_policy(|request| async move {
+    ///     match request.access() {
+    ///         Access::SetProperty(_) if request.credentials().unix_user_id() != Some(0) => Err(
+    ///             fdo::Error::AccessDenied("Only root can set properties".into()),
+    ///         ),
+    ///         _ => Ok(()),
+    ///     }
+    /// });
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// # })?;
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn set_access_policy<F, Fut>(&self, policy: F)
+    where
+        F: Fn(AccessRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        self.access.set_policy(AccessPolicy::new(policy));
+    }
+
+    /// Remove the access policy of the server, if any.
+    ///
+    /// See [`ObjectServer::set_access_policy`] for details.
+    pub fn remove_access_policy(&self) {
+        self.access.remove_policy();
+    }
+
+    pub(crate) fn set_access_policy_inner(&self, policy: AccessPolicy) {
+        self.access.set_policy(policy);
+    }
+
+    /// Set the limits on the method calls the server handles concurrently.
+    ///
+    /// See [`CallLimits`] for details. The new limits apply to the calls dispatched from now on.
+    pub fn set_call_limits(&self, limits: CallLimits) {
+        self.limiter.set_limits(limits);
+    }
+
+    /// Add an [`Interceptor`] to the server.
+    ///
+    /// Interceptors wrap the dispatch of all the method calls, in the order they're added: the
+    /// first added interceptor is the outermost one, seeing the calls first and their replies
+    /// last. The access policy (see [`ObjectServer::set_access_policy`]) is checked when the call
+    /// is passed on by the innermost interceptor, so the interceptors also see the denied calls,
+    /// along with their `AccessDenied` errors.
+    ///
+    /// The replies sent by the interfaces are handed to the interceptors, and only sent once the
+    /// whole chain returns. In particular, a [`ResponseDispatchNotifier`] fires before the reply
+    /// is actually sent.
+    pub fn add_interceptor<I>(&self, interceptor: I)
+    where
+        I: Interceptor,
+    {
+        self.interceptors.add(Arc::new(interceptor));
+    }
+
+    pub(crate) fn add_arc_interceptor(&self, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.add(interceptor);
+    }
+
+    /// Keep `msg` aside if it's the reply to a call being intercepted.
+    ///
+    /// Returns `true` if `msg` was kept, in which case it must not be sent.
+    pub(crate) fn divert_reply(&self, msg: &Message) -> bool {
+        self.interceptors.divert(msg)
+    }
+
+    /// The property changes waiting to be emitted together.
+    pub(crate) fn coalescer(&self) -> &Arc<Coalescer> {
+        &self.coalescer
+    }
+
+    // Remove the node at `path`, along with its children.
+    fn remove_node(root: &mut Node, path: &ObjectPath<'_>) {
+        let mut path_parts = path.rsplit('/').filter(|i| !i.is_empty());
+        let last_part = path_parts.next().unwrap();
+        let ppath = ObjectPath::from_string_unchecked(
+            path_parts.fold(String::new(), |a, p| format!("/{p}{a}")),
+        );
+        root.get_child_mut(&ppath, false)
+            .0
+            .unwrap()
+            .remove_node(last_part);
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -361,25 +707,25 @@ impl ObjectServer {
         // Note that an unknown member will still spawn a task. We should instead gather
         // all the details for the call before spawning.
         // See also https://github.com/dbus2/zbus/issues/674 for future of Interface.
-        let _ = hdr
+        let member = hdr
             .member()
             .ok_or_else(|| fdo::Error::Failed("Missing member".into()))?;
 
-        // Ensure the root lock isn't held while dispatching the message. That
-        // way, the object server can be mutated during that time.
-        let (iface, with_spawn) = {
-            let root = self.root.read().await;
-            let node = root
-                .get_child(path)
-                .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))?;
-
-            let iface = node.interface_lock(iface_name.as_ref()).ok_or_else(|| {
-                fdo::Error::UnknownInterface(format!("Unknown interface '{iface_name}'"))
-            })?;
-            (iface.instance, iface.spawn_tasks_for_methods)
+        let call = if self.interceptors.is_empty() {
+            None
+        } else {
+            Some(MethodCall::new(connection, msg, path, iface_name, member))
         };
+        let target = self.lookup_interface(path, iface_name).await;
+        let with_spawn = matches!(target, Ok((_, true)));
+        let target = target.map(|(iface, _)| iface);
 
         if with_spawn {
+            // Check the limits before spawning, so the calls exceeding them don't cost a task.
+            let admission = self
+                .limiter
+                .admit(hdr.sender())
+                .ok_or_else(limits_exceeded)?;
             let executor = connection.executor().clone();
             let task_name = format!("`{msg}` method dispatcher");
             let connection = connection.clone();
@@ -389,10 +735,16 @@ impl ObjectServer {
                     async move {
                         let server = connection.object_server();
                         let hdr = msg.header();
-                        if let Err(e) = server
-                            .dispatch_call_to_iface(iface, &connection, &msg, &hdr)
-                            .await
-                        {
+                        let res = match admission.permit().await {
+                            Some(_permit) => {
+                                // Account for the call until it's handled.
+                                server
+                                    .dispatch_call_to_target(target, call, &connection, &msg, &hdr)
+                                    .await
+                            }
+                            None => Err(limits_exceeded()),
+                        };
+                        if let Err(e) = res {
                             // When not spawning a task, this error is handled by the caller.
                             debug!("Returning error: {}", e);
                             if let Err(e) = connection.reply_dbus_error(&hdr, e).await {
@@ -409,11 +761,76 @@ impl ObjectServer {
                 .detach();
             Ok(())
         } else {
-            self.dispatch_call_to_iface(iface, connection, msg, hdr)
+            self.dispatch_call_to_target(target, call, connection, msg, hdr)
                 .await
         }
     }
 
+    // Look up the interface called by `msg`. Also returns whether a task should be spawned for the
+    // call.
+    //
+    // The caller must not be told about a failed lookup before it's allowed to make the call.
+    async fn lookup_interface(
+        &self,
+        path: &ObjectPath<'_>,
+        iface_name: &InterfaceName<'_>,
+    ) -> fdo::Result<(Arc<RwLock<dyn Interface>>, bool)> {
+        // Ensure the root lock isn't held while dispatching the message. That
+        // way, the object server can be mutated during that time.
+        let root = self.root.read().await;
+        let node = root
+            .resolve_child(path)
+            .await
+            .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))?;
+
+        let iface = node.interface_lock(iface_name.as_ref()).ok_or_else(|| {
+            fdo::Error::UnknownInterface(format!("Unknown interface '{iface_name}'"))
+        })?;
+
+        Ok((iface.instance, iface.spawn_tasks_for_methods))
+    }
+
+    // Dispatch the call to the looked up interface, through the interceptors if `call` is set,
+    // once the caller is allowed to make it.
+    async fn dispatch_call_to_target(
+        &self,
+        target: fdo::Result<Arc<RwLock<dyn Interface>>>,
+        call: Option<MethodCall>,
+        connection: &Connection,
+        msg: &Message,
+        hdr: &Header<'_>,
+    ) -> fdo::Result<()> {
+        let call = match call {
+            Some(call) => call,
+            None => {
+                self.access.check(connection, msg).await?;
+
+                return self
+                    .dispatch_call_to_iface(target?, connection, msg, hdr)
+                    .await;
+            }
+        };
+
+        // The innermost link of the chain, so the interceptors also see the denied calls.
+        let reply = self
+            .interceptors
+            .run(&call, || async {
+                self.access.check(connection, msg).await?;
+                let iface = target.as_ref().map_err(Clone::clone)?.clone();
+
+                self.dispatch_call_to_iface(iface, connection, msg, hdr)
+                    .await
+            })
+            .await?;
+        if let Some(reply) = reply {
+            if let Err(e) = connection.send(&reply).await {
+                debug!("Error sending reply. Message: {:?}, error: {:?}", msg, e);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Dispatch an incoming message to a registered interface.
     ///
     /// The object server will handle the message by:
@@ -446,6 +863,10 @@ impl ObjectServer {
     }
 }
 
+fn limits_exceeded() -> fdo::Error {
+    fdo::Error::LimitsExceeded("Too many method calls in progress".into())
+}
+
 #[cfg(feature = "blocking-api")]
 impl From<crate::blocking::ObjectServer> for ObjectServer {
     fn from(server: crate::blocking::ObjectServer) -> Self {
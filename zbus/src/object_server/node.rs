@@ -3,6 +3,7 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::Write,
+    ops::Deref,
 };
 
 use zbus_names::InterfaceName;
@@ -14,13 +15,35 @@ use crate::{
     Connection, ObjectServer,
 };
 
-use super::{ArcInterface, Interface};
+use super::{subtree::Subtree, ArcInterface, Interface};
 
 #[derive(Default, Debug)]
 pub(crate) struct Node {
     path: OwnedObjectPath,
     children: HashMap<String, Node>,
     interfaces: HashMap<InterfaceName<'static>, ArcInterface>,
+    // The handler of the objects under this node, that are not among `children`.
+    subtree: Option<Subtree>,
+}
+
+/// A node found by [`Node::resolve_child`].
+#[derive(Debug)]
+pub(crate) enum NodeRef<'n> {
+    /// An explicitly registered node.
+    Static(&'n Node),
+    /// A node resolved on demand by a [`Subtree`] handler.
+    Resolved(Box<Node>),
+}
+
+impl Deref for NodeRef<'_> {
+    type Target = Node;
+
+    fn deref(&self) -> &Node {
+        match self {
+            NodeRef::Static(node) => node,
+            NodeRef::Resolved(node) => node,
+        }
+    }
 }
 
 impl Node {
@@ -53,6 +76,101 @@ impl Node {
         Some(node)
     }
 
+    // Get the child Node at path, falling back to the closest subtree handler above it.
+    pub(crate) async fn resolve_child(&self, path: &ObjectPath<'_>) -> Option<NodeRef<'_>> {
+        let mut node = self;
+        let mut subtree = None;
+
+        for i in path.split('/').skip(1) {
+            if i.is_empty() {
+                continue;
+            }
+            if node.subtree.is_some() {
+                subtree = node.subtree.as_ref();
+            }
+            match node.children.get(i) {
+                Some(n) => node = n,
+                None => {
+                    let path = path.to_owned().into();
+                    return Self::resolve(subtree?, path).await.map(NodeRef::Resolved);
+                }
+            }
+        }
+
+        Some(NodeRef::Static(node))
+    }
+
+    // Ask `subtree` for the object at `path`.
+    async fn resolve(subtree: &Subtree, path: OwnedObjectPath) -> Option<Box<Node>> {
+        let object = subtree.0.object(&path).await?;
+        let mut node = Self::new(path);
+        for (name, arc_iface) in object.interfaces {
+            node.add_arc_interface(name, arc_iface);
+        }
+        node.subtree = Some(subtree.clone());
+
+        Some(Box::new(node))
+    }
+
+    // The children of this node served by its subtree handler, if any.
+    //
+    // Children that are not objects themselves are returned as nodes with only the standard
+    // interfaces, so their own children can be walked.
+    async fn dynamic_children(&self) -> Vec<Box<Node>> {
+        let subtree = match &self.subtree {
+            Some(subtree) => subtree,
+            None => return vec![],
+        };
+
+        let mut nodes = vec![];
+        for name in subtree.0.children(&self.path).await {
+            if self.children.contains_key(&name) {
+                continue;
+            }
+            let path = match self.child_path(&name) {
+                Some(path) => path,
+                None => continue,
+            };
+            let node = match Self::resolve(subtree, path.clone()).await {
+                Some(node) => node,
+                None => {
+                    let mut node = Self::new(path);
+                    node.subtree = Some(subtree.clone());
+
+                    Box::new(node)
+                }
+            };
+            nodes.push(node);
+        }
+
+        nodes
+    }
+
+    fn child_path(&self, name: &str) -> Option<OwnedObjectPath> {
+        if name.contains('/') {
+            return None;
+        }
+        let path = if self.path.as_str() == "/" {
+            format!("/{name}")
+        } else {
+            format!("{}/{name}", self.path)
+        };
+
+        ObjectPath::try_from(path).ok().map(Into::into)
+    }
+
+    pub(super) fn set_subtree(&mut self, subtree: Option<Subtree>) -> Option<Subtree> {
+        std::mem::replace(&mut self.subtree, subtree)
+    }
+
+    pub(super) fn has_subtree(&self) -> bool {
+        self.subtree.is_some()
+    }
+
+    pub(super) fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
     /// Get the child Node at path. Optionally create one if it doesn't exist.
     ///
     /// This also returns the path of the parent node that implements ObjectManager (if any). If
@@ -101,12 +219,28 @@ impl Node {
     }
 
     pub(super) fn is_empty(&self) -> bool {
-        !self.interfaces.keys().any(|k| {
-            *k != Peer::name()
-                && *k != Introspectable::name()
-                && *k != Properties::name()
-                && *k != ObjectManager::name()
-        })
+        self.subtree.is_none() && !self.interfaces.keys().any(|k| !is_standard_interface(k))
+    }
+
+    pub(super) fn has_interface(&self, interface_name: &InterfaceName<'_>) -> bool {
+        self.interfaces.contains_key(interface_name)
+    }
+
+    /// Remove all the interfaces, except for the standard ones.
+    ///
+    /// Returns the names of the removed interfaces.
+    pub(super) fn remove_interfaces(&mut self) -> Vec<InterfaceName<'static>> {
+        let names: Vec<_> = self
+            .interfaces
+            .keys()
+            .filter(|k| !is_standard_interface(k))
+            .cloned()
+            .collect();
+        for name in &names {
+            self.interfaces.remove(name);
+        }
+
+        names
     }
 
     pub(super) fn remove_node(&mut self, node: &str) -> bool {
@@ -142,6 +276,8 @@ impl Node {
                 node: &'a Node,
                 level: usize,
             },
+            /// Represent a child node served by a subtree handler, listed without its content.
+            Leaf { name: String, level: usize },
             /// Represent a closing `</node>`.
             End { level: usize },
         }
@@ -161,6 +297,17 @@ impl Node {
                 Fragment::Node { name, node, level } => {
                     stack.push(Fragment::End { level });
 
+                    if let Some(subtree) = &node.subtree {
+                        for name in subtree.0.children(&node.path).await {
+                            if !node.children.contains_key(&name) {
+                                stack.push(Fragment::Leaf {
+                                    name,
+                                    level: level + 2,
+                                });
+                            }
+                        }
+                    }
+
                     for (name, node) in &node.children {
                         stack.push(Fragment::Node {
                             name,
@@ -197,6 +344,16 @@ impl Node {
                             .introspect_to_writer(writer, level + 2);
                     }
                 }
+                Fragment::Leaf { name, level } => {
+                    writeln!(
+                        writer,
+                        "{:indent$}<node name=\"{}\"/>",
+                        "",
+                        name,
+                        indent = level
+                    )
+                    .unwrap();
+                }
                 Fragment::End { level } => {
                     writeln!(writer, "{:indent$}</node>", "", indent = level).unwrap();
                 }
@@ -220,23 +377,28 @@ impl Node {
         let mut managed_objects = ManagedObjects::new();
 
         // Recursively get all properties of all interfaces of descendants.
-        let mut node_list: Vec<_> = self.children.values().collect();
+        let mut node_list: Vec<_> = self.children.values().map(NodeRef::Static).collect();
+        node_list.extend(
+            self.dynamic_children()
+                .await
+                .into_iter()
+                .map(NodeRef::Resolved),
+        );
         while let Some(node) = node_list.pop() {
             let mut interfaces = HashMap::new();
-            for iface_name in node.interfaces.keys().filter(|n| {
-                // Filter standard interfaces.
-                *n != &Peer::name()
-                    && *n != &Introspectable::name()
-                    && *n != &Properties::name()
-                    && *n != &ObjectManager::name()
-            }) {
+            // Filter standard interfaces.
+            for iface_name in node.interfaces.keys().filter(|n| !is_standard_interface(n)) {
                 let props = node
                     .get_properties(object_server, connection, iface_name.clone())
                     .await?;
                 interfaces.insert(iface_name.clone().into(), props);
             }
             managed_objects.insert(node.path.clone(), interfaces);
-            node_list.extend(node.children.values());
+            let dynamic_children = node.dynamic_children().await;
+            if let NodeRef::Static(node) = node {
+                node_list.extend(node.children.values().map(NodeRef::Static));
+            }
+            node_list.extend(dynamic_children.into_iter().map(NodeRef::Resolved));
         }
 
         Ok(managed_objects)
@@ -258,3 +420,11 @@ impl Node {
             .await
     }
 }
+
+// Whether `name` is one of the interfaces the object server implements for all objects.
+fn is_standard_interface(name: &InterfaceName<'_>) -> bool {
+    *name == Peer::name()
+        || *name == Introspectable::name()
+        || *name == Properties::name()
+        || *name == ObjectManager::name()
+}
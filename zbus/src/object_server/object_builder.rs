@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use zbus_names::InterfaceName;
+use zvariant::ObjectPath;
+
+use crate::{ObjectServer, Result};
+
+use super::{ArcInterface, Interface};
+
+/// An object to register on an [`ObjectServer`] with all its interfaces at once.
+///
+/// Create it with [`ObjectServer::add_object`]. In contrast to registering the interfaces one by
+/// one through [`ObjectServer::at`], the object appears to the clients with all its interfaces
+/// at once: a single `InterfacesAdded` signal of [`ObjectManager`] is emitted for all of them.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// use zbus::{interface, Connection};
+///
+/// struct Battery;
+///
+/// #[interface(name = "org.myservice.Battery")]
+/// impl Battery {
+///     #[zbus(property)]
+///     fn percentage(&self) -> f64 {
+///         42.
+///     }
+/// }
+///
+/// struct Device;
+///
+/// #[interface(name = "org.myservice.Device")]
+/// impl Device {
+///     #[zbus(property)]
+///     fn name(&self) -> &str {
+///         "Battery"
+///     }
+/// }
+///
+/// # async_io::block_on(async {
+/// let connection = Connection::session().await?;
+/// connection
+///     .object_server()
+///     .add_object("/org/myservice/devices/battery")?
+///     .with(Device)
+///     .with(Battery)
+///     .commit()
+///     .await?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::add_object`]: crate::ObjectServer::add_object
+/// [`ObjectServer::at`]: crate::ObjectServer::at
+/// [`ObjectManager`]: crate::fdo::ObjectManager
+#[derive(Debug)]
+#[must_use]
+pub struct ObjectBuilder<'s> {
+    server: &'s ObjectServer,
+    path: ObjectPath<'static>,
+    interfaces: HashMap<InterfaceName<'static>, ArcInterface>,
+}
+
+impl<'s> ObjectBuilder<'s> {
+    pub(crate) fn new(server: &'s ObjectServer, path: ObjectPath<'static>) -> Self {
+        Self {
+            server,
+            path,
+            interfaces: HashMap::new(),
+        }
+    }
+
+    /// Add an interface to the object.
+    ///
+    /// If the object already has an interface with the same name, it's replaced.
+    pub fn with<I>(mut self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        self.interfaces.insert(I::name(), ArcInterface::new(iface));
+
+        self
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    ///
+    /// If the object already has an interface with the same name, it's replaced.
+    ///
+    /// This method is only available when the `dynamic-interface` feature is enabled.
+    ///
+    /// [`DynamicInterface`]: super::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn with_dynamic(mut self, iface: super::DynamicInterface) -> Self {
+        let (name, iface) = iface.into_arc_interface();
+        self.interfaces.insert(name, iface);
+
+        self
+    }
+
+    /// Register the object.
+    ///
+    /// The object may already exist, in which case the interfaces are added to it.
+    ///
+    /// # Errors
+    ///
+    /// If any of the interfaces already exists at the path, an `Error::InterfaceExists` error is
+    /// returned and none of the interfaces is registered.
+    pub async fn commit(self) -> Result<()> {
+        if self.interfaces.is_empty() {
+            return Ok(());
+        }
+
+        self.server.add_interfaces(self.path, self.interfaces).await
+    }
+}
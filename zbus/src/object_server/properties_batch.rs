@@ -0,0 +1,284 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tracing::warn;
+use zbus_names::{InterfaceName, OwnedBusName, OwnedInterfaceName};
+use zvariant::{OwnedObjectPath, Value};
+
+use super::SignalEmitter;
+use crate::{abstractions::sleep, fdo, Result};
+
+/// The property changes of one interface, not emitted yet.
+#[derive(Debug, Default)]
+pub(crate) struct Changes {
+    changed: HashMap<String, Value<'static>>,
+    invalidated: Vec<String>,
+}
+
+impl Changes {
+    /// Merge more changes in.
+    ///
+    /// A later change to a property replaces the earlier ones, so a property that changed and was
+    /// then invalidated ends up only invalidated, and vice versa.
+    pub(crate) fn record(
+        &mut self,
+        changed: HashMap<&str, Value<'_>>,
+        invalidated: &[&str],
+    ) -> Result<()> {
+        for (name, value) in changed {
+            let value = Value::from(value.try_to_owned()?);
+            self.invalidated.retain(|n| n != name);
+            self.changed.insert(name.to_string(), value);
+        }
+        for name in invalidated {
+            self.changed.remove(*name);
+            if !self.invalidated.iter().any(|n| n == name) {
+                self.invalidated.push(name.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Emit all the changes in a single `PropertiesChanged` signal.
+    pub(crate) async fn emit(
+        self,
+        emitter: &SignalEmitter<'_>,
+        interface_name: InterfaceName<'_>,
+    ) -> Result<()> {
+        let (names, values): (Vec<_>, Vec<_>) = self.changed.into_iter().unzip();
+        let changed = names.iter().map(String::as_str).zip(values).collect();
+        let invalidated: Vec<_> = self.invalidated.iter().map(String::as_str).collect();
+
+        fdo::Properties::properties_changed(emitter, interface_name, changed, invalidated.into())
+            .await
+    }
+}
+
+/// The changes recorded in a [`PropertiesBatch`], per interface, in the order of their first
+/// change.
+pub(crate) type Pending = Arc<Mutex<Vec<(OwnedInterfaceName, Changes)>>>;
+
+/// A batch of property changes.
+///
+/// All the property changes signaled through the batch (e.g through the `<property>_changed` and
+/// `<property>_invalidate` methods generated by the [`crate::interface`] macro) are merged into a
+/// single `org.freedesktop.DBus.Properties.PropertiesChanged` signal per interface, emitted on
+/// [`PropertiesBatch::commit`].
+///
+/// The batch dereferences to the [`SignalEmitter`] to pass to these methods. It's created through
+/// [`SignalEmitter::batch`] or [`InterfaceRef::batch`].
+///
+/// If the batch is dropped without being committed, the pending changes are emitted from a
+/// separate task.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use async_io::block_on;
+/// use zbus::{Connection, interface};
+///
+/// struct Player {
+///     title: String,
+///     position: u64,
+/// }
+///
+/// #[interface(name = "org.myiface.Player")]
+/// impl Player {
+///     #[zbus(property)]
+///     fn title(&self) -> &str {
+///         &self.title
+///     }
+///
+///     #[zbus(property)]
+///     fn position(&self) -> u64 {
+///         self.position
+///     }
+/// }
+///
+/// # block_on(async {
+/// # let connection = Connection::session().await?;
+/// # let player = Player { title: String::new(), position: 0 };
+/// # connection.object_server().at("/org/zbus/player", player).await?;
+/// let iface_ref = connection
+///     .object_server()
+///     .interface::<_, Player>("/org/zbus/player")
+///     .await?;
+/// let mut player = iface_ref.get_mut().await;
+/// player.title = "Dancing Queen".to_string();
+/// player.position = 0;
+///
+/// // A single `PropertiesChanged` signal, for both properties.
+/// let batch = iface_ref.batch();
+/// player.title_changed(&batch).await?;
+/// player.position_changed(&batch).await?;
+/// batch.commit().await?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`InterfaceRef::batch`]: super::InterfaceRef::batch
+#[derive(Debug)]
+pub struct PropertiesBatch<'s> {
+    emitter: SignalEmitter<'s>,
+    pending: Pending,
+}
+
+impl<'s> PropertiesBatch<'s> {
+    pub(crate) fn new(emitter: SignalEmitter<'s>, pending: Pending) -> Self {
+        Self { emitter, pending }
+    }
+
+    /// Emit the changes recorded so far.
+    pub async fn commit(self) -> Result<()> {
+        let pending = self.take();
+
+        emit(&self.emitter, pending).await
+    }
+
+    fn take(&self) -> Vec<(OwnedInterfaceName, Changes)> {
+        std::mem::take(&mut *self.pending.lock().expect("poisoned lock"))
+    }
+}
+
+impl<'s> Deref for PropertiesBatch<'s> {
+    type Target = SignalEmitter<'s>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.emitter
+    }
+}
+
+impl Drop for PropertiesBatch<'_> {
+    fn drop(&mut self) {
+        let pending = self.take();
+        if pending.is_empty() {
+            return;
+        }
+
+        let emitter = self.emitter.to_owned();
+        let executor = emitter.connection().executor().clone();
+        executor
+            .spawn(
+                async move {
+                    if let Err(e) = emit(&emitter, pending).await {
+                        warn!("Failed to emit the changes of a dropped batch: {e}");
+                    }
+                },
+                "properties batch",
+            )
+            .detach();
+    }
+}
+
+async fn emit(
+    emitter: &SignalEmitter<'_>,
+    pending: Vec<(OwnedInterfaceName, Changes)>,
+) -> Result<()> {
+    for (interface_name, changes) in pending {
+        changes.emit(emitter, interface_name.into()).await?;
+    }
+
+    Ok(())
+}
+
+type CoalescingKey = (OwnedObjectPath, Option<OwnedBusName>, OwnedInterfaceName);
+
+/// Property changes waiting for their time window to elapse, before being emitted together.
+#[derive(Debug, Default)]
+pub(crate) struct Coalescer {
+    pending: Mutex<HashMap<CoalescingKey, Changes>>,
+}
+
+impl Coalescer {
+    /// Record property changes, to be emitted `window` after the first change recorded for the
+    /// same interface, object path and destination.
+    pub(crate) fn record(
+        self: &Arc<Self>,
+        emitter: &SignalEmitter<'_>,
+        interface_name: InterfaceName<'_>,
+        changed: HashMap<&str, Value<'_>>,
+        invalidated: &[&str],
+        window: Duration,
+    ) -> Result<()> {
+        let key = (
+            emitter.path().to_owned().into(),
+            emitter.destination().map(|d| d.to_owned().into()),
+            interface_name.to_owned().into(),
+        );
+        let mut pending = self.pending.lock().expect("poisoned lock");
+        let entry = match pending.entry(key.clone()) {
+            Entry::Occupied(entry) => return entry.into_mut().record(changed, invalidated),
+            Entry::Vacant(entry) => entry,
+        };
+        let mut changes = Changes::default();
+        changes.record(changed, invalidated)?;
+        entry.insert(changes);
+
+        let coalescer = self.clone();
+        let emitter = emitter.to_owned();
+        let executor = emitter.connection().executor().clone();
+        executor
+            .spawn(
+                async move {
+                    sleep(window).await;
+
+                    let changes = coalescer
+                        .pending
+                        .lock()
+                        .expect("poisoned lock")
+                        .remove(&key);
+                    let Some(changes) = changes else {
+                        return;
+                    };
+                    if let Err(e) = changes.emit(&emitter, key.2.into()).await {
+                        warn!("Failed to emit coalesced property changes: {e}");
+                    }
+                },
+                "properties coalescing",
+            )
+            .detach();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zvariant::Value;
+
+    use super::Changes;
+
+    #[test]
+    fn merge_changes() {
+        let mut changes = Changes::default();
+        changes
+            .record(HashMap::from([("Foo", Value::from(1u32))]), &["Bar"])
+            .unwrap();
+        changes
+            .record(HashMap::from([("Foo", Value::from(2u32))]), &[])
+            .unwrap();
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.changed["Foo"], Value::from(2u32));
+        assert_eq!(changes.invalidated, ["Bar"]);
+
+        // The latest kind of change wins.
+        changes
+            .record(
+                HashMap::from([("Bar", Value::from("bar"))]),
+                &["Foo", "Foo"],
+            )
+            .unwrap();
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.changed["Bar"], Value::from("bar"));
+        assert_eq!(changes.invalidated, ["Foo"]);
+    }
+}
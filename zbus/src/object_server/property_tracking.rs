@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use tracing::warn;
+use zbus_names::InterfaceName;
+use zvariant::{OwnedValue, Value};
+
+use super::{Interface, SignalEmitter};
+use crate::Result;
+
+/// The values of an interface's properties, at one point in time.
+///
+/// When property changes are tracked automatically (see the `track_properties` attribute of the
+/// [`crate::interface`] macro), the properties are snapshotted before and after each method call
+/// and each [`InterfaceRef::get_mut`] guard, to find out which ones changed.
+///
+/// [`InterfaceRef::get_mut`]: super::InterfaceRef::get_mut
+#[derive(Debug)]
+pub struct PropertySnapshot {
+    interface_name: InterfaceName<'static>,
+    // The name of each property, its value and whether its changes are signaled without it.
+    values: Vec<(&'static str, OwnedValue, bool)>,
+}
+
+impl PropertySnapshot {
+    /// Create an empty snapshot of the properties of the interface `interface_name`.
+    pub fn new(interface_name: InterfaceName<'static>) -> Self {
+        Self {
+            interface_name,
+            values: vec![],
+        }
+    }
+
+    /// Add the value of the property `name` to the snapshot.
+    ///
+    /// If `invalidates` is `true`, changes to the property are signaled without its new value.
+    pub fn insert(&mut self, name: &'static str, value: Value<'_>, invalidates: bool) {
+        // A value we can't keep, can't be compared either. The property isn't tracked then.
+        if let Ok(value) = value.try_to_owned() {
+            self.values.push((name, value, invalidates));
+        }
+    }
+
+    /// Emit the `org.freedesktop.DBus.Properties.PropertiesChanged` signal for the properties
+    /// whose value in `newer` differs from the one in `self`.
+    ///
+    /// Nothing is emitted if no property changed.
+    pub async fn emit_changes(&self, newer: &Self, emitter: &SignalEmitter<'_>) -> Result<()> {
+        let mut changed = HashMap::new();
+        let mut invalidated = vec![];
+        for (name, value, invalidates) in &newer.values {
+            let unchanged = self.values.iter().any(|(n, v, _)| n == name && v == value);
+            if unchanged {
+                continue;
+            }
+
+            if *invalidates {
+                invalidated.push(*name);
+            } else {
+                changed.insert(*name, Value::from(value.try_clone()?));
+            }
+        }
+        if changed.is_empty() && invalidated.is_empty() {
+            return Ok(());
+        }
+
+        emitter
+            .properties_changed(self.interface_name.clone(), changed, &invalidated)
+            .await
+    }
+}
+
+/// Tracks the changes to the properties of an interface, around a method call or a mutable
+/// borrow of the interface.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct PropertyTracker {
+    emitter: SignalEmitter<'static>,
+    before: PropertySnapshot,
+}
+
+impl PropertyTracker {
+    /// Snapshot the properties of `iface`, if it tracks their changes.
+    pub async fn start(iface: &dyn Interface, emitter: SignalEmitter<'static>) -> Option<Self> {
+        let before = iface.property_snapshot(&emitter).await?;
+
+        Some(Self { emitter, before })
+    }
+
+    /// The emitter of the property changes.
+    pub fn emitter(&self) -> &SignalEmitter<'static> {
+        &self.emitter
+    }
+
+    /// Snapshot the properties of `iface` again, and signal the ones that changed.
+    pub async fn finish(self, iface: &dyn Interface) {
+        let Some(after) = iface.property_snapshot(&self.emitter).await else {
+            return;
+        };
+        if let Err(e) = self.before.emit_changes(&after, &self.emitter).await {
+            warn!(
+                "Failed to emit the property changes of `{}`: {e}",
+                self.before.interface_name
+            );
+        }
+    }
+}
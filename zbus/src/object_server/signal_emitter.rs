@@ -1,6 +1,13 @@
+use std::{collections::HashMap, time::Duration};
+
 use zbus_names::{BusName, InterfaceName, MemberName};
+use zvariant::Value;
 
-use crate::{zvariant::ObjectPath, Connection, Error, Result};
+use super::{
+    properties_batch::{Pending, PropertiesBatch},
+    Changes,
+};
+use crate::{fdo, zvariant::ObjectPath, Connection, Error, Result};
 
 /// A signal emitter.
 ///
@@ -13,6 +20,7 @@ pub struct SignalEmitter<'s> {
     conn: Connection,
     path: ObjectPath<'s>,
     destination: Option<BusName<'s>>,
+    batch: Option<Pending>,
 }
 
 impl<'s> SignalEmitter<'s> {
@@ -27,6 +35,7 @@ impl<'s> SignalEmitter<'s> {
                 conn: conn.clone(),
                 path: p,
                 destination: None,
+                batch: None,
             })
             .map_err(Into::into)
     }
@@ -37,6 +46,7 @@ impl<'s> SignalEmitter<'s> {
             conn,
             path,
             destination: None,
+            batch: None,
         }
     }
 
@@ -60,6 +70,84 @@ impl<'s> SignalEmitter<'s> {
             .await
     }
 
+    /// Emit the `org.freedesktop.DBus.Properties.PropertiesChanged` signal.
+    ///
+    /// Unlike [`fdo::Properties::properties_changed`], this takes batches into account: if `self`
+    /// belongs to a [`PropertiesBatch`], the changes are only recorded, to be emitted when the
+    /// batch is committed.
+    pub async fn properties_changed(
+        &self,
+        interface_name: InterfaceName<'_>,
+        changed_properties: HashMap<&str, Value<'_>>,
+        invalidated_properties: &[&str],
+    ) -> Result<()> {
+        self.properties_changed_within(
+            interface_name,
+            changed_properties,
+            invalidated_properties,
+            None,
+        )
+        .await
+    }
+
+    /// Same as [`SignalEmitter::properties_changed`], but outside of a batch, the changes are
+    /// merged with the other ones signaled within `window` and emitted at its end.
+    #[doc(hidden)]
+    pub async fn properties_changed_within(
+        &self,
+        interface_name: InterfaceName<'_>,
+        changed_properties: HashMap<&str, Value<'_>>,
+        invalidated_properties: &[&str],
+        window: Option<Duration>,
+    ) -> Result<()> {
+        if let Some(batch) = &self.batch {
+            let mut batch = batch.lock().expect("poisoned lock");
+            let changes = match batch.iter_mut().find(|(name, _)| *name == interface_name) {
+                Some((_, changes)) => changes,
+                None => {
+                    batch.push((interface_name.to_owned().into(), Changes::default()));
+                    // SAFETY: We just pushed an element.
+                    &mut batch.last_mut().unwrap().1
+                }
+            };
+
+            return changes.record(changed_properties, invalidated_properties);
+        }
+
+        match window {
+            Some(window) => self.conn.object_server().coalescer().record(
+                self,
+                interface_name,
+                changed_properties,
+                invalidated_properties,
+                window,
+            ),
+            None => {
+                fdo::Properties::properties_changed(
+                    self,
+                    interface_name,
+                    changed_properties,
+                    invalidated_properties.into(),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Start a batch of property changes.
+    ///
+    /// The returned batch dereferences to a copy of `self`, through which the property changes
+    /// are recorded instead of emitted. See [`PropertiesBatch`] for details.
+    pub fn batch(&self) -> PropertiesBatch<'s> {
+        let pending = Pending::default();
+        let emitter = Self {
+            batch: Some(pending.clone()),
+            ..self.clone()
+        };
+
+        PropertiesBatch::new(emitter, pending)
+    }
+
     /// Set the destination for the signal emission.
     ///
     /// Signals are typically broadcasted and thus don't have a destination. However, there are
@@ -92,6 +180,7 @@ impl<'s> SignalEmitter<'s> {
             conn: self.conn.clone(),
             path: self.path.to_owned(),
             destination: self.destination.as_ref().map(|d| d.to_owned()),
+            batch: self.batch.clone(),
         }
     }
 
@@ -101,6 +190,7 @@ impl<'s> SignalEmitter<'s> {
             conn: self.conn,
             path: self.path.into_owned(),
             destination: self.destination.map(|d| d.into_owned()),
+            batch: self.batch,
         }
     }
 }
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use async_trait::async_trait;
+use zbus_names::InterfaceName;
+use zvariant::ObjectPath;
+
+use super::{ArcInterface, Interface};
+
+/// A handler serving all the objects under an object path, on demand.
+///
+/// Register it with [`ObjectServer::subtree_at`] for services exposing a large number of objects
+/// (e.g. one per file or per device), that are not worth registering individually with
+/// [`ObjectServer::at`].
+///
+/// The handler is consulted for any object path under the path it's registered at, for which no
+/// object was explicitly registered through [`ObjectServer::at`]. Explicitly registered objects
+/// always take precedence, including the intermediate objects of their paths.
+///
+/// The methods of the handler are called while the object server is locked for reading. Hence,
+/// they must not attempt to add or remove objects from the server. They should also return
+/// promptly, as other method calls are not dispatched in the meantime.
+///
+/// The objects returned by [`SubtreeHandler::object`] only live as long as the method call they
+/// were resolved for. The next call to the same path resolves a new object, so any state the
+/// interfaces keep must be shared with the handler (e.g. through an [`Arc`]) for it to persist.
+///
+/// Note that the handler is responsible for emitting the `InterfacesAdded` and
+/// `InterfacesRemoved` signals of [`ObjectManager`] for the objects it serves, if needed.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// use async_trait::async_trait;
+/// use zbus::{
+///     interface,
+///     object_server::{SubtreeHandler, SubtreeObject},
+///     zvariant::ObjectPath,
+///     Connection,
+/// };
+///
+/// struct Device(u32);
+///
+/// #[interface(name = "org.myservice.Device")]
+/// impl Device {
+///     #[zbus(property)]
+///     fn index(&self) -> u32 {
+///         self.0
+///     }
+/// }
+///
+/// struct Devices(u32);
+///
+/// #[async_trait]
+/// impl SubtreeHandler for Devices {
+///     async fn object(&self, path: &ObjectPath<'_>) -> Option<SubtreeObject> {
+///         let index = path.strip_prefix("/org/myservice/devices/")?.parse().ok()?;
+///
+///         (index < self.0).then(|| SubtreeObject::new().interface(Device(index)))
+///     }
+///
+///     async fn children(&self, path: &ObjectPath<'_>) -> Vec<String> {
+///         if path.as_str() != "/org/myservice/devices" {
+///             return vec![];
+///         }
+///
+///         (0..self.0).map(|i| i.to_string()).collect()
+///     }
+/// }
+///
+/// # async_io::block_on(async {
+/// let connection = Connection::session().await?;
+/// connection
+///     .object_server()
+///     .subtree_at("/org/myservice/devices", Devices(1000))
+///     .await?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`ObjectServer::subtree_at`]: crate::ObjectServer::subtree_at
+/// [`ObjectServer::at`]: crate::ObjectServer::at
+/// [`ObjectManager`]: crate::fdo::ObjectManager
+#[async_trait]
+pub trait SubtreeHandler: Send + Sync + 'static {
+    /// The object at `path`, or `None` if there is no such object.
+    ///
+    /// The standard interfaces (e.g `org.freedesktop.DBus.Properties`) are added to the returned
+    /// object on your behalf.
+    async fn object(&self, path: &ObjectPath<'_>) -> Option<SubtreeObject>;
+
+    /// The names of the direct children of `path`.
+    ///
+    /// These are listed on introspection and walked by `GetManagedObjects` of [`ObjectManager`].
+    /// A child doesn't need to be an object itself, but can be the intermediate node of its own
+    /// children.
+    ///
+    /// [`ObjectManager`]: crate::fdo::ObjectManager
+    async fn children(&self, path: &ObjectPath<'_>) -> Vec<String>;
+}
+
+/// An object resolved by a [`SubtreeHandler`].
+#[derive(Debug, Default)]
+pub struct SubtreeObject {
+    pub(super) interfaces: HashMap<InterfaceName<'static>, ArcInterface>,
+}
+
+impl SubtreeObject {
+    /// Create an object without any interface.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an interface to the object.
+    ///
+    /// If the object already has an interface with the same name, it's replaced.
+    ///
+    /// `iface` is dropped once the method call the object was resolved for is handled. Changes
+    /// made to its own fields, through `&mut self` methods or property setters, are therefore lost
+    /// for the next call. Keep such state in the handler instead, and share it with `iface`.
+    pub fn interface<I>(mut self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        self.interfaces.insert(I::name(), ArcInterface::new(iface));
+
+        self
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    ///
+    /// If the object already has an interface with the same name, it's replaced.
+    ///
+    /// This method is only available when the `dynamic-interface` feature is enabled.
+    ///
+    /// [`DynamicInterface`]: super::DynamicInterface
+    #[cfg(feature = "dynamic-interface")]
+    pub fn dynamic_interface(mut self, iface: super::DynamicInterface) -> Self {
+        let (name, iface) = iface.into_arc_interface();
+        self.interfaces.insert(name, iface);
+
+        self
+    }
+}
+
+/// A registered [`SubtreeHandler`].
+#[derive(Clone)]
+pub(crate) struct Subtree(pub Arc<dyn SubtreeHandler>);
+
+impl fmt::Debug for Subtree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arc<dyn SubtreeHandler>")
+            .finish_non_exhaustive()
+    }
+}
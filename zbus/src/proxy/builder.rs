@@ -1,4 +1,4 @@
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use std::{collections::HashSet, marker::PhantomData, sync::Arc, time::Duration};
 
 use zbus_names::{BusName, InterfaceName};
 use zvariant::{ObjectPath, Str};
@@ -29,6 +29,7 @@ pub struct Builder<'a, T = ()> {
     proxy_type: PhantomData<T>,
     cache: CacheProperties,
     uncached_properties: Option<HashSet<Str<'a>>>,
+    method_timeout: Option<Duration>,
 }
 
 impl<T> Clone for Builder<'_, T> {
@@ -40,6 +41,7 @@ impl<T> Clone for Builder<'_, T> {
             interface: self.interface.clone(),
             cache: self.cache,
             uncached_properties: self.uncached_properties.clone(),
+            method_timeout: self.method_timeout,
             proxy_type: PhantomData,
         }
     }
@@ -92,6 +94,21 @@ impl<'a, T> Builder<'a, T> {
         self
     }
 
+    /// Set the default timeout for method call replies.
+    ///
+    /// This overrides the default method timeout of the connection (see
+    /// [`crate::connection::Builder::method_timeout`]) for all method calls made through the
+    /// proxy, including the ones made by [`proxy`]-generated methods and property getters and
+    /// setters.
+    ///
+    /// [`proxy`]: macro@crate::proxy
+    #[must_use]
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.method_timeout = Some(timeout);
+
+        self
+    }
+
     pub(crate) fn build_internal(self) -> Result<Proxy<'a>> {
         let conn = self.conn;
         let destination = self
@@ -101,6 +118,7 @@ impl<'a, T> Builder<'a, T> {
         let interface = self.interface.ok_or(Error::MissingParameter("interface"))?;
         let cache = self.cache;
         let uncached_properties = self.uncached_properties.unwrap_or_default();
+        let method_timeout = self.method_timeout.or_else(|| conn.method_timeout());
 
         Ok(Proxy {
             inner: Arc::new(ProxyInner::new(
@@ -110,6 +128,7 @@ impl<'a, T> Builder<'a, T> {
                 interface,
                 cache,
                 uncached_properties,
+                method_timeout,
             )),
         })
     }
@@ -153,6 +172,7 @@ where
             interface: T::INTERFACE.clone(),
             cache: CacheProperties::default(),
             uncached_properties: None,
+            method_timeout: None,
             proxy_type: PhantomData,
         }
     }
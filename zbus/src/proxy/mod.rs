@@ -12,6 +12,7 @@ use std::{
     pin::Pin,
     sync::{Arc, OnceLock, RwLock, RwLockReadGuard},
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, info_span, instrument, trace, Instrument};
 
@@ -19,6 +20,7 @@ use zbus_names::{BusName, InterfaceName, MemberName, UniqueName};
 use zvariant::{ObjectPath, OwnedValue, Str, Value};
 
 use crate::{
+    abstractions::maybe_timeout,
     fdo::{self, IntrospectableProxy, NameOwnerChanged, PropertiesChangedStream, PropertiesProxy},
     message::{Flags, Message, Sequence, Type},
     AsyncDrop, Connection, Error, Executor, MatchRule, MessageStream, OwnedMatchRule, Result, Task,
@@ -102,6 +104,8 @@ pub(crate) struct ProxyInner<'a> {
     /// Set of properties which do not get cached, by name.
     /// This overrides proxy-level caching behavior.
     uncached_properties: HashSet<Str<'a>>,
+    /// The default timeout for method call replies.
+    method_timeout: Option<Duration>,
 }
 
 impl Drop for ProxyInnerStatic {
@@ -282,9 +286,12 @@ impl PropertiesCache {
         let cache_clone = cache.clone();
         let task_name = format!("{interface} proxy caching");
         let proxy_caching = async move {
-            let result = cache_clone
-                .init(proxy, interface, uncached_properties)
-                .await;
+            let method_timeout = proxy.inner().method_timeout();
+            let result = maybe_timeout(
+                cache_clone.init(proxy, interface, uncached_properties),
+                method_timeout,
+            )
+            .await;
             let (prop_changes, interface, uncached_properties) = {
                 let mut caching_result = cache_clone.caching_result.write().expect("lock poisoned");
                 let ready = match &*caching_result {
@@ -493,6 +500,7 @@ impl<'a> ProxyInner<'a> {
         interface: InterfaceName<'a>,
         cache: CacheProperties,
         uncached_properties: HashSet<Str<'a>>,
+        method_timeout: Option<Duration>,
     ) -> Self {
         let property_cache = match cache {
             CacheProperties::Yes | CacheProperties::Lazily => Some(OnceLock::new()),
@@ -508,6 +516,7 @@ impl<'a> ProxyInner<'a> {
             interface,
             property_cache,
             uncached_properties,
+            method_timeout,
         }
     }
 
@@ -637,22 +646,33 @@ impl<'a> Proxy<'a> {
         &self.inner.interface
     }
 
+    /// The default timeout for method call replies.
+    ///
+    /// This is the timeout set through [`Builder::method_timeout`], or the default method timeout
+    /// of the associated connection if none was set. `None` means method calls wait indefinitely
+    /// for replies.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner.method_timeout
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](https://docs.rs/zbus_xml) crate for parsing the
     /// result.
     pub async fn introspect(&self) -> fdo::Result<String> {
-        let proxy = IntrospectableProxy::builder(&self.inner.inner_without_borrows.conn)
+        let mut builder = IntrospectableProxy::builder(&self.inner.inner_without_borrows.conn)
             .destination(&self.inner.destination)?
-            .path(&self.inner.path)?
-            .build()
-            .await?;
+            .path(&self.inner.path)?;
+        if let Some(timeout) = self.method_timeout() {
+            builder = builder.method_timeout(timeout);
+        }
+        let proxy = builder.build().await?;
 
         proxy.introspect().await
     }
 
     fn properties_proxy(&self) -> PropertiesProxy<'_> {
-        PropertiesProxy::builder(&self.inner.inner_without_borrows.conn)
+        let mut builder = PropertiesProxy::builder(&self.inner.inner_without_borrows.conn)
             // Safe because already checked earlier
             .destination(self.inner.destination.as_ref())
             .unwrap()
@@ -660,14 +680,16 @@ impl<'a> Proxy<'a> {
             .path(self.inner.path.as_ref())
             .unwrap()
             // does not have properties
-            .cache_properties(CacheProperties::No)
-            .build_internal()
-            .unwrap()
-            .into()
+            .cache_properties(CacheProperties::No);
+        if let Some(timeout) = self.method_timeout() {
+            builder = builder.method_timeout(timeout);
+        }
+
+        builder.build_internal().unwrap().into()
     }
 
     fn owned_properties_proxy(&self) -> PropertiesProxy<'static> {
-        PropertiesProxy::builder(&self.inner.inner_without_borrows.conn)
+        let mut builder = PropertiesProxy::builder(&self.inner.inner_without_borrows.conn)
             // Safe because already checked earlier
             .destination(self.inner.destination.to_owned())
             .unwrap()
@@ -675,10 +697,12 @@ impl<'a> Proxy<'a> {
             .path(self.inner.path.to_owned())
             .unwrap()
             // does not have properties
-            .cache_properties(CacheProperties::No)
-            .build_internal()
-            .unwrap()
-            .into()
+            .cache_properties(CacheProperties::No);
+        if let Some(timeout) = self.method_timeout() {
+            builder = builder.method_timeout(timeout);
+        }
+
+        builder.build_internal().unwrap().into()
     }
 
     /// Get the cache, starting it in the background if needed.
@@ -826,11 +850,12 @@ impl<'a> Proxy<'a> {
         self.inner
             .inner_without_borrows
             .conn
-            .call_method(
+            .call_method_with_timeout(
                 Some(&self.inner.destination),
                 self.inner.path.as_str(),
                 Some(&self.inner.interface),
                 method_name,
+                self.method_timeout(),
                 body,
             )
             .await
@@ -853,6 +878,46 @@ impl<'a> Proxy<'a> {
         reply.body().deserialize()
     }
 
+    /// Call a method and return the reply body, with the given reply timeout.
+    ///
+    /// This is the same as [`call`], except that `timeout` is used instead of the default method
+    /// timeout of the proxy. If `timeout` is `None`, the call will wait for the reply
+    /// indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// If no reply is received within `timeout`, [`Error::Timeout`] is returned.
+    ///
+    /// [`call`]: struct.Proxy.html#method.call
+    pub async fn call_with_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<R>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        let reply = self
+            .inner
+            .inner_without_borrows
+            .conn
+            .call_method_with_timeout(
+                Some(&self.inner.destination),
+                self.inner.path.as_str(),
+                Some(&self.inner.interface),
+                method_name,
+                timeout,
+                body,
+            )
+            .await?;
+
+        reply.body().deserialize()
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -889,7 +954,11 @@ impl<'a> Proxy<'a> {
             )
             .await?
         {
-            Some(reply) => reply.await?.body().deserialize().map(Some),
+            Some(reply) => maybe_timeout(reply, self.method_timeout())
+                .await?
+                .body()
+                .deserialize()
+                .map(Some),
             None => Ok(None),
         }
     }
@@ -1378,6 +1447,50 @@ mod tests {
         block_on(test_signal()).unwrap();
     }
 
+    #[test]
+    #[timeout(15000)]
+    fn method_timeout() {
+        block_on(test_method_timeout()).unwrap();
+    }
+
+    async fn test_method_timeout() -> Result<()> {
+        // `dest_conn` has no object server so it never replies to method calls.
+        let conn = connection::Builder::session()?
+            .method_timeout(Duration::from_secs(60))
+            .build()
+            .await?;
+        let dest_conn = Connection::session().await?;
+        let unique_name = dest_conn.unique_name().unwrap().clone();
+
+        let proxy: Proxy<'_> = Builder::new(&conn)
+            .destination(unique_name)?
+            .path("/org/freedesktop/zbus/Timeout")?
+            .interface("org.freedesktop.zbus.Timeout")?
+            .cache_properties(CacheProperties::No)
+            .method_timeout(Duration::from_millis(100))
+            .build()
+            .await?;
+        assert_eq!(conn.method_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(proxy.method_timeout(), Some(Duration::from_millis(100)));
+
+        let err = proxy.call::<_, _, ()>("Hang", &()).await.unwrap_err();
+        assert_eq!(err, Error::Timeout);
+        let err = proxy
+            .call_with_timeout::<_, _, ()>("Hang", Some(Duration::from_millis(10)), &())
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::Timeout);
+
+        // Timeouts are reported as `NoReply` errors by `fdo::Error`.
+        let err = fdo::Error::from(err);
+        assert!(matches!(err, fdo::Error::NoReply(_)));
+        // ..which is also what the `Properties` proxy methods return.
+        let err = proxy.get_property::<u32>("Hanging").await.unwrap_err();
+        assert!(matches!(err, Error::FDO(e) if matches!(*e, fdo::Error::NoReply(_))));
+
+        Ok(())
+    }
+
     async fn test_signal() -> Result<()> {
         // Register a well-known name with the session bus and ensure we get the appropriate
         // signals called for that.
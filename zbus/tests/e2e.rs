@@ -1,7 +1,7 @@
 #![allow(clippy::disallowed_names)]
-use std::collections::HashMap;
 #[cfg(all(unix, not(feature = "tokio"), feature = "p2p"))]
 use std::os::unix::net::UnixStream;
+use std::{collections::HashMap, sync::Arc};
 #[cfg(all(unix, feature = "tokio", feature = "p2p"))]
 use tokio::net::UnixStream;
 
@@ -16,10 +16,13 @@ use zbus::{
     block_on,
     fdo::{ObjectManager, ObjectManagerProxy},
     message,
-    object_server::ResponseDispatchNotifier,
+    object_server::{
+        Access, CallLimits, Interceptor, MethodCall, Next, Overflow, ResponseDispatchNotifier,
+        SubtreeHandler, SubtreeObject,
+    },
     DBusError, Error, Message, MessageStream,
 };
-use zvariant::{Optional, OwnedValue, Str, Type, Value};
+use zvariant::{ObjectPath, Optional, OwnedValue, Str, Type, Value};
 
 use zbus::{
     connection, interface,
@@ -1095,3 +1098,999 @@ async fn iface_and_proxy_(#[allow(unused)] p2p: bool) {
     );
     debug!("Bus confirmed that all names were definitely released.");
 }
+
+struct File(u32);
+
+#[interface(name = "org.zbus.File")]
+impl File {
+    fn size(&self) -> u32 {
+        self.0 * 10
+    }
+
+    #[zbus(property)]
+    fn index(&self) -> u32 {
+        self.0
+    }
+}
+
+struct Files;
+
+#[async_trait::async_trait]
+impl SubtreeHandler for Files {
+    async fn object(&self, path: &ObjectPath<'_>) -> Option<SubtreeObject> {
+        let index: u32 = path.strip_prefix("/org/zbus/Files/")?.parse().ok()?;
+
+        (index < 3).then(|| SubtreeObject::new().interface(File(index)))
+    }
+
+    async fn children(&self, path: &ObjectPath<'_>) -> Vec<String> {
+        if path.as_str() != "/org/zbus/Files" {
+            return vec![];
+        }
+
+        (0..3)
+            .map(|i| i.to_string())
+            .chain(["static".into()])
+            .collect()
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn subtree_handler() {
+    block_on(subtree_handler_());
+}
+
+#[instrument]
+async fn subtree_handler_() {
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Files", ObjectManager)
+        .unwrap()
+        .serve_at("/org/zbus/Files/static", File(42))
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let server = service_conn.object_server();
+    assert!(server.subtree_at("/org/zbus/Files", Files).await.unwrap());
+    assert!(!server.subtree_at("/org/zbus/Files", Files).await.unwrap());
+    let dest = service_conn.unique_name().unwrap().to_owned();
+
+    let client_conn = Connection::session().await.unwrap();
+    let file = |path: &'static str| {
+        zbus::Proxy::new_owned(client_conn.clone(), dest.clone(), path, "org.zbus.File")
+    };
+
+    // Objects resolved by the handler, and explicitly registered ones taking precedence.
+    let proxy = file("/org/zbus/Files/1").await.unwrap();
+    assert_eq!(proxy.call::<_, _, u32>("Size", &()).await.unwrap(), 10);
+    assert_eq!(proxy.get_property::<u32>("Index").await.unwrap(), 1);
+    let proxy = file("/org/zbus/Files/static").await.unwrap();
+    assert_eq!(proxy.call::<_, _, u32>("Size", &()).await.unwrap(), 420);
+    let proxy = file("/org/zbus/Files/3").await.unwrap();
+    let err = proxy.call::<_, _, u32>("Size", &()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::MethodError(name, _, _) if name == "org.freedesktop.DBus.Error.UnknownObject"
+    ));
+
+    let xml = zbus::fdo::IntrospectableProxy::builder(&client_conn)
+        .destination(&dest)
+        .unwrap()
+        .path("/org/zbus/Files")
+        .unwrap()
+        .build()
+        .await
+        .unwrap()
+        .introspect()
+        .await
+        .unwrap();
+    let node = zbus_xml::Node::from_reader(xml.as_bytes()).unwrap();
+    let mut children: Vec<_> = node.nodes().iter().filter_map(|n| n.name()).collect();
+    children.sort();
+    assert_eq!(children, ["0", "1", "2", "static"]);
+
+    let objects = ObjectManagerProxy::builder(&client_conn)
+        .destination(&dest)
+        .unwrap()
+        .path("/org/zbus/Files")
+        .unwrap()
+        .build()
+        .await
+        .unwrap()
+        .get_managed_objects()
+        .await
+        .unwrap();
+    assert_eq!(objects.len(), 4);
+    let path = ObjectPath::try_from("/org/zbus/Files/2").unwrap();
+    let props = &objects[&path]["org.zbus.File"];
+    assert_eq!(u32::try_from(&props["Index"]).unwrap(), 2);
+
+    assert!(!server.remove_subtree("/org/zbus/Files").await.unwrap());
+    let proxy = file("/org/zbus/Files/1").await.unwrap();
+    assert!(proxy.call::<_, _, u32>("Size", &()).await.is_err());
+    let proxy = file("/org/zbus/Files/static").await.unwrap();
+    assert_eq!(proxy.call::<_, _, u32>("Size", &()).await.unwrap(), 420);
+}
+
+struct Counter {
+    own: u32,
+    shared: Arc<std::sync::atomic::AtomicU32>,
+}
+
+#[interface(name = "org.zbus.Counter")]
+impl Counter {
+    fn increment(&mut self) -> (u32, u32) {
+        self.own += 1;
+        let shared = self
+            .shared
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+            + 1;
+
+        (self.own, shared)
+    }
+}
+
+struct Counters(Arc<std::sync::atomic::AtomicU32>);
+
+#[async_trait::async_trait]
+impl SubtreeHandler for Counters {
+    async fn object(&self, path: &ObjectPath<'_>) -> Option<SubtreeObject> {
+        (path.as_str() == "/org/zbus/Counters/0").then(|| {
+            SubtreeObject::new().interface(Counter {
+                own: 0,
+                shared: self.0.clone(),
+            })
+        })
+    }
+
+    async fn children(&self, _path: &ObjectPath<'_>) -> Vec<String> {
+        vec![]
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn subtree_handler_state() {
+    block_on(subtree_handler_state_());
+}
+
+#[instrument]
+async fn subtree_handler_state_() {
+    let service_conn = Connection::session().await.unwrap();
+    let server = service_conn.object_server();
+    let err = server
+        .remove_subtree("/org/zbus/Counters")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::FDO(e) if matches!(*e, zbus::fdo::Error::UnknownObject(_))
+    ));
+    let shared = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    assert!(server
+        .subtree_at("/org/zbus/Counters", Counters(shared.clone()))
+        .await
+        .unwrap());
+    let dest = service_conn.unique_name().unwrap().to_owned();
+
+    let client_conn = Connection::session().await.unwrap();
+    let proxy = zbus::Proxy::new(
+        &client_conn,
+        dest,
+        "/org/zbus/Counters/0",
+        "org.zbus.Counter",
+    )
+    .await
+    .unwrap();
+    // Each call is served by a fresh instance, so only the state shared with the handler persists.
+    for i in 1..=3 {
+        let counts: (u32, u32) = proxy.call("Increment", &()).await.unwrap();
+        assert_eq!(counts, (1, i));
+    }
+    assert_eq!(shared.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    assert!(server.remove_subtree("/org/zbus/Counters").await.unwrap());
+}
+
+#[test]
+#[timeout(15000)]
+fn access_policy() {
+    block_on(access_policy_());
+}
+
+#[instrument]
+async fn access_policy_() {
+    let requests = Arc::new(std::sync::Mutex::new(vec![]));
+    let policy_requests = requests.clone();
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Files/7", File(7))
+        .unwrap()
+        .access_policy(move |request| {
+            let requests = policy_requests.clone();
+            async move {
+                let denied = request.interface() == "org.zbus.File"
+                    && *request.access() == Access::GetProperty("Index".into());
+                requests.lock().unwrap().push((
+                    request.path().to_string(),
+                    request.access().clone(),
+                    request.credentials().process_id().is_some(),
+                ));
+                if denied {
+                    return Err(zbus::fdo::Error::AccessDenied("Not for you".into()));
+                }
+
+                Ok(())
+            }
+        })
+        .build()
+        .await
+        .unwrap();
+    let dest = service_conn.unique_name().unwrap().to_owned();
+
+    let client_conn = Connection::session().await.unwrap();
+    let proxy: zbus::Proxy<'_> = zbus::proxy::Builder::new(&client_conn)
+        .destination(dest)
+        .unwrap()
+        .path("/org/zbus/Files/7")
+        .unwrap()
+        .interface("org.zbus.File")
+        .unwrap()
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(proxy.call::<_, _, u32>("Size", &()).await.unwrap(), 70);
+    let err = proxy.get_property::<u32>("Index").await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::FDO(e) if matches!(&*e, zbus::fdo::Error::AccessDenied(msg) if msg == "Not for you")
+    ));
+    let path = "/org/zbus/Files/7".to_string();
+    assert_eq!(
+        *requests.lock().unwrap(),
+        [
+            (
+                path.clone(),
+                Access::Method("Size".try_into().unwrap()),
+                true
+            ),
+            (path, Access::GetProperty("Index".into()), true),
+        ]
+    );
+
+    service_conn.object_server().remove_access_policy();
+    assert_eq!(proxy.get_property::<u32>("Index").await.unwrap(), 7);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+type RecordedCalls = Arc<std::sync::Mutex<Vec<(String, Option<message::Type>)>>>;
+
+// Records the calls along with the type of their replies.
+struct Recorder(RecordedCalls);
+
+#[async_trait::async_trait]
+impl Interceptor for Recorder {
+    async fn intercept(
+        &self,
+        call: &MethodCall,
+        next: Next<'_>,
+    ) -> zbus::fdo::Result<Option<Message>> {
+        let reply = next.run().await;
+        let reply_type = reply
+            .as_ref()
+            .ok()
+            .and_then(|r| r.as_ref())
+            .map(|r| r.message_type());
+        self.0
+            .lock()
+            .unwrap()
+            .push((call.member().to_string(), reply_type));
+
+        reply
+    }
+}
+
+// Overrides the `Index` property and translates the errors.
+struct Overrider;
+
+#[async_trait::async_trait]
+impl Interceptor for Overrider {
+    async fn intercept(
+        &self,
+        call: &MethodCall,
+        next: Next<'_>,
+    ) -> zbus::fdo::Result<Option<Message>> {
+        if *call.access() == Access::GetProperty("Index".into()) {
+            let reply = Message::method_return(&call.header())?.build(&Value::from(100u32))?;
+
+            return Ok(Some(reply));
+        }
+
+        next.run()
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(format!("Translated: {e}")))
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn interceptors() {
+    block_on(interceptors_());
+}
+
+#[instrument]
+async fn interceptors_() {
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Files/3", File(3))
+        .unwrap()
+        .interceptor(Recorder(calls.clone()))
+        .interceptor(Overrider)
+        .build()
+        .await
+        .unwrap();
+    let dest = service_conn.unique_name().unwrap().to_owned();
+
+    let client_conn = Connection::session().await.unwrap();
+    let proxy: zbus::Proxy<'_> = zbus::proxy::Builder::new(&client_conn)
+        .destination(dest)
+        .unwrap()
+        .path("/org/zbus/Files/3")
+        .unwrap()
+        .interface("org.zbus.File")
+        .unwrap()
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(proxy.call::<_, _, u32>("Size", &()).await.unwrap(), 30);
+    assert_eq!(proxy.get_property::<u32>("Index").await.unwrap(), 100);
+    let err = proxy.call::<_, _, u32>("Weight", &()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::MethodError(name, Some(msg), _)
+            if name == "org.freedesktop.DBus.Error.Failed"
+                && msg == "Translated: org.freedesktop.DBus.Error.UnknownMethod: Unknown method 'Weight'"
+    ));
+    let err = proxy.set_property("Index", 5u32).await.unwrap_err();
+    assert!(matches!(err, zbus::fdo::Error::UnknownProperty(_)));
+
+    // The access policy is checked within the chain.
+    service_conn
+        .object_server()
+        .set_access_policy(|_| async { Err(zbus::fdo::Error::AccessDenied("Not for you".into())) });
+    let err = proxy.call::<_, _, u32>("Size", &()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::MethodError(name, Some(msg), _)
+            if name == "org.freedesktop.DBus.Error.Failed"
+                && msg == "Translated: org.freedesktop.DBus.Error.AccessDenied: Not for you"
+    ));
+    assert_eq!(proxy.get_property::<u32>("Index").await.unwrap(), 100);
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            ("Size".to_string(), Some(message::Type::MethodReturn)),
+            ("Get".to_string(), Some(message::Type::MethodReturn)),
+            ("Weight".to_string(), None),
+            ("Set".to_string(), Some(message::Type::Error)),
+            ("Size".to_string(), None),
+            ("Get".to_string(), Some(message::Type::MethodReturn)),
+        ]
+    );
+}
+
+struct Label(&'static str);
+
+#[interface(name = "org.zbus.Label")]
+impl Label {
+    #[zbus(property)]
+    fn label(&self) -> &str {
+        self.0
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn add_object() {
+    block_on(add_object_());
+}
+
+#[instrument]
+async fn add_object_() {
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Objects", ObjectManager)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let server = service_conn.object_server();
+    let client_conn = Connection::session().await.unwrap();
+    let manager = ObjectManagerProxy::builder(&client_conn)
+        .destination(service_conn.unique_name().unwrap().to_owned())
+        .unwrap()
+        .path("/org/zbus/Objects")
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let mut added_stream = manager.receive_interfaces_added().await.unwrap();
+    let mut removed_stream = manager.receive_interfaces_removed().await.unwrap();
+
+    server
+        .add_object("/org/zbus/Objects/1")
+        .unwrap()
+        .with(File(1))
+        .with(Label("one"))
+        .commit()
+        .await
+        .unwrap();
+    let added = added_stream.next().await.unwrap();
+    let args = added.args().unwrap();
+    assert_eq!(args.object_path(), "/org/zbus/Objects/1");
+    let ifaces = args.interfaces_and_properties();
+    assert_eq!(ifaces.len(), 2);
+    assert_eq!(ifaces["org.zbus.File"]["Index"], Value::from(1u32));
+    assert_eq!(ifaces["org.zbus.Label"]["Label"], Value::from("one"));
+
+    // Nothing is registered if any of the interfaces already exists.
+    let err = server
+        .add_object("/org/zbus/Objects/1")
+        .unwrap()
+        .with(Gate {
+            started: channel(1).0,
+            release: Arc::new(Event::new()),
+        })
+        .with(Label("uno"))
+        .commit()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InterfaceExists(name, _) if name == "org.zbus.Label"));
+    assert!(server
+        .interface::<_, Gate>("/org/zbus/Objects/1")
+        .await
+        .is_err());
+
+    assert!(server.remove_object("/org/zbus/Objects/1").await.unwrap());
+    let removed = removed_stream.next().await.unwrap();
+    let args = removed.args().unwrap();
+    assert_eq!(args.object_path(), "/org/zbus/Objects/1");
+    let mut ifaces: Vec<_> = args.interfaces().iter().map(|i| i.as_str()).collect();
+    ifaces.sort();
+    assert_eq!(ifaces, ["org.zbus.File", "org.zbus.Label"]);
+    assert!(matches!(
+        server.remove_object("/org/zbus/Objects/1").await,
+        Err(Error::InterfaceNotFound)
+    ));
+}
+
+struct Gate {
+    started: Sender<()>,
+    release: Arc<Event>,
+}
+
+#[interface(name = "org.zbus.Gate")]
+impl Gate {
+    async fn wait(&self) {
+        let listener = self.release.listen();
+        self.started.send(()).await.unwrap();
+        listener.await;
+    }
+
+    fn ping(&self) {}
+}
+
+#[test]
+#[timeout(15000)]
+fn call_limits() {
+    block_on(call_limits_());
+}
+
+#[instrument]
+async fn call_limits_() {
+    let (started_tx, mut started_rx) = channel(1);
+    let release = Arc::new(Event::new());
+    let gate = Gate {
+        started: started_tx,
+        release: release.clone(),
+    };
+    let one = std::num::NonZeroUsize::new(1).unwrap();
+    let limits = CallLimits::new()
+        .max_calls_per_sender(one)
+        .overflow(Overflow::Reject);
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Gate", gate)
+        .unwrap()
+        .call_limits(limits)
+        .build()
+        .await
+        .unwrap();
+    let dest = service_conn.unique_name().unwrap().to_owned();
+
+    let gate = |conn: Connection| {
+        zbus::Proxy::new_owned(conn, dest.clone(), "/org/zbus/Gate", "org.zbus.Gate")
+    };
+    let proxy = gate(Connection::session().await.unwrap()).await.unwrap();
+    let other_proxy = gate(Connection::session().await.unwrap()).await.unwrap();
+
+    let waiting = async { proxy.call::<_, _, ()>("Wait", &()).await.unwrap() };
+    let limited = async {
+        started_rx.recv().await.unwrap();
+
+        // The sender already has a call in flight, unlike the other one.
+        let err = proxy.call::<_, _, ()>("Ping", &()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MethodError(name, _, _)
+                if name == "org.freedesktop.DBus.Error.LimitsExceeded"
+        ));
+        other_proxy.call::<_, _, ()>("Ping", &()).await.unwrap();
+
+        release.notify(usize::MAX);
+    };
+    futures_util::future::join(waiting, limited).await;
+
+    // The call in flight is accounted for until it's handled.
+    proxy.call::<_, _, ()>("Ping", &()).await.unwrap();
+
+    // Queued calls don't hold up the calls of other senders.
+    service_conn.object_server().set_call_limits(
+        CallLimits::new()
+            .max_calls_per_sender(one)
+            .overflow(Overflow::Queue)
+            .max_queued_per_sender(1),
+    );
+    let queued_done = std::sync::atomic::AtomicBool::new(false);
+    let waiting = async { proxy.call::<_, _, ()>("Wait", &()).await.unwrap() };
+    let queued = async {
+        started_rx.recv().await.unwrap();
+
+        let queued = async {
+            proxy.call::<_, _, ()>("Ping", &()).await.unwrap();
+            queued_done.store(true, std::sync::atomic::Ordering::SeqCst);
+        };
+        // The queue of the sender is already full.
+        let overflowing = async {
+            let err = proxy.call::<_, _, ()>("Ping", &()).await.unwrap_err();
+            assert!(matches!(
+                err,
+                Error::MethodError(name, _, _)
+                    if name == "org.freedesktop.DBus.Error.LimitsExceeded"
+            ));
+        };
+        let queued = futures_util::future::join(queued, overflowing);
+        let other = async {
+            other_proxy.call::<_, _, ()>("Ping", &()).await.unwrap();
+            assert!(!queued_done.load(std::sync::atomic::Ordering::SeqCst));
+
+            release.notify(usize::MAX);
+        };
+        futures_util::future::join(queued, other).await;
+    };
+    futures_util::future::join(waiting, queued).await;
+    assert!(queued_done.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[cfg(feature = "dynamic-interface")]
+#[test]
+#[timeout(15000)]
+fn dynamic_interface() {
+    block_on(dynamic_interface_());
+}
+
+#[cfg(feature = "dynamic-interface")]
+#[instrument]
+async fn dynamic_interface_() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use zbus::object_server::DynamicInterface;
+
+    let xml = r#"
+      <node>
+        <interface name="org.zbus.Calculator">
+          <method name="Add">
+            <arg name="a" type="u" direction="in"/>
+            <arg name="b" type="u" direction="in"/>
+            <arg name="sum" type="u" direction="out"/>
+          </method>
+          <method name="Swap">
+            <arg name="pair" type="(uu)" direction="in"/>
+            <arg name="swapped" type="(uu)" direction="out"/>
+          </method>
+          <signal name="Added">
+            <arg name="sum" type="u"/>
+          </signal>
+          <property name="Name" type="s" access="read"/>
+          <property name="Total" type="u" access="readwrite"/>
+          <property name="Label" type="v" access="readwrite"/>
+        </interface>
+      </node>
+    "#;
+    let node = zbus_xml::Node::from_reader(xml.as_bytes()).unwrap();
+    let description = node.interfaces()[0].clone();
+
+    // All methods & properties need their handlers.
+    assert!(DynamicInterface::builder(description.clone())
+        .getter("Name", |_| async {
+            Ok(OwnedValue::from(Str::from("calc")))
+        })
+        .build()
+        .is_err());
+
+    let total = Arc::new(AtomicU32::new(0));
+    let (get_total, set_total) = (total.clone(), total.clone());
+    let label = Arc::new(std::sync::Mutex::new(OwnedValue::from(0u8)));
+    let (get_label, set_label) = (label.clone(), label);
+    let iface = DynamicInterface::builder(description)
+        .method("Add", move |ctxt, args| {
+            let total = total.clone();
+            async move {
+                let a = u32::try_from(&args[0]).map_err(Error::from)?;
+                let b = u32::try_from(&args[1]).map_err(Error::from)?;
+                total.fetch_add(a + b, Ordering::SeqCst);
+                ctxt.emit_signal("Added", vec![(a + b).into()]).await?;
+
+                Ok(vec![(a + b).into()])
+            }
+        })
+        .method("Swap", |_, args| async move {
+            let (a, b): (u32, u32) = args[0]
+                .try_clone()
+                .map_err(Error::from)?
+                .try_into()
+                .map_err(Error::from)?;
+
+            Ok(vec![Value::from((b, a)).try_into().map_err(Error::from)?])
+        })
+        .getter("Name", |_| async {
+            Ok(OwnedValue::from(Str::from("calc")))
+        })
+        .getter("Total", move |_| {
+            let total = get_total.load(Ordering::SeqCst);
+            async move { Ok(total.into()) }
+        })
+        .setter("Total", move |_, value| {
+            let res = u32::try_from(value).map(|v| set_total.store(v, Ordering::SeqCst));
+            async move { Ok(res.map_err(Error::from)?) }
+        })
+        .getter("Label", move |_| {
+            let label = get_label.lock().unwrap().try_clone();
+            async move { Ok(label.map_err(Error::from)?) }
+        })
+        .setter("Label", move |_, value| {
+            *set_label.lock().unwrap() = value;
+            async { Ok(()) }
+        })
+        .build()
+        .unwrap();
+
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_dynamic_at("/org/zbus/Calculator", iface)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let dest = service_conn.unique_name().unwrap().to_owned();
+    let client_conn = Connection::session().await.unwrap();
+    let proxy: zbus::Proxy<'_> = zbus::proxy::Builder::new(&client_conn)
+        .destination(&dest)
+        .unwrap()
+        .path("/org/zbus/Calculator")
+        .unwrap()
+        .interface("org.zbus.Calculator")
+        .unwrap()
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+        .unwrap();
+
+    let mut added = proxy.receive_signal("Added").await.unwrap();
+    assert_eq!(
+        proxy.call::<_, _, u32>("Add", &(1u32, 2u32)).await.unwrap(),
+        3
+    );
+    let signal = added.next().await.unwrap();
+    assert_eq!(signal.body().deserialize::<u32>().unwrap(), 3);
+    // A single structure argument isn't confused with its fields as separate arguments.
+    assert_eq!(
+        proxy
+            .call::<_, _, (u32, u32)>("Swap", &((1u32, 2u32),))
+            .await
+            .unwrap(),
+        (2, 1)
+    );
+    let err = proxy
+        .call::<_, _, u32>("Add", &("1", 2u32))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::MethodError(name, _, _) if name == "org.freedesktop.DBus.Error.InvalidArgs"
+    ));
+
+    assert_eq!(proxy.get_property::<String>("Name").await.unwrap(), "calc");
+    assert_eq!(proxy.get_property::<u32>("Total").await.unwrap(), 3);
+    let props = zbus::fdo::PropertiesProxy::builder(&client_conn)
+        .destination(&dest)
+        .unwrap()
+        .path("/org/zbus/Calculator")
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let mut changed = props.receive_properties_changed().await.unwrap();
+    let iface_name = zbus::names::InterfaceName::from_static_str("org.zbus.Calculator").unwrap();
+    props
+        .set(iface_name.clone(), "Total", 7u32.into())
+        .await
+        .unwrap();
+    let changed = changed.next().await.unwrap();
+    let args = changed.args().unwrap();
+    assert_eq!(args.changed_properties()["Total"], Value::from(7u32));
+    assert_eq!(proxy.get_property::<u32>("Total").await.unwrap(), 7);
+    assert!(matches!(
+        props.set(iface_name.clone(), "Name", "other".into()).await,
+        Err(zbus::fdo::Error::PropertyReadOnly(_))
+    ));
+    assert!(matches!(
+        props.set(iface_name.clone(), "Total", "7".into()).await,
+        Err(zbus::fdo::Error::InvalidArgs(_))
+    ));
+    // Variant properties take values of any type.
+    assert_eq!(proxy.get_property::<u8>("Label").await.unwrap(), 0);
+    props
+        .set(iface_name.clone(), "Label", "calc".into())
+        .await
+        .unwrap();
+    assert_eq!(proxy.get_property::<String>("Label").await.unwrap(), "calc");
+    let all = props.get_all(iface_name).await.unwrap();
+    assert_eq!(all.len(), 3);
+
+    let xml = zbus::fdo::IntrospectableProxy::builder(&client_conn)
+        .destination(&dest)
+        .unwrap()
+        .path("/org/zbus/Calculator")
+        .unwrap()
+        .build()
+        .await
+        .unwrap()
+        .introspect()
+        .await
+        .unwrap();
+    let node = zbus_xml::Node::from_reader(xml.as_bytes()).unwrap();
+    let iface = node
+        .interfaces()
+        .iter()
+        .find(|i| i.name() == "org.zbus.Calculator")
+        .unwrap();
+    assert_eq!(iface.methods()[0].args().len(), 3);
+    assert_eq!(iface.signals().len(), 1);
+    assert_eq!(iface.properties().len(), 3);
+
+    assert!(service_conn
+        .object_server()
+        .remove_dynamic("/org/zbus/Calculator", "org.zbus.Calculator")
+        .await
+        .unwrap());
+    assert!(proxy.call::<_, _, u32>("Add", &(1u32, 2u32)).await.is_err());
+}
+
+struct Player {
+    title: String,
+    position: u64,
+}
+
+#[interface(name = "org.zbus.Player")]
+impl Player {
+    #[zbus(property)]
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[zbus(property)]
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+struct Meter(u32);
+
+#[interface(name = "org.zbus.Meter", properties_changed_window = "100ms")]
+impl Meter {
+    #[zbus(property)]
+    fn level(&self) -> u32 {
+        self.0
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn batched_properties_changed() {
+    block_on(batched_properties_changed_());
+}
+
+#[instrument]
+async fn batched_properties_changed_() {
+    let player = Player {
+        title: "Waterloo".to_string(),
+        position: 42,
+    };
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Player", player)
+        .unwrap()
+        .serve_at("/org/zbus/Meter", Meter(0))
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let dest = service_conn.unique_name().unwrap().to_owned();
+    let client_conn = Connection::session().await.unwrap();
+    let props = |path| {
+        zbus::fdo::PropertiesProxy::builder(&client_conn)
+            .destination(dest.clone())
+            .unwrap()
+            .path(path)
+            .unwrap()
+            .build()
+    };
+
+    let player_props = props("/org/zbus/Player").await.unwrap();
+    let mut changed = player_props.receive_properties_changed().await.unwrap();
+    let player_ref = service_conn
+        .object_server()
+        .interface::<_, Player>("/org/zbus/Player")
+        .await
+        .unwrap();
+    {
+        let mut player = player_ref.get_mut().await;
+        player.title = "Dancing Queen".to_string();
+        player.position = 0;
+        let batch = player_ref.batch();
+        player.title_changed(&batch).await.unwrap();
+        player.position_changed(&batch).await.unwrap();
+        player.title_changed(&batch).await.unwrap();
+        batch.commit().await.unwrap();
+        player.position = 1;
+        player
+            .position_changed(player_ref.signal_emitter())
+            .await
+            .unwrap();
+    }
+    let signal = changed.next().await.unwrap();
+    let args = signal.args().unwrap();
+    assert_eq!(args.interface_name(), "org.zbus.Player");
+    let changed_props = args.changed_properties();
+    assert_eq!(changed_props.len(), 2);
+    assert_eq!(changed_props["Title"], Value::from("Dancing Queen"));
+    assert_eq!(changed_props["Position"], Value::from(0u64));
+    let signal = changed.next().await.unwrap();
+    let args = signal.args().unwrap();
+    assert_eq!(args.changed_properties().len(), 1);
+    assert_eq!(args.changed_properties()["Position"], Value::from(1u64));
+
+    let meter_props = props("/org/zbus/Meter").await.unwrap();
+    let mut changed = meter_props.receive_properties_changed().await.unwrap();
+    let meter_ref = service_conn
+        .object_server()
+        .interface::<_, Meter>("/org/zbus/Meter")
+        .await
+        .unwrap();
+    for level in 1..=3 {
+        let mut meter = meter_ref.get_mut().await;
+        meter.0 = level;
+        meter
+            .level_changed(meter_ref.signal_emitter())
+            .await
+            .unwrap();
+    }
+    // Only the last value makes it, once the window elapsed.
+    let signal = changed.next().await.unwrap();
+    let args = signal.args().unwrap();
+    assert_eq!(args.changed_properties()["Level"], Value::from(3u32));
+}
+
+struct Thermostat {
+    target: u32,
+    mode: String,
+    serial: u32,
+}
+
+#[interface(name = "org.zbus.Thermostat", track_properties = true)]
+impl Thermostat {
+    fn bump(&mut self) {
+        self.target += 1;
+        self.mode = "heat".to_string();
+        self.serial += 1;
+    }
+
+    fn noop(&self) {}
+
+    #[zbus(property)]
+    fn target(&self) -> u32 {
+        self.target
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn serial(&self) -> u32 {
+        self.serial
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn tracked_properties() {
+    block_on(tracked_properties_());
+}
+
+#[instrument]
+async fn tracked_properties_() {
+    let thermostat = Thermostat {
+        target: 20,
+        mode: "off".to_string(),
+        serial: 1,
+    };
+    let service_conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/zbus/Thermostat", thermostat)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let dest = service_conn.unique_name().unwrap().to_owned();
+    let client_conn = Connection::session().await.unwrap();
+    let props = zbus::fdo::PropertiesProxy::builder(&client_conn)
+        .destination(dest.clone())
+        .unwrap()
+        .path("/org/zbus/Thermostat")
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let mut changed = props.receive_properties_changed().await.unwrap();
+    let proxy: zbus::Proxy<'_> = zbus::proxy::Builder::new(&client_conn)
+        .destination(dest)
+        .unwrap()
+        .path("/org/zbus/Thermostat")
+        .unwrap()
+        .interface("org.zbus.Thermostat")
+        .unwrap()
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+        .unwrap();
+
+    // Nothing is emitted for a call that changes nothing.
+    proxy.call::<_, _, ()>("Noop", &()).await.unwrap();
+    proxy.call::<_, _, ()>("Bump", &()).await.unwrap();
+    let signal = changed.next().await.unwrap();
+    let args = signal.args().unwrap();
+    assert_eq!(args.interface_name(), "org.zbus.Thermostat");
+    assert_eq!(args.changed_properties().len(), 1);
+    assert_eq!(args.changed_properties()["Target"], Value::from(21u32));
+    assert_eq!(**args.invalidated_properties(), ["Mode"]);
+
+    let iface_ref = service_conn
+        .object_server()
+        .interface::<_, Thermostat>("/org/zbus/Thermostat")
+        .await
+        .unwrap();
+    iface_ref.get_mut().await.target = 18;
+    let signal = changed.next().await.unwrap();
+    let args = signal.args().unwrap();
+    assert_eq!(args.changed_properties().len(), 1);
+    assert_eq!(args.changed_properties()["Target"], Value::from(18u32));
+    assert!(args.invalidated_properties().is_empty());
+}
@@ -149,6 +149,15 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
                                 #error_converts
                                 _ => Self::#ident(value),
                             }
+                        } else if let #zbus::Error::Timeout = &value {
+                            // Reply timeouts are reported as `NoReply`, if the error type has it.
+                            let desc = &::std::option::Option::Some(
+                                ::std::string::ToString::to_string(&value),
+                            );
+                            match "org.freedesktop.DBus.Error.NoReply" {
+                                #error_converts
+                                _ => Self::#ident(value),
+                            }
                         } else {
                             Self::#ident(value)
                         }
@@ -25,6 +25,14 @@ def_attrs! {
         name str,
         spawn bool,
         introspection_docs bool,
+        properties_changed_window str,
+        track_properties bool,
+        annotation [{
+            pub ImplAnnotationAttributes("annotation") {
+                name str,
+                value str
+            }
+        }],
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -50,6 +58,12 @@ def_attrs! {
             }
         },
         out_args [str],
+        annotation [{
+            pub MethodAnnotationAttributes("annotation") {
+                name str,
+                value str
+            }
+        }],
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -69,7 +83,13 @@ def_attrs! {
         connection none,
         header none,
         signal_context none,
-        signal_emitter none
+        signal_emitter none,
+        annotation [{
+            pub ArgAnnotationAttributes("annotation") {
+                name str,
+                value str
+            }
+        }]
     };
 }
 
@@ -80,6 +100,7 @@ struct Property<'a> {
     emits_changed_signal: PropertyEmitsChangedSignal,
     ty: Option<&'a Type>,
     doc_comments: TokenStream,
+    annotations: Vec<Annotation>,
 }
 
 impl Property<'_> {
@@ -90,6 +111,7 @@ impl Property<'_> {
             emits_changed_signal: PropertyEmitsChangedSignal::True,
             ty: None,
             doc_comments: quote!(),
+            annotations: vec![],
         }
     }
 }
@@ -127,6 +149,8 @@ struct MethodInfo {
     typed_inputs: Vec<PatType>,
     /// The method arguments' introspection
     intro_args: TokenStream,
+    /// The annotations of the method
+    annotations: Vec<Annotation>,
     /// Whether the output type is a Result
     is_result_output: bool,
     /// Code block to deserialize arguments from zbus message
@@ -241,8 +265,18 @@ impl MethodInfo {
             None
         };
 
+        let mut annotations = attrs
+            .annotation
+            .iter()
+            .map(|a| Annotation::new(a.name.as_ref(), a.value.as_ref(), method.span()))
+            .collect::<syn::Result<Vec<_>>>()?;
+        let has_deprecated = annotations.iter().any(|a| a.name == DEPRECATED_ANNOTATION);
+        if is_deprecated(&method.attrs) && !has_deprecated {
+            annotations.push(Annotation::deprecated());
+        }
+
         let mut intro_args = quote!();
-        intro_args.extend(introspect_input_args(&typed_inputs, is_signal, cfg_attrs));
+        intro_args.extend(introspect_input_args(&typed_inputs, is_signal, cfg_attrs)?);
         let is_result_output = introspect_add_output_args(
             &mut intro_args,
             output,
@@ -283,6 +317,7 @@ impl MethodInfo {
             typed_inputs,
             signal_emitter_arg,
             intro_args,
+            annotations,
             is_result_output,
             args_from_msg,
             args_names,
@@ -305,6 +340,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
     let mut set_mut_dispatch = quote!();
     let mut get_dispatch = quote!();
     let mut get_all = quote!();
+    let mut property_snapshot = quote!();
     let mut call_dispatch = quote!();
     let mut call_mut_dispatch = quote!();
     let mut introspect = quote!();
@@ -326,6 +362,13 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
     };
 
     let impl_attrs = ImplAttributes::parse_nested_metas(args)?;
+    let iface_annotations = impl_attrs
+        .annotation
+        .iter()
+        .map(|a| {
+            Annotation::new(a.name.as_ref(), a.value.as_ref(), input.span()).map(|a| a.introspect())
+        })
+        .collect::<syn::Result<TokenStream>>()?;
     let iface_name = {
         match (impl_attrs.name, impl_attrs.interface) {
             // Ensure the interface name is valid.
@@ -346,6 +389,22 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         .proxy
         .map(|p| Proxy::new(ty, &iface_name, p, &zbus));
     let introspect_docs = impl_attrs.introspection_docs.unwrap_or(true);
+    let properties_changed_window = match impl_attrs.properties_changed_window {
+        Some(window) => {
+            let millis = parse_window_millis(&window).ok_or_else(|| {
+                Error::new(
+                    input.span(),
+                    "`properties_changed_window` must be a duration in `ms` or `s` (e.g \"50ms\")",
+                )
+            })?;
+
+            quote!(::std::option::Option::Some(
+                ::std::time::Duration::from_millis(#millis)
+            ))
+        }
+        None => quote!(::std::option::Option::None),
+    };
+    let track_properties = impl_attrs.track_properties.unwrap_or(false);
 
     // Store parsed information about each method
     let mut methods = vec![];
@@ -442,6 +501,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
             typed_inputs,
             signal_emitter_arg,
             intro_args,
+            annotations,
             is_result_output,
             args_from_msg,
             args_names,
@@ -463,7 +523,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         match method_type {
             MethodType::Signal => {
                 introspect.extend(doc_comments);
-                introspect.extend(introspect_signal(&member_name, &intro_args));
+                introspect.extend(introspect_signal(&member_name, &annotations, &intro_args));
                 let signal_emitter = signal_emitter_arg.unwrap().pat;
 
                 method.block = parse_quote!({
@@ -478,8 +538,19 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                 method_clone.sig.asyncness = Some(Async(method_clone.span()));
                 *method_clone.sig.inputs.first_mut().unwrap() = parse_quote!(&self);
                 method_clone.vis = Visibility::Inherited;
+                // Deprecation is declared on the trait methods, it has no effect on their impls.
+                let deprecated_attrs: Vec<_> = method_clone
+                    .attrs
+                    .iter()
+                    .filter(|a| a.path().is_ident("deprecated"))
+                    .cloned()
+                    .collect();
+                method_clone
+                    .attrs
+                    .retain(|a| !a.path().is_ident("deprecated"));
                 let sig = &method_clone.sig;
                 signals_trait_methods.extend(quote! {
+                    #(#deprecated_attrs)*
                     #sig;
                 });
                 method_clone.block = parse_quote!({
@@ -517,6 +588,11 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                 let prop_invalidate_method_name = format_ident!("{sk_member_name}_invalidate");
 
                 p.doc_comments.extend(doc_comments);
+                for annotation in annotations {
+                    if !p.annotations.contains(&annotation) {
+                        p.annotations.push(annotation);
+                    }
+                }
                 if has_inputs {
                     p.write = true;
 
@@ -718,6 +794,43 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
 
                     get_all.extend(q);
 
+                    let invalidates = match p.emits_changed_signal {
+                        PropertyEmitsChangedSignal::True => Some(false),
+                        PropertyEmitsChangedSignal::Invalidates => Some(true),
+                        _ => None,
+                    };
+                    if let Some(invalidates) = invalidates {
+                        let insert = quote!(
+                            __zbus__snapshot.insert(
+                                #member_name,
+                                <#zbus::zvariant::Value as ::std::convert::From<_>>::from(value),
+                                #invalidates,
+                            );
+                        );
+                        let q = if is_fallible_property {
+                            quote!(
+                                #(#cfg_attrs)*
+                                {
+                                    #args_from_msg
+                                    if let Ok(value) = self.#ident(#args_names)#method_await {
+                                        #insert
+                                    }
+                                }
+                            )
+                        } else {
+                            quote!(
+                                #(#cfg_attrs)*
+                                {
+                                    #args_from_msg
+                                    let value = self.#ident(#args_names)#method_await;
+                                    #insert
+                                }
+                            )
+                        };
+
+                        property_snapshot.extend(q);
+                    }
+
                     let prop_value_handled = if is_fallible_property {
                         quote!(self.#ident(#args_names)#method_await?)
                     } else {
@@ -737,11 +850,11 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                                 let mut changed = ::std::collections::HashMap::new();
                                 let value = <#zbus::zvariant::Value as ::std::convert::From<_>>::from(#prop_value_handled);
                                 changed.insert(#member_name, value);
-                                #zbus::fdo::Properties::properties_changed(
-                                    __zbus__signal_emitter,
+                                __zbus__signal_emitter.properties_changed_within(
                                     #zbus::names::InterfaceName::from_static_str_unchecked(#iface_name),
                                     changed,
-                                    ::std::borrow::Cow::Borrowed(&[]),
+                                    &[],
+                                    #properties_changed_window,
                                 ).await
                             }
                         );
@@ -755,11 +868,11 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                                 &self,
                                 __zbus__signal_emitter: &#zbus::object_server::SignalEmitter<'_>,
                             ) -> #zbus::Result<()> {
-                                #zbus::fdo::Properties::properties_changed(
-                                    __zbus__signal_emitter,
+                                __zbus__signal_emitter.properties_changed_within(
                                     #zbus::names::InterfaceName::from_static_str_unchecked(#iface_name),
                                     ::std::collections::HashMap::new(),
-                                    ::std::borrow::Cow::Borrowed(&[#member_name]),
+                                    &[#member_name],
+                                    #properties_changed_window,
                                 ).await
                             }
                         );
@@ -770,14 +883,40 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
             }
             MethodType::Other => {
                 introspect.extend(doc_comments);
-                introspect.extend(introspect_method(&member_name, &intro_args));
+                introspect.extend(introspect_method(&member_name, &annotations, &intro_args));
+
+                let (track_start, track_finish) = if track_properties {
+                    (
+                        quote! {
+                            let __zbus__tracker = match __zbus__message.header().path() {
+                                ::std::option::Option::Some(path) => {
+                                    let emitter = #zbus::object_server::SignalEmitter::from_parts(
+                                        ::std::clone::Clone::clone(__zbus__connection),
+                                        path.to_owned(),
+                                    );
+                                    #zbus::object_server::PropertyTracker::start(&*self, emitter).await
+                                }
+                                ::std::option::Option::None => ::std::option::Option::None,
+                            };
+                        },
+                        quote! {
+                            if let ::std::option::Option::Some(tracker) = __zbus__tracker {
+                                tracker.finish(&*self).await;
+                            }
+                        },
+                    )
+                } else {
+                    (quote!(), quote!())
+                };
 
                 let m = quote! {
                     #(#cfg_attrs)*
                     #member_name => {
                         let future = async move {
                             #args_from_msg
+                            #track_start
                             let reply = self.#ident(#args_names)#method_await;
+                            #track_finish
                             let hdr = __zbus__message.header();
                             if hdr.primary().flags().contains(zbus::message::Flags::NoReplyExpected) {
                                 Ok(())
@@ -817,6 +956,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         quote!()
     } else {
         quote! {
+            #[allow(deprecated)]
             impl #generics #self_ty
             #where_clause
             {
@@ -852,6 +992,26 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         }
     };
 
+    let property_snapshot_impl = if track_properties {
+        quote! {
+            async fn property_snapshot(
+                &self,
+                __zbus__signal_emitter: &#zbus::object_server::SignalEmitter<'_>,
+            ) -> ::std::option::Option<#zbus::object_server::PropertySnapshot> {
+                let __zbus__header = ::std::option::Option::None::<&#zbus::message::Header<'_>>;
+                let __zbus__connection = __zbus__signal_emitter.connection();
+                let __zbus__object_server = __zbus__connection.object_server();
+                let mut __zbus__snapshot = #zbus::object_server::PropertySnapshot::new(
+                    #zbus::names::InterfaceName::from_static_str_unchecked(#iface_name),
+                );
+                #property_snapshot
+                ::std::option::Option::Some(__zbus__snapshot)
+            }
+        }
+    } else {
+        quote!()
+    };
+
     let proxy = proxy.map(|proxy| proxy.gen()).transpose()?;
     let introspect_format_str = format!("{}<interface name=\"{iface_name}\">", "{:indent$}");
 
@@ -862,6 +1022,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
 
         #signals_trait_and_impl
 
+        #[allow(deprecated)]
         #[#zbus::export::async_trait::async_trait]
         impl #generics #zbus::object_server::Interface for #self_ty
         #where_clause
@@ -906,6 +1067,8 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                 Ok(props)
             }
 
+            #property_snapshot_impl
+
             fn set<'call>(
                 &'call self,
                 __zbus__property_name: &'call str,
@@ -973,6 +1136,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                     use #zbus::zvariant::Type;
 
                     let level = level + 2;
+                    #iface_annotations
                     #introspect
                 }
                 ::std::writeln!(writer, r#"{:indent$}</interface>"#, "", indent = level).unwrap();
@@ -1005,6 +1169,7 @@ fn get_args_from_inputs(
                 header,
                 signal_emitter,
                 signal_context,
+                ..
             } = ArgAttributes::parse(&input.attrs)?;
 
             if object_server {
@@ -1133,54 +1298,83 @@ fn clear_input_arg_attrs(inputs: &mut Punctuated<FnArg, Token![,]>) {
     }
 }
 
-fn introspect_signal(name: &str, args: &TokenStream) -> TokenStream {
+fn introspect_signal(name: &str, annotations: &[Annotation], args: &TokenStream) -> TokenStream {
     let format_str = format!("{}<signal name=\"{name}\">", "{:indent$}");
+    let annotations = annotations.iter().map(Annotation::introspect);
     quote!(
         ::std::writeln!(writer, #format_str, "", indent = level).unwrap();
         {
             let level = level + 2;
+            #(#annotations)*
             #args
         }
         ::std::writeln!(writer, "{:indent$}</signal>", "", indent = level).unwrap();
     )
 }
 
-fn introspect_method(name: &str, args: &TokenStream) -> TokenStream {
+fn introspect_method(name: &str, annotations: &[Annotation], args: &TokenStream) -> TokenStream {
     let format_str = format!("{}<method name=\"{name}\">", "{:indent$}");
+    let annotations = annotations.iter().map(Annotation::introspect);
     quote!(
         ::std::writeln!(writer, #format_str, "", indent = level).unwrap();
         {
             let level = level + 2;
+            #(#annotations)*
             #args
         }
         ::std::writeln!(writer, "{:indent$}</method>", "", indent = level).unwrap();
     )
 }
 
-fn introspect_input_args<'i>(
-    inputs: &'i [PatType],
+fn introspect_input_args(
+    inputs: &[PatType],
     is_signal: bool,
-    cfg_attrs: &'i [&'i syn::Attribute],
-) -> impl Iterator<Item = TokenStream> + 'i {
-    inputs
-        .iter()
-        .filter_map(move |pat_type @ PatType { ty, attrs, .. }| {
-            if is_special_arg(attrs) {
-                return None;
-            }
+    cfg_attrs: &[&syn::Attribute],
+) -> syn::Result<TokenStream> {
+    let mut args = quote!();
+    for pat_type @ PatType { ty, attrs, .. } in inputs {
+        if is_special_arg(attrs) {
+            continue;
+        }
 
-            let ident = pat_ident(pat_type).unwrap();
-            let arg_name = quote!(#ident).to_string();
-            let dir = if is_signal { "" } else { " direction=\"in\"" };
+        let ident = pat_ident(pat_type).unwrap();
+        let arg_name = quote!(#ident).to_string();
+        let dir = if is_signal { "" } else { " direction=\"in\"" };
+        let annotations = ArgAttributes::parse(attrs)?
+            .annotation
+            .iter()
+            .map(|a| Annotation::new(a.name.as_ref(), a.value.as_ref(), pat_type.span()))
+            .collect::<syn::Result<Vec<_>>>()?;
+        if annotations.is_empty() {
             let format_str = format!(
                 "{}<arg name=\"{arg_name}\" type=\"{}\"{dir}/>",
                 "{:indent$}", "{}",
             );
-            Some(quote!(
+            args.extend(quote!(
                 #(#cfg_attrs)*
                 ::std::writeln!(writer, #format_str, "", <#ty>::SIGNATURE, indent = level).unwrap();
-            ))
-        })
+            ));
+        } else {
+            let format_str = format!(
+                "{}<arg name=\"{arg_name}\" type=\"{}\"{dir}>",
+                "{:indent$}", "{}",
+            );
+            let annotations = annotations.iter().map(Annotation::introspect);
+            args.extend(quote!(
+                #(#cfg_attrs)*
+                {
+                    ::std::writeln!(writer, #format_str, "", <#ty>::SIGNATURE, indent = level).unwrap();
+                    {
+                        let level = level + 2;
+                        #(#annotations)*
+                    }
+                    ::std::writeln!(writer, "{:indent$}</arg>", "", indent = level).unwrap();
+                }
+            ));
+        }
+    }
+
+    Ok(args)
 }
 
 fn count_regular_args(inputs: &[PatType]) -> usize {
@@ -1322,6 +1516,18 @@ fn get_return_type(output: &ReturnType) -> syn::Result<&Type> {
     }
 }
 
+/// Parse a duration such as "50ms" or "1s" into milliseconds.
+fn parse_window_millis(window: &str) -> Option<u64> {
+    match window.strip_suffix("ms") {
+        Some(millis) => millis.parse().ok(),
+        None => window
+            .strip_suffix('s')?
+            .parse::<u64>()
+            .ok()?
+            .checked_mul(1000),
+    }
+}
+
 fn introspect_properties(
     introspection: &mut TokenStream,
     properties: BTreeMap<String, Property<'_>>,
@@ -1344,7 +1550,15 @@ fn introspect_properties(
         })?;
 
         let doc_comments = prop.doc_comments;
-        if prop.emits_changed_signal == PropertyEmitsChangedSignal::True {
+        let mut annotations = vec![];
+        if prop.emits_changed_signal != PropertyEmitsChangedSignal::True {
+            annotations.push(Annotation {
+                name: "org.freedesktop.DBus.Property.EmitsChangedSignal".to_string(),
+                value: prop.emits_changed_signal.to_string(),
+            });
+        }
+        annotations.extend(prop.annotations);
+        if annotations.is_empty() {
             let format_str = format!(
                 "{}<property name=\"{name}\" type=\"{}\" access=\"{access}\"/>",
                 "{:indent$}", "{}",
@@ -1354,21 +1568,19 @@ fn introspect_properties(
                 ::std::writeln!(writer, #format_str, "", <#ty>::SIGNATURE, indent = level).unwrap();
             ));
         } else {
-            let emits_changed_signal = prop.emits_changed_signal.to_string();
-            let annot_name = "org.freedesktop.DBus.Property.EmitsChangedSignal";
             let format_str = format!(
-                "{}<property name=\"{name}\" type=\"{}\" access=\"{access}\">\n\
-                    {}<annotation name=\"{annot_name}\" value=\"{emits_changed_signal}\"/>\n\
-                {}</property>",
-                "{:indent$}", "{}", "{:annot_indent$}", "{:indent$}",
+                "{}<property name=\"{name}\" type=\"{}\" access=\"{access}\">",
+                "{:indent$}", "{}",
             );
+            let annotations = annotations.iter().map(Annotation::introspect);
             introspection.extend(quote!(
                 #doc_comments
-                ::std::writeln!(
-                    writer,
-                    #format_str,
-                    "", <#ty>::SIGNATURE, "", "", indent = level, annot_indent = level + 2,
-                ).unwrap();
+                ::std::writeln!(writer, #format_str, "", <#ty>::SIGNATURE, indent = level).unwrap();
+                {
+                    let level = level + 2;
+                    #(#annotations)*
+                }
+                ::std::writeln!(writer, "{:indent$}</property>", "", indent = level).unwrap();
             ));
         }
     }
@@ -1507,7 +1719,7 @@ impl Proxy {
         let member_name = method_info.member_name;
         let mut proxy_method_attrs = quote! { name = #member_name, };
         proxy_method_attrs.extend(match method_info.method_type {
-            MethodType::Signal => quote!(signal),
+            MethodType::Signal => quote!(signal,),
             MethodType::Property(_) => {
                 let emits_changed_signal = properties
                     .get(&member_name)
@@ -1516,7 +1728,7 @@ impl Proxy {
                     .to_string();
                 let emits_changed_signal = quote! { emits_changed_signal = #emits_changed_signal };
 
-                quote! { property(#emits_changed_signal) }
+                quote! { property(#emits_changed_signal), }
             }
             MethodType::Other => quote!(),
         });
@@ -1540,6 +1752,9 @@ impl Proxy {
                 proxy_method_attrs.extend(quote! { allow_interactive_auth, });
             }
         }
+        for Annotation { name, value } in &method_info.annotations {
+            proxy_method_attrs.extend(quote! { annotation(name = #name, value = #value), });
+        }
         let cfg_attrs = method_info.cfg_attrs;
         let doc_attrs = method_info.doc_attrs;
         self.methods.extend(quote! {
@@ -53,6 +53,10 @@ mod utils;
 ///   attribute nor one of the default values are specified. Please make sure to explicitly set
 ///   either this attribute or the default values, according to your needs.
 ///
+/// * `annotation` - a D-Bus annotation of the interface, e.g
+///   `annotation(name = "org.gtk.GDBus.C.Name", value = "Foo")`. It can be repeated. On the client
+///   side, annotations are only informative.
+///
 /// Each trait method will be expanded to call to the associated D-Bus remote interface.
 ///
 /// Trait methods accept `proxy` attributes:
@@ -98,6 +102,11 @@ mod utils;
 ///
 ///   NB: Any doc comments provided shall be appended to the ones added by the macro.
 ///
+/// * `annotation` - a D-Bus annotation of the member, which can be repeated. The
+///   `org.freedesktop.DBus.Deprecated` annotation marks the generated methods as `#[deprecated]`,
+///   and `org.freedesktop.DBus.Method.NoReply` is equivalent to `no_reply` on methods. The method
+///   arguments also accept `annotation` attributes, which are only informative.
+///
 /// # Signals
 ///
 /// For each signal method declared, this macro will provide a method, named `receive_<method_name>`
@@ -235,6 +244,23 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   (Default: `true`). If your interface is well-known or well-documented, you may want to set
 ///   this to `false` to reduce the the size of your binary and D-Bus traffic.
 ///
+/// * `properties_changed_window` - a duration in milliseconds (e.g `"50ms"`) or seconds (e.g
+///   `"1s"`). If specified, the property changes signaled through the generated `_changed` and
+///   `_invalidate` methods are not emitted right away. Instead, all the changes of the interface
+///   signaled within this time window are merged into a single "PropertiesChanged" signal, emitted
+///   at its end.
+///
+/// * `track_properties` - whether property changes are tracked automatically (Default: `false`).
+///   If `true`, the values of the properties are compared before and after each method call, and
+///   while the interface is mutably borrowed through `InterfaceRef::get_mut`. A "PropertiesChanged"
+///   signal is then emitted for the properties that changed, according to their
+///   `emits_changed_signal` attribute. This spares calls to the generated `_changed` methods, at
+///   the cost of calling all the property getters twice for each method call. The property values
+///   are compared once the `get_mut` guard is dropped, so the signal is emitted asynchronously.
+///
+/// * `annotation` - an annotation to add to the introspection data of the interface, e.g
+///   `annotation(name = "org.gtk.GDBus.C.Name", value = "Foo")`. It can be repeated.
+///
 /// The methods accepts the `interface` attributes:
 ///
 /// * `name` - override the D-Bus name (pascal case form of the method by default)
@@ -274,6 +300,11 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   In such case, your method must return a tuple containing
 ///   your out arguments, in the same order as passed to `out_args`.
 ///
+/// * `annotation` - an annotation to add to the introspection data of the method, signal or
+///   property (e.g `annotation(name = "org.freedesktop.systemd1.Privileged", value = "true")`). It
+///   can be repeated. The annotations of a property's getter and setter are merged. Methods marked
+///   `#[deprecated]` get the `org.freedesktop.DBus.Deprecated` annotation automatically.
+///
 /// The `struct_return` attribute (from zbus 1.x) is no longer supported. If you want to return a
 /// single structure from a method, declare it to return a tuple containing either a named structure
 /// or a nested tuple.
@@ -289,6 +320,9 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// using this since it will force all interested peers to fetch the new value and hence result in
 /// excess traffic on the bus.
 ///
+/// To signal changes to several properties in a single "PropertiesChanged" signal, pass a
+/// `zbus::object_server::PropertiesBatch` to these methods instead of the usual `SignalEmitter`.
+///
 /// The method arguments support the following `zbus` attributes:
 ///
 /// * `object_server` - This marks the method argument to receive a reference to the
@@ -301,6 +335,8 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   external property access.
 /// * `signal_emitter` - This marks the method argument to receive a [`SignalEmitter`] instance,
 ///   which is needed for emitting signals the easy way.
+/// * `annotation` - an annotation to add to the introspection data of the argument. It can be
+///   repeated.
 ///
 /// # Example
 ///
@@ -1,4 +1,7 @@
-use crate::utils::{pat_ident, typed_arg, zbus_path, PropertyEmitsChangedSignal};
+use crate::utils::{
+    is_deprecated, pat_ident, typed_arg, zbus_path, Annotation, PropertyEmitsChangedSignal,
+    DEPRECATED_ANNOTATION, NO_REPLY_ANNOTATION,
+};
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
@@ -20,7 +23,13 @@ def_attrs! {
         async_name str,
         blocking_name str,
         gen_async bool,
-        gen_blocking bool
+        gen_blocking bool,
+        annotation [{
+            pub TraitAnnotationAttributes("annotation") {
+                name str,
+                value str
+            }
+        }]
     };
 
     // Keep this in sync with interface's proxy method attributes.
@@ -37,7 +46,22 @@ def_attrs! {
         blocking_object str,
         no_reply none,
         no_autostart none,
-        allow_interactive_auth none
+        allow_interactive_auth none,
+        annotation [{
+            pub MethodAnnotationAttributes("annotation") {
+                name str,
+                value str
+            }
+        }]
+    };
+
+    pub ArgAttributes("argument") {
+        annotation [{
+            pub ArgAnnotationAttributes("annotation") {
+                name str,
+                value str
+            }
+        }]
     };
 }
 
@@ -64,6 +88,10 @@ impl AsyncOpts {
 
 pub fn expand(args: Punctuated<Meta, Token![,]>, input: ItemTrait) -> Result<TokenStream, Error> {
     let attrs = TraitAttributes::parse_nested_metas(args)?;
+    // Interface annotations are only informative on the client side.
+    for a in &attrs.annotation {
+        Annotation::new(a.name.as_ref(), a.value.as_ref(), input.span())?;
+    }
 
     let iface_name = match (attrs.interface, attrs.name) {
         (Some(name), None) | (None, Some(name)) => Ok(Some(name)),
@@ -205,7 +233,8 @@ pub fn create_proxy(
 
     for i in input.items.iter() {
         if let syn::TraitItem::Fn(m) = i {
-            let method_attrs = MethodAttributes::parse(&m.attrs)?;
+            let mut method_attrs = MethodAttributes::parse(&m.attrs)?;
+            let m = &apply_annotations(m, &mut method_attrs)?;
             let property = method_attrs.property.as_ref();
 
             let method_name = m.sig.ident.to_string();
@@ -660,6 +689,42 @@ fn gen_proxy_method_call(
     }
 }
 
+/// Validate the annotations of a method and its arguments, applying the ones with a meaning on the
+/// client side.
+///
+/// `org.freedesktop.DBus.Deprecated` marks the generated methods as `#[deprecated]`, and
+/// `org.freedesktop.DBus.Method.NoReply` is equivalent to the `no_reply` attribute. The returned
+/// method is stripped of its arguments' `zbus` attributes.
+fn apply_annotations(
+    m: &TraitItemFn,
+    method_attrs: &mut MethodAttributes,
+) -> Result<TraitItemFn, Error> {
+    let annotations = method_attrs
+        .annotation
+        .iter()
+        .map(|a| Annotation::new(a.name.as_ref(), a.value.as_ref(), m.span()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut m = m.clone();
+    if Annotation::is_set(&annotations, DEPRECATED_ANNOTATION) && !is_deprecated(&m.attrs) {
+        m.attrs.push(parse_quote!(#[deprecated]));
+    }
+    let is_method_call = method_attrs.property.is_none() && !method_attrs.signal;
+    if is_method_call && Annotation::is_set(&annotations, NO_REPLY_ANNOTATION) {
+        method_attrs.no_reply = true;
+    }
+
+    for input in &mut m.sig.inputs {
+        if let FnArg::Typed(t) = input {
+            for a in ArgAttributes::parse(&t.attrs)?.annotation {
+                Annotation::new(a.name.as_ref(), a.value.as_ref(), t.span())?;
+            }
+            t.attrs.retain(|attr| !attr.path().is_ident("zbus"));
+        }
+    }
+
+    Ok(m)
+}
+
 fn gen_proxy_property(
     property_name: &str,
     method_name: &str,
@@ -3,7 +3,7 @@ use std::fmt::Display;
 use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::{format_ident, quote};
-use syn::{Attribute, FnArg, Ident, Pat, PatIdent, PatType};
+use syn::{Attribute, Error, FnArg, Ident, Pat, PatIdent, PatType};
 
 pub fn zbus_path() -> TokenStream {
     if let Ok(FoundCrate::Name(name)) = crate_name("zbus") {
@@ -54,6 +54,87 @@ pub fn is_blank(s: &str) -> bool {
     s.trim().is_empty()
 }
 
+/// Standard annotation `org.freedesktop.DBus.Deprecated`.
+pub const DEPRECATED_ANNOTATION: &str = "org.freedesktop.DBus.Deprecated";
+
+/// Standard annotation `org.freedesktop.DBus.Method.NoReply`.
+pub const NO_REPLY_ANNOTATION: &str = "org.freedesktop.DBus.Method.NoReply";
+
+/// An annotation, from a `#[zbus(annotation(name = "...", value = "..."))]` attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub name: String,
+    pub value: String,
+}
+
+impl Annotation {
+    pub fn new(name: Option<&String>, value: Option<&String>, span: Span) -> syn::Result<Self> {
+        let (Some(name), Some(value)) = (name, value) else {
+            return Err(Error::new(
+                span,
+                "`annotation` requires both a `name` and a `value`",
+            ));
+        };
+        // Annotation names have the same format as interface names.
+        zbus_names::InterfaceName::try_from(name.as_str())
+            .map_err(|e| Error::new(span, format!("invalid annotation name `{name}`: {e}")))?;
+
+        Ok(Self {
+            name: name.clone(),
+            value: value.clone(),
+        })
+    }
+
+    /// The annotation that marks deprecated members.
+    pub fn deprecated() -> Self {
+        Self {
+            name: DEPRECATED_ANNOTATION.to_string(),
+            value: "true".to_string(),
+        }
+    }
+
+    /// Whether `annotations` contain `name`, with the value `true`.
+    pub fn is_set(annotations: &[Self], name: &str) -> bool {
+        annotations
+            .iter()
+            .any(|a| a.name == name && a.value == "true")
+    }
+
+    /// The code writing the annotation to the introspection `writer`, at `level`.
+    pub fn introspect(&self) -> TokenStream {
+        let name = xml_escape(&self.name);
+        let value = xml_escape(&self.value);
+
+        quote!(
+            ::std::writeln!(
+                writer,
+                "{:indent$}<annotation name=\"{}\" value=\"{}\"/>",
+                "", #name, #value, indent = level,
+            ).unwrap();
+        )
+    }
+}
+
+/// Whether the item has a `#[deprecated]` attribute.
+pub fn is_deprecated(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| a.path().is_ident("deprecated"))
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
 /// Standard annotation `org.freedesktop.DBus.Property.EmitsChangedSignal`.
 ///
 /// See <https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format>.
@@ -291,6 +291,113 @@ fn test_interface() {
     }
 }
 
+#[test]
+fn test_interface_annotations() {
+    use zbus::object_server::Interface;
+
+    struct Annotated;
+
+    #[interface(
+        name = "org.freedesktop.zbus.Annotated",
+        annotation(name = "org.gtk.GDBus.C.Name", value = "Annotated"),
+        proxy(gen_blocking = false, default_path = "/org/freedesktop/zbus/Annotated")
+    )]
+    impl Annotated {
+        #[deprecated]
+        fn old(&self) {}
+
+        #[zbus(
+            annotation(name = "org.freedesktop.DBus.Method.NoReply", value = "true"),
+            annotation(name = "org.freedesktop.systemd1.Privileged", value = "true")
+        )]
+        fn fire(
+            &self,
+            #[zbus(annotation(name = "org.zbus.Unit", value = "<seconds>"))] delay: u32,
+        ) {
+            let _ = delay;
+        }
+
+        #[zbus(property, annotation(name = "org.zbus.Custom", value = "yes"))]
+        fn level(&self) -> u8 {
+            0
+        }
+
+        #[zbus(property(emits_changed_signal = "const"))]
+        #[deprecated]
+        fn limit(&self) -> u8 {
+            0
+        }
+
+        #[zbus(signal, annotation(name = "org.zbus.Custom", value = "no"))]
+        #[deprecated = "Use `Level` instead"]
+        async fn fired(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+    }
+
+    const EXPECTED_XML: &str = r#"<interface name="org.freedesktop.zbus.Annotated">
+  <annotation name="org.gtk.GDBus.C.Name" value="Annotated"/>
+  <method name="Old">
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+  </method>
+  <method name="Fire">
+    <annotation name="org.freedesktop.DBus.Method.NoReply" value="true"/>
+    <annotation name="org.freedesktop.systemd1.Privileged" value="true"/>
+    <arg name="delay" type="u" direction="in">
+      <annotation name="org.zbus.Unit" value="&lt;seconds&gt;"/>
+    </arg>
+  </method>
+  <signal name="Fired">
+    <annotation name="org.zbus.Custom" value="no"/>
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+  </signal>
+  <property name="Level" type="y" access="read">
+    <annotation name="org.zbus.Custom" value="yes"/>
+  </property>
+  <property name="Limit" type="y" access="read">
+    <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="const"/>
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+  </property>
+</interface>
+"#;
+    let mut xml = String::new();
+    Annotated.introspect_to_writer(&mut xml, 0);
+    assert_eq!(xml, EXPECTED_XML);
+
+    #[proxy(
+        interface = "org.freedesktop.zbus.Annotated",
+        annotation(name = "org.gtk.GDBus.C.Name", value = "Annotated"),
+        gen_blocking = false
+    )]
+    trait Client {
+        #[zbus(annotation(name = "org.freedesktop.DBus.Deprecated", value = "true"))]
+        fn old(&self) -> zbus::Result<()>;
+
+        #[zbus(annotation(name = "org.freedesktop.DBus.Method.NoReply", value = "true"))]
+        fn fire(
+            &self,
+            #[zbus(annotation(name = "org.zbus.Unit", value = "seconds"))] delay: u32,
+        ) -> zbus::Result<()>;
+    }
+
+    if false {
+        block_on(async {
+            // check compilation
+            let c = zbus::Connection::session().await.unwrap();
+            let client = ClientProxy::new(&c, "org.freedesktop.zbus", "/")
+                .await
+                .unwrap();
+            #[allow(deprecated)]
+            client.old().await.unwrap();
+            client.fire(1).await.unwrap();
+            let proxy = AnnotatedProxy::new(&c, "org.freedesktop.zbus")
+                .await
+                .unwrap();
+            #[allow(deprecated)]
+            proxy.limit().await.unwrap();
+            proxy.fire(1).await.unwrap();
+        });
+    }
+}
+
 mod signal_from_message {
     use super::*;
     use zbus::message::Message;
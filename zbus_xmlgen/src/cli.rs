@@ -13,6 +13,17 @@ pub struct Args {
     /// be saved to that file. Use '-' to print the output to stdout.
     #[clap(short, long, allow_hyphen_values = true, global = true)]
     pub output: Option<String>,
+
+    /// Generate `#[interface]` skeletons to implement the interfaces, instead of proxies to call
+    /// them.
+    #[clap(long, global = true)]
+    pub server: bool,
+
+    /// Also generate code for the interfaces of all the descendants of the object, walking its
+    /// tree of child nodes. Each interface is only generated once, with the path of the object
+    /// implementing it as default path, if there's only one such object.
+    #[clap(short, long, global = true)]
+    pub recursive: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -2,20 +2,22 @@ use snakecase::ascii::to_snakecase;
 use std::{
     error::Error,
     fmt::{Display, Formatter, Write},
+    fs::File,
+    path::Path,
     process::{Command, Stdio},
 };
 
-use zbus::{
-    names::BusName,
-    zvariant::{ObjectPath, Signature},
-};
-use zbus_xml::{Arg, ArgDirection, Interface};
+use zbus_names::BusName;
+use zbus_xml::{Annotation, Arg, ArgDirection, Interface, Node};
+use zvariant::{ObjectPath, Signature};
+
+mod structs;
+use structs::{Member, Structs, TypeDef};
 
 pub fn write_interfaces(
-    interfaces: &[Interface<'_>],
+    interfaces: &[(Interface<'_>, Option<ObjectPath<'_>>)],
     standard_interfaces: &[Interface<'_>],
     service: Option<BusName<'_>>,
-    path: Option<ObjectPath<'_>>,
     input_src: &str,
     cargo_bin_name: &str,
     cargo_bin_version: &str,
@@ -24,14 +26,17 @@ pub fn write_interfaces(
 
     write_doc_header(
         &mut unformatted,
-        interfaces,
+        &interfaces.iter().map(|(i, _)| i).collect::<Vec<_>>(),
         standard_interfaces,
         input_src,
         cargo_bin_name,
         cargo_bin_version,
+        false,
     )?;
 
-    for interface in interfaces {
+    let structs = Structs::new(interfaces.iter().map(|(i, _)| i));
+    structs.write_definitions(&mut unformatted)?;
+    for (interface, path) in interfaces {
         let gen = GenTrait {
             interface,
             service: service.as_ref(),
@@ -39,41 +44,156 @@ pub fn write_interfaces(
             format: false,
         };
 
-        write!(unformatted, "{}", gen)?;
+        gen.write_trait(&mut unformatted, &structs)?;
+    }
+
+    Ok(format_or_keep(unformatted))
+}
+
+/// Like [`write_interfaces`], but generates `#[interface]` skeletons to implement the interfaces,
+/// instead of proxies to call them.
+pub fn write_server_interfaces(
+    interfaces: &[Interface<'_>],
+    standard_interfaces: &[Interface<'_>],
+    input_src: &str,
+    cargo_bin_name: &str,
+    cargo_bin_version: &str,
+) -> Result<String, Box<dyn Error>> {
+    let mut unformatted = String::new();
+
+    write_doc_header(
+        &mut unformatted,
+        &interfaces.iter().collect::<Vec<_>>(),
+        standard_interfaces,
+        input_src,
+        cargo_bin_name,
+        cargo_bin_version,
+        true,
+    )?;
+
+    let structs = Structs::new(interfaces);
+    structs.write_definitions(&mut unformatted)?;
+    for interface in interfaces {
+        let gen = GenInterface {
+            interface,
+            format: false,
+        };
+
+        gen.write_skeleton(&mut unformatted, &structs)?;
+    }
+
+    Ok(format_or_keep(unformatted))
+}
+
+/// Generate the proxies of the interfaces described by `node`, to be included in a crate at build
+/// time.
+///
+/// Unlike [`write_interfaces`], the code is neither formatted nor preceded by a doc header, so it
+/// only depends on `node`. The standard `org.freedesktop.DBus.*` interfaces are skipped, since zbus
+/// already provides proxies for them in its `fdo` module.
+pub fn generate_proxies(node: &Node<'_>) -> String {
+    let interfaces: Vec<_> = node
+        .interfaces()
+        .iter()
+        .filter(|i| !i.name().starts_with("org.freedesktop.DBus"))
+        .collect();
+    let structs = Structs::new(interfaces.iter().copied());
+
+    // Writing to a `String` can't fail.
+    let mut code = "use zbus::proxy;\n".to_string();
+    structs.write_definitions(&mut code).unwrap();
+    for interface in interfaces {
+        let gen = GenTrait {
+            interface,
+            service: None,
+            path: None,
+            format: false,
+        };
+        gen.write_trait(&mut code, &structs).unwrap();
     }
 
-    let formatted = match format_generated_code(&unformatted) {
+    code
+}
+
+/// Generate the proxies of the interfaces described in the D-Bus XML file at `xml_path`, into the
+/// file at `out_path`.
+///
+/// This is meant to be called from a build script, so that the proxies always match the XML, e.g:
+///
+/// ```no_run
+/// // build.rs
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+///     zbus_xmlgen::generate_proxies_file("xml/org.example.Foo.xml", out_dir.join("foo.rs"))
+/// }
+/// ```
+///
+/// The generated file is then included in a module of its own:
+///
+/// ```ignore
+/// mod foo {
+///     include!(concat!(env!("OUT_DIR"), "/foo.rs"));
+/// }
+/// ```
+///
+/// Cargo is instructed to run the build script again whenever the XML file changes. See
+/// [`generate_proxies`] for the details of the generated code.
+///
+/// There is deliberately no `zbus::proxy_from_xml!("path/to/iface.xml")` macro doing the same at
+/// compile time. It would have to live in `zbus_macros`, which zbus depends on, and make it depend
+/// on this crate, whose command line tool and tests depend on zbus in turn. That would create a
+/// cycle between the packages. A build script doesn't have that problem and is just as
+/// deterministic.
+pub fn generate_proxies_file(
+    xml_path: impl AsRef<Path>,
+    out_path: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+    let xml_path = xml_path.as_ref();
+    println!("cargo:rerun-if-changed={}", xml_path.display());
+
+    let node = Node::from_reader(File::open(xml_path)?)?;
+    std::fs::write(out_path, generate_proxies(&node))?;
+
+    Ok(())
+}
+
+fn format_or_keep(unformatted: String) -> String {
+    match format_generated_code(&unformatted) {
         Ok(formatted) => formatted,
         Err(e) => {
             eprintln!("Failed to format generated code: {}", e);
             unformatted
         }
-    };
-
-    Ok(formatted)
+    }
 }
 
 /// Write a doc header, listing the included Interfaces and how the
 /// code was generated.
 fn write_doc_header<W: std::fmt::Write>(
     w: &mut W,
-    interfaces: &[Interface<'_>],
+    interfaces: &[&Interface<'_>],
     standard_interfaces: &[Interface<'_>],
     input_src: &str,
     cargo_bin_name: &str,
     cargo_bin_version: &str,
+    server: bool,
 ) -> std::fmt::Result {
+    let (kind, kinds) = if server {
+        ("skeleton", "skeletons")
+    } else {
+        ("proxy", "proxies")
+    };
     if let Some((first_iface, following_ifaces)) = interfaces.split_first() {
         if following_ifaces.is_empty() {
             writeln!(
                 w,
-                "//! # D-Bus interface proxy for: `{}`",
+                "//! # D-Bus interface {kind} for: `{}`",
                 first_iface.name()
             )?;
         } else {
             write!(
                 w,
-                "//! # D-Bus interface proxies for: `{}`",
+                "//! # D-Bus interface {kinds} for: `{}`",
                 first_iface.name()
             )?;
             for iface in following_ifaces {
@@ -91,13 +211,51 @@ fn write_doc_header<W: std::fmt::Write>(
          //!
          //! You may prefer to adapt it, instead of using it verbatim.
          //!
-         //! More information can be found in the [Writing a client proxy] section of the zbus
-         //! documentation.
-         //!
         ",
         cargo_bin_name, cargo_bin_version, input_src,
     )?;
 
+    if server {
+        write!(
+            w,
+            "//! The methods and properties are left to implement, in place of the `todo!()`s.
+             //!
+             //! More information can be found in the [Writing a server interface] section of the
+             //! zbus documentation.
+             //!
+            "
+        )?;
+
+        if !standard_interfaces.is_empty() {
+            write!(
+                w,
+                "//! The [D-Bus standard interfaces] (`org.freedesktop.DBus.*`) are implemented by the
+                 //! zbus `ObjectServer` on your behalf, consequently `{}` did not generate code for them.
+                 //!
+                ",
+                cargo_bin_name,
+            )?;
+        }
+
+        write!(
+            w,
+            "//! [Writing a server interface]: https://dbus2.github.io/zbus/server.html
+            //! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+            use zbus::interface;
+            "
+        )?;
+
+        return Ok(());
+    }
+
+    write!(
+        w,
+        "//! More information can be found in the [Writing a client proxy] section of the zbus
+         //! documentation.
+         //!
+        "
+    )?;
+
     if !standard_interfaces.is_empty() {
         write!(w,
             "//! This type implements the [D-Bus standard interfaces], (`org.freedesktop.DBus.*`) for which the
@@ -154,6 +312,14 @@ impl Display for GenTrait<'_> {
 
 impl GenTrait<'_> {
     fn write_interface<W: Write>(&self, w: &mut W) -> std::fmt::Result {
+        let structs = Structs::new([self.interface]);
+        structs.write_definitions(w)?;
+
+        self.write_trait(w, &structs)
+    }
+
+    /// Write the proxy trait, with the types of `structs` for its arguments.
+    fn write_trait<W: Write>(&self, w: &mut W, structs: &Structs) -> std::fmt::Result {
         let iface = self.interface;
         let idx = iface.name().rfind('.').unwrap() + 1;
         let name = &iface.name()[idx..];
@@ -174,21 +340,28 @@ impl GenTrait<'_> {
         let mut methods = iface.methods().to_vec();
         methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
         for m in &methods {
-            let (inputs, output) = inputs_output_from_args(m.args());
+            let types = structs.args(iface, Member::Method, m.name().as_str(), m.args().len());
+            let (inputs, output) = inputs_output_from_args(m.args(), &types);
             let name = to_identifier(&to_snakecase(m.name().as_str()));
             writeln!(w)?;
             writeln!(w, "    /// {} method", m.name())?;
             if pascal_case(&name) != m.name().as_str() {
                 writeln!(w, "    #[zbus(name = \"{}\")]", m.name())?;
             }
-            hide_clippy_lints(w, m)?;
+            hide_clippy_lints(w, m, &types)?;
             writeln!(w, "    fn {name}({inputs}){output};")?;
         }
 
         let mut signals = iface.signals().to_vec();
         signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
         for signal in &signals {
-            let args = parse_signal_args(signal.args());
+            let types = structs.args(
+                iface,
+                Member::Signal,
+                signal.name().as_str(),
+                signal.args().len(),
+            );
+            let args = parse_signal_args(signal.args(), &types);
             let name = to_identifier(&to_snakecase(signal.name().as_str()));
             writeln!(w)?;
             writeln!(w, "    /// {} signal", signal.name())?;
@@ -214,14 +387,14 @@ impl GenTrait<'_> {
             writeln!(w, "    /// {} property", p.name())?;
             if p.access().read() {
                 writeln!(w, "{}", fn_attribute)?;
-                let output = to_rust_type(p.ty(), false, false);
-                hide_clippy_type_complexity_lint(w, p.ty())?;
+                let output = to_rust_type(p.ty(), false, false, None);
+                hide_clippy_type_complexity_lint(w, p.ty(), None)?;
                 writeln!(w, "    fn {name}(&self) -> zbus::Result<{output}>;",)?;
             }
 
             if p.access().write() {
                 writeln!(w, "{}", fn_attribute)?;
-                let input = to_rust_type(p.ty(), true, true);
+                let input = to_rust_type(p.ty(), true, true, None);
                 writeln!(
                     w,
                     "    fn set_{name}(&self, value: {input}) -> zbus::Result<()>;",
@@ -232,7 +405,265 @@ impl GenTrait<'_> {
     }
 }
 
-fn hide_clippy_lints<W: Write>(write: &mut W, method: &zbus_xml::Method<'_>) -> std::fmt::Result {
+pub struct GenInterface<'i> {
+    pub interface: &'i Interface<'i>,
+    pub format: bool,
+}
+
+impl Display for GenInterface<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.format {
+            let mut unformatted = String::new();
+            self.write_interface(&mut unformatted)?;
+
+            let formatted = format_generated_code(&unformatted).unwrap_or(unformatted);
+
+            write!(f, "{}", formatted)
+        } else {
+            self.write_interface(f)
+        }
+    }
+}
+
+impl GenInterface<'_> {
+    fn write_interface<W: Write>(&self, w: &mut W) -> std::fmt::Result {
+        let structs = Structs::new([self.interface]);
+        structs.write_definitions(w)?;
+
+        self.write_skeleton(w, &structs)
+    }
+
+    /// Write the skeleton, with the types of `structs` for its arguments.
+    fn write_skeleton<W: Write>(&self, w: &mut W, structs: &Structs) -> std::fmt::Result {
+        let iface = self.interface;
+        let idx = iface.name().rfind('.').unwrap() + 1;
+        let name = &iface.name()[idx..];
+
+        writeln!(w, "pub struct {name};")?;
+        writeln!(w)?;
+        write!(w, "#[interface(name = \"{}\"", iface.name())?;
+        for a in iface.annotations() {
+            write!(w, ", {}", annotation_attribute(a))?;
+        }
+        writeln!(w, ")]")?;
+        // The arguments of the skeleton methods are unused, until they get implemented.
+        writeln!(w, "#[allow(unused_variables)]")?;
+        writeln!(w, "impl {name} {{")?;
+
+        let mut methods = iface.methods().to_vec();
+        methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        for m in &methods {
+            let types = structs.args(iface, Member::Method, m.name().as_str(), m.args().len());
+            let (inputs, output, out_names) = server_inputs_output_from_args(m.args(), &types);
+            let name = to_identifier(&to_snakecase(m.name().as_str()));
+            let mut attrs = vec![];
+            if pascal_case(&name) != m.name().as_str() {
+                attrs.push(format!("name = \"{}\"", m.name()));
+            }
+            if let Some(out_names) = out_names {
+                attrs.push(format!("out_args({})", out_names.join(", ")));
+            }
+            attrs.extend(m.annotations().iter().map(annotation_attribute));
+
+            writeln!(w)?;
+            writeln!(w, "    /// {} method", m.name())?;
+            write_zbus_attributes(w, &attrs)?;
+            hide_clippy_lints(w, m, &types)?;
+            writeln!(w, "    async fn {name}({inputs}){output} {{")?;
+            writeln!(w, "        todo!()")?;
+            writeln!(w, "    }}")?;
+        }
+
+        let mut signals = iface.signals().to_vec();
+        signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        for signal in &signals {
+            let types = structs.args(
+                iface,
+                Member::Signal,
+                signal.name().as_str(),
+                signal.args().len(),
+            );
+            let args = server_signal_args(signal.args(), &types);
+            let name = to_identifier(&to_snakecase(signal.name().as_str()));
+            let mut attrs = vec!["signal".to_string()];
+            if pascal_case(&name) != signal.name().as_str() {
+                attrs.push(format!("name = \"{}\"", signal.name()));
+            }
+            attrs.extend(signal.annotations().iter().map(annotation_attribute));
+
+            writeln!(w)?;
+            writeln!(w, "    /// {} signal", signal.name())?;
+            write_zbus_attributes(w, &attrs)?;
+            writeln!(w, "    async fn {name}({args}) -> zbus::Result<()>;")?;
+        }
+
+        let mut props = iface.properties().to_vec();
+        props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        for p in props {
+            let name = to_identifier(&to_snakecase(p.name().as_str()));
+            let name_attribute = if pascal_case(&name) != p.name().as_str() {
+                Some(format!("name = \"{}\"", p.name()))
+            } else {
+                None
+            };
+            let ty = to_rust_type(p.ty(), false, false, None);
+
+            // zbus requires a getter, even for write-only properties. It carries the annotations.
+            let mut property = "property".to_string();
+            let mut annotations = vec![];
+            for a in p.annotations() {
+                if a.name() == EMITS_CHANGED_SIGNAL {
+                    property = format!("property(emits_changed_signal = {:?})", a.value());
+                } else {
+                    annotations.push(annotation_attribute(a));
+                }
+            }
+            let attrs: Vec<_> = std::iter::once(property)
+                .chain(name_attribute.clone())
+                .chain(annotations)
+                .collect();
+
+            writeln!(w)?;
+            writeln!(w, "    /// {} property", p.name())?;
+            write_zbus_attributes(w, &attrs)?;
+            hide_clippy_type_complexity_lint(w, p.ty(), None)?;
+            writeln!(
+                w,
+                "    async fn {name}(&self) -> zbus::fdo::Result<{ty}> {{"
+            )?;
+            writeln!(w, "        todo!()")?;
+            writeln!(w, "    }}")?;
+
+            if p.access().write() {
+                let attrs: Vec<_> = std::iter::once("property".to_string())
+                    .chain(name_attribute)
+                    .collect();
+                writeln!(w)?;
+                write_zbus_attributes(w, &attrs)?;
+                hide_clippy_type_complexity_lint(w, p.ty(), None)?;
+                writeln!(
+                    w,
+                    "    async fn set_{name}(&mut self, value: {ty}) -> zbus::fdo::Result<()> {{",
+                )?;
+                writeln!(w, "        todo!()")?;
+                writeln!(w, "    }}")?;
+            }
+        }
+        writeln!(w, "}}")
+    }
+}
+
+const EMITS_CHANGED_SIGNAL: &str = "org.freedesktop.DBus.Property.EmitsChangedSignal";
+
+fn annotation_attribute(annotation: &Annotation) -> String {
+    format!(
+        "annotation(name = {:?}, value = {:?})",
+        annotation.name(),
+        annotation.value()
+    )
+}
+
+fn write_zbus_attributes<W: Write>(w: &mut W, attrs: &[String]) -> std::fmt::Result {
+    if !attrs.is_empty() {
+        writeln!(w, "    #[zbus({})]", attrs.join(", "))?;
+    }
+
+    Ok(())
+}
+
+fn arg_annotations(arg: &Arg) -> String {
+    arg.annotations()
+        .iter()
+        .map(|a| format!("#[zbus({})] ", annotation_attribute(a)))
+        .collect()
+}
+
+fn server_inputs_output_from_args(
+    args: &[Arg],
+    types: &[Option<&TypeDef>],
+) -> (String, String, Option<Vec<String>>) {
+    let mut inputs = vec!["&self".to_string()];
+    let mut outputs = vec![];
+    let mut n = 0;
+    let mut gen_name = || {
+        n += 1;
+        format!("arg_{n}")
+    };
+
+    for (a, named) in args.iter().zip(types) {
+        match a.direction() {
+            None | Some(ArgDirection::In) => {
+                let ty = to_rust_type(a.ty(), false, false, *named);
+                let arg = if let Some(name) = a.name() {
+                    to_identifier(name)
+                } else {
+                    gen_name()
+                };
+                inputs.push(format!("{}{arg}: {ty}", arg_annotations(a)));
+            }
+            Some(ArgDirection::Out) => outputs.push((a, *named)),
+        }
+    }
+
+    let output = match outputs.as_slice() {
+        [] => "()".to_string(),
+        // A single structure is nested in a tuple, not to be taken for multiple out arguments.
+        [(a, named)] if matches!(**a.ty(), Signature::Structure(_)) => {
+            format!("({},)", to_rust_type(a.ty(), false, false, *named))
+        }
+        [(a, named)] => to_rust_type(a.ty(), false, false, *named),
+        _ => {
+            let tys: Vec<_> = outputs
+                .iter()
+                .map(|(a, named)| to_rust_type(a.ty(), false, false, *named))
+                .collect();
+
+            format!("({})", tys.join(", "))
+        }
+    };
+    // Only multiple out arguments can be named, and only if they all have a name.
+    let out_names = if outputs.len() > 1 {
+        outputs
+            .iter()
+            .map(|(a, _)| a.name().map(|name| format!("{name:?}")))
+            .collect()
+    } else {
+        None
+    };
+
+    (
+        inputs.join(", "),
+        format!(" -> zbus::fdo::Result<{output}>"),
+        out_names,
+    )
+}
+
+fn server_signal_args(args: &[Arg], types: &[Option<&TypeDef>]) -> String {
+    let mut inputs = vec!["emitter: &zbus::object_server::SignalEmitter<'_>".to_string()];
+    let mut n = 0;
+    let mut gen_name = || {
+        n += 1;
+        format!("arg_{n}")
+    };
+
+    for (a, named) in args.iter().zip(types) {
+        let ty = to_rust_type(a.ty(), true, false, *named);
+        let arg = if let Some(name) = a.name() {
+            to_identifier(name)
+        } else {
+            gen_name()
+        };
+        inputs.push(format!("{}{arg}: {ty}", arg_annotations(a)));
+    }
+
+    inputs.join(", ")
+}
+
+fn hide_clippy_lints<W: Write>(
+    write: &mut W,
+    method: &zbus_xml::Method<'_>,
+    types: &[Option<&TypeDef>],
+) -> std::fmt::Result {
     // check for <https://rust-lang.github.io/rust-clippy/master/index.html#/too_many_arguments>
     // triggers when a functions has at least 7 paramters
     if method.args().len() >= 7 {
@@ -240,9 +671,9 @@ fn hide_clippy_lints<W: Write>(write: &mut W, method: &zbus_xml::Method<'_>) ->
     }
 
     // check for <https://rust-lang.github.io/rust-clippy/master/index.html#/type_complexity>
-    for arg in method.args() {
+    for (arg, named) in method.args().iter().zip(types) {
         let signature = arg.ty();
-        hide_clippy_type_complexity_lint(write, signature)?;
+        hide_clippy_type_complexity_lint(write, signature, *named)?;
     }
 
     Ok(())
@@ -251,15 +682,16 @@ fn hide_clippy_lints<W: Write>(write: &mut W, method: &zbus_xml::Method<'_>) ->
 fn hide_clippy_type_complexity_lint<W: Write>(
     write: &mut W,
     signature: &Signature,
+    named: Option<&TypeDef>,
 ) -> std::fmt::Result {
-    let complexity = estimate_type_complexity(signature);
+    let complexity = estimate_type_complexity(signature, named);
     if complexity >= 1700 {
         writeln!(write, "    #[allow(clippy::type_complexity)]")?;
     }
     Ok(())
 }
 
-fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
+fn inputs_output_from_args(args: &[Arg], types: &[Option<&TypeDef>]) -> (String, String) {
     let mut inputs = vec!["&self".to_string()];
     let mut output = vec![];
     let mut n = 0;
@@ -268,10 +700,10 @@ fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
         format!("arg_{n}")
     };
 
-    for a in args {
+    for (a, named) in args.iter().zip(types) {
         match a.direction() {
             None | Some(ArgDirection::In) => {
-                let ty = to_rust_type(a.ty(), true, true);
+                let ty = to_rust_type(a.ty(), true, true, *named);
                 let arg = if let Some(name) = a.name() {
                     to_identifier(name)
                 } else {
@@ -280,7 +712,7 @@ fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
                 inputs.push(format!("{arg}: {ty}"));
             }
             Some(ArgDirection::Out) => {
-                let ty = to_rust_type(a.ty(), false, false);
+                let ty = to_rust_type(a.ty(), false, false, *named);
                 output.push(ty);
             }
         }
@@ -295,7 +727,7 @@ fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
     (inputs.join(", "), format!(" -> zbus::Result<{output}>"))
 }
 
-fn parse_signal_args(args: &[Arg]) -> String {
+fn parse_signal_args(args: &[Arg], types: &[Option<&TypeDef>]) -> String {
     let mut inputs = vec!["&self".to_string()];
     let mut n = 0;
     let mut gen_name = || {
@@ -303,8 +735,8 @@ fn parse_signal_args(args: &[Arg]) -> String {
         format!("arg_{n}")
     };
 
-    for a in args {
-        let ty = to_rust_type(a.ty(), true, false);
+    for (a, named) in args.iter().zip(types) {
+        let ty = to_rust_type(a.ty(), true, false, *named);
         let arg = if let Some(name) = a.name() {
             to_identifier(name)
         } else {
@@ -316,9 +748,22 @@ fn parse_signal_args(args: &[Arg]) -> String {
     inputs.join(", ")
 }
 
-fn to_rust_type(ty: &Signature, input: bool, as_ref: bool) -> String {
+/// The Rust type for the `ty` signature.
+///
+/// The `named` type generated for an argument or property is used in place of the part of `ty` it
+/// stands for.
+fn to_rust_type(ty: &Signature, input: bool, as_ref: bool, named: Option<&TypeDef>) -> String {
     // can't haz recursive closure, yet
-    fn signature_to_rust_type(signature: &Signature, input: bool, as_ref: bool) -> String {
+    fn signature_to_rust_type(
+        signature: &Signature,
+        input: bool,
+        as_ref: bool,
+        named: Option<&TypeDef>,
+    ) -> String {
+        if let Some(named) = named.filter(|n| n.is_for(signature)) {
+            return format!("{}{}", if as_ref { "&" } else { "" }, named.name());
+        }
+
         match signature {
             Signature::Unit => "".into(),
             Signature::U8 => "u8".into(),
@@ -361,7 +806,7 @@ fn to_rust_type(ty: &Signature, input: bool, as_ref: bool) -> String {
             }
             Signature::Variant => "zbus::zvariant::OwnedValue".into(),
             Signature::Array(child) => {
-                let child_ty = signature_to_rust_type(child, input, as_ref);
+                let child_ty = signature_to_rust_type(child, input, as_ref, named);
                 if input && as_ref {
                     format!("&[{}]", child_ty)
                 } else {
@@ -369,15 +814,15 @@ fn to_rust_type(ty: &Signature, input: bool, as_ref: bool) -> String {
                 }
             }
             Signature::Dict { key, value } => {
-                let key_ty = signature_to_rust_type(key, input, as_ref);
-                let value_ty = signature_to_rust_type(value, input, as_ref);
+                let key_ty = signature_to_rust_type(key, input, as_ref, None);
+                let value_ty = signature_to_rust_type(value, input, as_ref, named);
 
                 format!("std::collections::HashMap<{}, {}>", key_ty, value_ty)
             }
             Signature::Structure(fields) => {
                 let fields = fields
                     .iter()
-                    .map(|f| signature_to_rust_type(f, input, as_ref))
+                    .map(|f| signature_to_rust_type(f, input, as_ref, None))
                     .collect::<Vec<_>>();
 
                 if fields.len() > 1 {
@@ -391,7 +836,7 @@ fn to_rust_type(ty: &Signature, input: bool, as_ref: bool) -> String {
         }
     }
 
-    signature_to_rust_type(ty, input, as_ref)
+    signature_to_rust_type(ty, input, as_ref, named)
 }
 
 static KWORDS: &[&str] = &[
@@ -427,7 +872,11 @@ pub fn pascal_case(s: &str) -> String {
     pascal
 }
 
-fn estimate_type_complexity(signature: &Signature) -> u32 {
+fn estimate_type_complexity(signature: &Signature, named: Option<&TypeDef>) -> u32 {
+    if named.is_some_and(|n| n.is_for(signature)) {
+        return 1;
+    }
+
     let mut score = 0;
 
     match signature {
@@ -445,16 +894,16 @@ fn estimate_type_complexity(signature: &Signature) -> u32 {
         #[cfg(unix)]
         Signature::Fd => score += 10,
         Signature::ObjectPath | Signature::Signature | Signature::Variant => score += 10,
-        Signature::Array(child) => score += 5 * estimate_type_complexity(child),
+        Signature::Array(child) => score += 5 * estimate_type_complexity(child, named),
         Signature::Dict { key, value } => {
             score *= 10 + 50;
-            score += 5 * estimate_type_complexity(key);
-            score += 5 * estimate_type_complexity(value);
+            score += 5 * estimate_type_complexity(key, None);
+            score += 5 * estimate_type_complexity(value, named);
         }
         Signature::Structure(fields) => {
             score += 50;
             for field in fields.iter() {
-                score += 5 * estimate_type_complexity(field);
+                score += 5 * estimate_type_complexity(field, None);
             }
         }
         #[allow(unreachable_patterns)]
@@ -468,6 +917,8 @@ fn format_generated_code(generated_code: &str) -> std::io::Result<String> {
     use std::io::{Read, Write};
 
     let mut process = Command::new("rustfmt")
+        // The server skeletons use `async fn`, which needs a 2018+ edition.
+        .args(["--edition", "2021"])
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         // rustfmt may post warnings about features not being enabled on stable rust
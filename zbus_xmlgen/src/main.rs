@@ -1,6 +1,7 @@
 #![deny(rust_2018_idioms)]
 
 use std::{
+    collections::HashSet,
     error::Error,
     fs::{File, OpenOptions},
     io::Write,
@@ -15,7 +16,7 @@ use zbus::{
 };
 use zbus_xml::{Interface, Node};
 
-use zbus_xmlgen::write_interfaces;
+use zbus_xmlgen::{write_interfaces, write_server_interfaces};
 
 mod cli;
 
@@ -28,15 +29,15 @@ enum OutputTarget {
 fn main() -> Result<(), Box<dyn Error>> {
     let args = cli::Args::parse();
 
-    let DBusInfo(node, service, path, input_src) = match args.command {
+    let DBusInfo(interfaces, service, input_src) = match args.command {
         cli::Command::System {
             service,
             object_path,
-        } => DBusInfo::new(Connection::system()?, service, object_path)?,
+        } => DBusInfo::new(Connection::system()?, service, object_path, args.recursive)?,
         cli::Command::Session {
             service,
             object_path,
-        } => DBusInfo::new(Connection::session()?, service, object_path)?,
+        } => DBusInfo::new(Connection::session()?, service, object_path, args.recursive)?,
         cli::Command::Address {
             address,
             service,
@@ -45,20 +46,30 @@ fn main() -> Result<(), Box<dyn Error>> {
             connection::Builder::address(&*address)?.build()?,
             service,
             object_path,
+            args.recursive,
         )?,
         cli::Command::File { path } => {
             let input_src = path.file_name().unwrap().to_string_lossy().to_string();
             let f = File::open(path)?;
-            DBusInfo(Node::from_reader(f)?, None, None, input_src)
+            let node = Node::from_reader(f)?;
+            let mut interfaces = FoundInterfaces::default();
+            // The paths of the child nodes can only be known if the root node has an absolute one.
+            let path = node
+                .name()
+                .filter(|_| args.recursive)
+                .and_then(|name| ObjectPath::try_from(name.to_string()).ok());
+            interfaces.add_node(&node, path.as_ref(), args.recursive);
+
+            DBusInfo(interfaces.into_interfaces(), None, input_src)
         }
     };
 
     let fdo_iface_prefix = "org.freedesktop.DBus";
-    let (fdo_standard_ifaces, needed_ifaces): (Vec<Interface<'_>>, Vec<Interface<'_>>) = node
-        .interfaces()
-        .iter()
-        .cloned()
-        .partition(|i| i.name().starts_with(fdo_iface_prefix));
+    let (fdo_standard_ifaces, needed_ifaces): (Vec<_>, Vec<_>) = interfaces
+        .into_iter()
+        .partition(|(i, _)| i.name().starts_with(fdo_iface_prefix));
+    let fdo_standard_ifaces: Vec<Interface<'_>> =
+        fdo_standard_ifaces.into_iter().map(|(i, _)| i).collect();
 
     if !fdo_standard_ifaces.is_empty() {
         eprintln!("Skipping `org.freedesktop.DBus` interfaces, please use https://docs.rs/zbus/latest/zbus/fdo/index.html")
@@ -77,25 +88,42 @@ fn main() -> Result<(), Box<dyn Error>> {
         _ => OutputTarget::MultipleFiles,
     };
 
-    for interface in needed_ifaces {
-        let output = write_interfaces(
-            &[interface.clone()],
-            &fdo_standard_ifaces,
-            service.clone(),
-            path.clone(),
-            &input_src,
-            env!("CARGO_BIN_NAME"),
-            env!("CARGO_PKG_VERSION"),
-        )?;
-
-        let interface_name = interface.name();
-        match output_target {
-            OutputTarget::Stdout => println!("{}", output),
-            OutputTarget::SingleFile(ref mut file) => {
-                file.write_all(output.as_bytes())?;
-                println!("Generated code for `{}`", interface_name);
+    // Output to a single file or stdout makes up a single module, where all the interfaces are
+    // generated together so the types of their arguments don't clash.
+    let generate = |interfaces: &[(Interface<'_>, Option<ObjectPath<'_>>)]| {
+        if args.server {
+            let interfaces: Vec<_> = interfaces.iter().map(|(i, _)| i.clone()).collect();
+            write_server_interfaces(
+                &interfaces,
+                &fdo_standard_ifaces,
+                &input_src,
+                env!("CARGO_BIN_NAME"),
+                env!("CARGO_PKG_VERSION"),
+            )
+        } else {
+            write_interfaces(
+                interfaces,
+                &fdo_standard_ifaces,
+                service.clone(),
+                &input_src,
+                env!("CARGO_BIN_NAME"),
+                env!("CARGO_PKG_VERSION"),
+            )
+        }
+    };
+
+    match output_target {
+        OutputTarget::Stdout => println!("{}", generate(&needed_ifaces)?),
+        OutputTarget::SingleFile(ref mut file) => {
+            file.write_all(generate(&needed_ifaces)?.as_bytes())?;
+            for (interface, _) in &needed_ifaces {
+                println!("Generated code for `{}`", interface.name());
             }
-            OutputTarget::MultipleFiles => {
+        }
+        OutputTarget::MultipleFiles => {
+            for interface in &needed_ifaces {
+                let output = generate(std::slice::from_ref(interface))?;
+                let interface_name = interface.0.name();
                 let filename = interface_name
                     .split('.')
                     .next_back()
@@ -104,16 +132,15 @@ fn main() -> Result<(), Box<dyn Error>> {
                 std::fs::write(format!("{}.rs", &filename), output)?;
                 println!("Generated code for `{}` in {}.rs", interface_name, filename);
             }
-        };
-    }
+        }
+    };
 
     Ok(())
 }
 
 struct DBusInfo<'a>(
-    Node<'a>,
+    Vec<(Interface<'a>, Option<ObjectPath<'a>>)>,
     Option<BusName<'a>>,
-    Option<ObjectPath<'a>>,
     String,
 );
 
@@ -122,6 +149,7 @@ impl DBusInfo<'_> {
         connection: Connection,
         service: String,
         object_path: String,
+        recursive: bool,
     ) -> Result<Self, Box<dyn Error>> {
         let service: BusName<'_> = service.try_into()?;
         let path: ObjectPath<'_> = object_path.try_into()?;
@@ -131,20 +159,259 @@ impl DBusInfo<'_> {
             path, service,
         );
 
-        let xml = IntrospectableProxy::builder(&connection)
-            .destination(service.clone())
-            .expect("invalid destination")
-            .path(path.clone())
-            .expect("invalid path")
-            .build()
-            .unwrap()
-            .introspect()?;
+        let node = introspect(&connection, &service, &path)?;
+        let interfaces = if recursive {
+            walk(node, path.into_owned(), |path| {
+                introspect(&connection, &service, path)
+            })
+        } else {
+            let mut interfaces = FoundInterfaces::default();
+            interfaces.add_node(&node, Some(&path), false);
+
+            interfaces
+        };
 
         Ok(DBusInfo(
-            Node::from_reader(xml.as_bytes())?,
+            interfaces.into_interfaces(),
             Some(service),
-            Some(path),
             input_src,
         ))
     }
 }
+
+fn introspect(
+    connection: &Connection,
+    service: &BusName<'_>,
+    path: &ObjectPath<'_>,
+) -> Result<Node<'static>, Box<dyn Error>> {
+    let xml = IntrospectableProxy::builder(connection)
+        .destination(service.clone())
+        .expect("invalid destination")
+        .path(path.clone())
+        .expect("invalid path")
+        .build()
+        .unwrap()
+        .introspect()?;
+
+    Ok(Node::from_reader(xml.as_bytes())?)
+}
+
+/// Walk the object tree from `node`, at `path`, introspecting each child node in turn.
+fn walk<F>(
+    node: Node<'static>,
+    path: ObjectPath<'static>,
+    mut introspect: F,
+) -> FoundInterfaces<'static>
+where
+    F: FnMut(&ObjectPath<'_>) -> Result<Node<'static>, Box<dyn Error>>,
+{
+    let mut interfaces = FoundInterfaces::default();
+    // Children with absolute names can point anywhere, including back at an ancestor.
+    let mut visited = HashSet::from([path.clone()]);
+    let mut nodes = vec![(node, path)];
+    while let Some((node, path)) = nodes.pop() {
+        interfaces.add_node(&node, Some(&path), false);
+
+        for child in node.nodes() {
+            let Some(child_path) = child_path(&path, child) else {
+                continue;
+            };
+            if !visited.insert(child_path.clone()) {
+                continue;
+            }
+            match introspect(&child_path) {
+                Ok(child) => nodes.push((child, child_path)),
+                Err(e) => eprintln!("Skipping `{child_path}`, failed to introspect it: {e}"),
+            }
+        }
+    }
+
+    interfaces
+}
+
+/// The path of the `child` node of the object at `path`.
+fn child_path(path: &ObjectPath<'_>, child: &Node<'_>) -> Option<ObjectPath<'static>> {
+    let name = child.name()?;
+    let child_path = if name.starts_with('/') {
+        name.to_string()
+    } else if path.as_str() == "/" {
+        format!("/{name}")
+    } else {
+        format!("{path}/{name}")
+    };
+
+    ObjectPath::try_from(child_path).ok()
+}
+
+/// The interfaces found in an object tree, without duplicates, and the paths implementing them.
+#[derive(Default)]
+struct FoundInterfaces<'a>(Vec<(Interface<'a>, Vec<ObjectPath<'a>>)>);
+
+impl<'a> FoundInterfaces<'a> {
+    /// Add the interfaces of `node`, at `path`, and of its child nodes, if `recursive`.
+    fn add_node(&mut self, node: &Node<'a>, path: Option<&ObjectPath<'a>>, recursive: bool) {
+        for interface in node.interfaces() {
+            let found = match self
+                .0
+                .iter_mut()
+                .find(|(i, _)| i.name() == interface.name())
+            {
+                Some((_, paths)) => paths,
+                None => {
+                    self.0.push((interface.clone(), vec![]));
+                    &mut self.0.last_mut().unwrap().1
+                }
+            };
+            if let Some(path) = path {
+                if !found.contains(path) {
+                    found.push(path.clone());
+                }
+            }
+        }
+
+        if recursive {
+            for child in node.nodes() {
+                let child_path = path.and_then(|path| child_path(path, child));
+                self.add_node(child, child_path.as_ref(), true);
+            }
+        }
+    }
+
+    /// The interfaces found, with their path if they were only found at one.
+    fn into_interfaces(self) -> Vec<(Interface<'a>, Option<ObjectPath<'a>>)> {
+        self.0
+            .into_iter()
+            .map(|(interface, mut paths)| {
+                let path = if paths.len() == 1 { paths.pop() } else { None };
+
+                (interface, path)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zbus::zvariant::ObjectPath;
+    use zbus_xml::Node;
+
+    use super::{child_path, walk, FoundInterfaces};
+
+    fn node(xml: &str) -> Node<'static> {
+        Node::from_reader(xml.as_bytes()).unwrap()
+    }
+
+    fn path(path: &str) -> ObjectPath<'static> {
+        ObjectPath::try_from(path.to_string()).unwrap()
+    }
+
+    // The names of the interfaces found, with their path if they were only found at one.
+    fn found(interfaces: FoundInterfaces<'_>) -> Vec<(String, Option<String>)> {
+        interfaces
+            .into_interfaces()
+            .into_iter()
+            .map(|(i, path)| (i.name().to_string(), path.map(|p| p.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn child_paths() {
+        let child = |name| node(&format!(r#"<node name="{name}"/>"#));
+
+        assert_eq!(child_path(&path("/"), &child("foo")), Some(path("/foo")));
+        assert_eq!(
+            child_path(&path("/org/foo"), &child("bar")),
+            Some(path("/org/foo/bar"))
+        );
+        assert_eq!(
+            child_path(&path("/org/foo"), &child("/org/bar")),
+            Some(path("/org/bar"))
+        );
+        assert_eq!(child_path(&path("/org/foo"), &child("b-a-r")), None);
+        assert_eq!(child_path(&path("/org/foo"), &node("<node/>")), None);
+    }
+
+    #[test]
+    fn add_nodes() {
+        let root = node(
+            r#"
+            <node name="/org/zbus">
+              <interface name="org.zbus.Root"/>
+              <node name="a">
+                <interface name="org.zbus.Child"/>
+                <interface name="org.zbus.Leaf"/>
+              </node>
+              <node name="/org/zbus/b">
+                <interface name="org.zbus.Child"/>
+              </node>
+              <node name="/org/zbus">
+                <interface name="org.zbus.Root"/>
+              </node>
+            </node>
+            "#,
+        );
+
+        let mut interfaces = FoundInterfaces::default();
+        interfaces.add_node(&root, Some(&path("/org/zbus")), true);
+        assert_eq!(
+            found(interfaces),
+            [
+                ("org.zbus.Root".into(), Some("/org/zbus".into())),
+                ("org.zbus.Child".into(), None),
+                ("org.zbus.Leaf".into(), Some("/org/zbus/a".into())),
+            ]
+        );
+
+        // The paths are unknown without the one of the root node.
+        let mut interfaces = FoundInterfaces::default();
+        interfaces.add_node(&root, None, true);
+        assert_eq!(
+            found(interfaces),
+            [
+                ("org.zbus.Root".into(), None),
+                ("org.zbus.Child".into(), None),
+                ("org.zbus.Leaf".into(), None),
+            ]
+        );
+
+        // Only the interfaces of the root node, unless recursive.
+        let mut interfaces = FoundInterfaces::default();
+        interfaces.add_node(&root, Some(&path("/org/zbus")), false);
+        assert_eq!(
+            found(interfaces),
+            [("org.zbus.Root".into(), Some("/org/zbus".into()))]
+        );
+    }
+
+    #[test]
+    fn walk_cycles() {
+        // A child pointing back at its parent, by its absolute path.
+        let objects = HashMap::from([
+            (
+                "/org/zbus",
+                r#"<node><interface name="org.zbus.Root"/><node name="a"/></node>"#,
+            ),
+            (
+                "/org/zbus/a",
+                r#"<node><interface name="org.zbus.Child"/><node name="/org/zbus"/></node>"#,
+            ),
+        ]);
+        let mut introspected = vec![];
+        let interfaces = walk(node(objects["/org/zbus"]), path("/org/zbus"), |path| {
+            introspected.push(path.to_string());
+
+            Ok(node(objects[path.as_str()]))
+        });
+
+        assert_eq!(introspected, ["/org/zbus/a"]);
+        assert_eq!(
+            found(interfaces),
+            [
+                ("org.zbus.Root".into(), Some("/org/zbus".into())),
+                ("org.zbus.Child".into(), Some("/org/zbus/a".into())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use snakecase::ascii::to_snakecase;
+use zbus_xml::{Annotation, Arg, ArgDirection, Interface};
+use zvariant::Signature;
+
+use crate::{pascal_case, to_identifier, to_rust_type};
+
+/// The annotation giving the name of the type to generate for an argument.
+const TYPE_NAME_ANNOTATION: &str = "org.zbus.xmlgen.TypeName";
+/// The annotation listing the entries of an `a{sv}` argument, as space-separated `key:signature`
+/// pairs, to generate a struct for it.
+const DICT_FIELDS_ANNOTATION: &str = "org.zbus.xmlgen.DictFields";
+/// The annotation QtDBus uses to name the type of an argument. On methods and signals, it's
+/// suffixed with the direction and index of the argument, e.g `.Out0`.
+const QT_TYPE_NAME_ANNOTATION: &str = "org.qtproject.QtDBus.QtTypeName";
+/// The names of the types and traits of the Rust prelude, which generated types must not shadow.
+const PRELUDE_NAMES: &[&str] = &[
+    "AsMut",
+    "AsRef",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "DoubleEndedIterator",
+    "Drop",
+    "Eq",
+    "ExactSizeIterator",
+    "Extend",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "FromIterator",
+    "Into",
+    "IntoIterator",
+    "Iterator",
+    "Option",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Result",
+    "Self",
+    "Send",
+    "Sized",
+    "String",
+    "Sync",
+    "ToOwned",
+    "ToString",
+    "TryFrom",
+    "TryInto",
+    "Unpin",
+    "Vec",
+];
+
+/// The kind of interface member.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Member {
+    Method,
+    Signal,
+}
+
+/// The types generated for the arguments of an interface.
+///
+/// Structures with more than one field get a named struct, instead of an anonymous tuple, and so do
+/// `a{sv}` dictionaries whose entries are hinted through the `org.zbus.xmlgen.DictFields`
+/// annotation. Arrays and dictionary values of such types are covered as well.
+///
+/// Properties keep anonymous types, since their types must convert from and into
+/// `zvariant::Value`, which can't be derived for tuple structs.
+///
+/// The types are named uniquely across all the interfaces generated in the same module, and never
+/// after the prelude or the items the `proxy` and `interface` macros generate for the interfaces.
+#[derive(Debug, Default)]
+pub(crate) struct Structs {
+    defs: Vec<TypeDef>,
+    // The index of the type of each argument in `defs`.
+    types: HashMap<(String, Member, String, usize), usize>,
+    // The names types can't be given.
+    reserved: HashSet<String>,
+}
+
+impl Structs {
+    /// The types for the arguments of the `interfaces`, generated in the same module.
+    pub(crate) fn new<'i>(interfaces: impl IntoIterator<Item = &'i Interface<'i>>) -> Self {
+        let interfaces: Vec<_> = interfaces.into_iter().collect();
+        let mut structs = Self {
+            reserved: PRELUDE_NAMES.iter().map(|n| n.to_string()).collect(),
+            ..Self::default()
+        };
+        structs
+            .reserved
+            .extend(interfaces.iter().flat_map(|i| macro_item_names(i)));
+
+        for iface in interfaces {
+            let iface_name = iface.name().to_string();
+
+            let mut methods = iface.methods().to_vec();
+            methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+            for m in &methods {
+                let key = (iface_name.clone(), Member::Method, m.name().to_string());
+                structs.add_args(key, m.args(), m.annotations());
+            }
+
+            let mut signals = iface.signals().to_vec();
+            signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+            for s in &signals {
+                let key = (iface_name.clone(), Member::Signal, s.name().to_string());
+                structs.add_args(key, s.args(), s.annotations());
+            }
+        }
+
+        structs
+    }
+
+    /// The types generated for the `n` arguments of the `member` of `iface` named `name`.
+    pub(crate) fn args(
+        &self,
+        iface: &Interface<'_>,
+        member: Member,
+        name: &str,
+        n: usize,
+    ) -> Vec<Option<&TypeDef>> {
+        (0..n)
+            .map(|i| {
+                self.types
+                    .get(&(iface.name().to_string(), member, name.to_string(), i))
+            })
+            .map(|i| i.map(|i| &self.defs[*i]))
+            .collect()
+    }
+
+    /// Write the definitions of all the generated types.
+    pub(crate) fn write_definitions<W: Write>(&self, w: &mut W) -> std::fmt::Result {
+        for def in &self.defs {
+            def.write_definition(w)?;
+            writeln!(w)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_args(
+        &mut self,
+        (iface_name, member, member_name): (String, Member, String),
+        args: &[Arg],
+        annotations: &[Annotation],
+    ) {
+        let (mut n_in, mut n_out) = (0, 0);
+        for (i, a) in args.iter().enumerate() {
+            // QtDBus numbers the arguments of each direction separately. Signal arguments have
+            // none, and are numbered as out arguments.
+            let qt_suffix = match (member, a.direction()) {
+                (Member::Method, None | Some(ArgDirection::In)) => {
+                    n_in += 1;
+                    format!("In{}", n_in - 1)
+                }
+                _ => {
+                    n_out += 1;
+                    format!("Out{}", n_out - 1)
+                }
+            };
+            let hint = type_name_hint(a.annotations(), None)
+                .or_else(|| type_name_hint(annotations, Some(&qt_suffix)));
+            let candidate = hint.unwrap_or_else(|| match a.name() {
+                Some(name) => type_name(name),
+                None => format!("{}Arg{i}", type_name(&member_name)),
+            });
+
+            let key = (iface_name.clone(), member, member_name.clone(), i);
+            self.add(key, a, candidate);
+        }
+    }
+
+    fn add(&mut self, key: (String, Member, String, usize), arg: &Arg, candidate: String) {
+        let Some(kind) = TypeKind::new(arg.ty(), arg.annotations()) else {
+            return;
+        };
+        let member_name = &key.2;
+
+        // The same name may be used for different types, by different members. The name of the
+        // member then tells them apart.
+        let prefixed = format!("{}{candidate}", type_name(member_name));
+        let mut names = [candidate, prefixed.clone()]
+            .into_iter()
+            .chain((2..).map(|n| format!("{prefixed}{n}")));
+        let index = loop {
+            let name = names.next().unwrap();
+            if self.reserved.contains(&name) {
+                continue;
+            }
+            match self.defs.iter().position(|d| d.name == name) {
+                Some(i) if self.defs[i].kind == kind => break i,
+                Some(_) => continue,
+                None => {
+                    self.defs.push(TypeDef { name, kind });
+                    break self.defs.len() - 1;
+                }
+            }
+        };
+
+        self.types.insert(key, index);
+    }
+}
+
+/// A type generated for arguments.
+#[derive(Debug)]
+pub(crate) struct TypeDef {
+    name: String,
+    kind: TypeKind,
+}
+
+impl TypeDef {
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// If `signature` is the one this type stands for.
+    ///
+    /// The type doesn't stand for the whole signature of the arguments of arrays and dictionaries,
+    /// but for their elements and values.
+    pub(crate) fn is_for(&self, signature: &Signature) -> bool {
+        match &self.kind {
+            TypeKind::Struct(fields) => {
+                matches!(signature, Signature::Structure(f) if f.iter().eq(fields.iter()))
+            }
+            TypeKind::Dict(_) => is_string_variant_dict(signature),
+        }
+    }
+
+    fn write_definition<W: Write>(&self, w: &mut W) -> std::fmt::Result {
+        let name = &self.name;
+        match &self.kind {
+            TypeKind::Struct(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|f| format!("pub {}", to_rust_type(f, false, false, None)))
+                    .collect();
+
+                writeln!(
+                    w,
+                    "#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]"
+                )?;
+                writeln!(w, "pub struct {name}({});", fields.join(", "))
+            }
+            TypeKind::Dict(entries) => {
+                writeln!(
+                    w,
+                    "#[derive(Debug, Default, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]"
+                )?;
+                writeln!(w, "#[zvariant(signature = \"dict\")]")?;
+                writeln!(w, "pub struct {name} {{")?;
+                for (key, ty) in entries {
+                    let field = to_identifier(&to_snakecase(key));
+                    let ty = to_rust_type(ty, false, false, None);
+                    writeln!(
+                        w,
+                        "    #[serde(\
+                            rename = {key:?}, \
+                            with = \"zbus::zvariant::as_value::optional\", \
+                            skip_serializing_if = \"Option::is_none\", \
+                            default\
+                        )]"
+                    )?;
+                    writeln!(w, "    pub {field}: Option<{ty}>,")?;
+                }
+                writeln!(w, "}}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum TypeKind {
+    /// A struct, with the signatures of its fields.
+    Struct(Vec<Signature>),
+    /// An `a{sv}` dictionary, with the keys and signatures of its known entries.
+    Dict(Vec<(String, Signature)>),
+}
+
+impl TypeKind {
+    /// The kind of type to generate for the `ty` signature, if any.
+    fn new(ty: &Signature, annotations: &[Annotation]) -> Option<Self> {
+        let dict_fields = annotations
+            .iter()
+            .find(|a| a.name() == DICT_FIELDS_ANNOTATION)
+            .map(|a| parse_dict_fields(a.value()));
+        match ty {
+            Signature::Structure(fields) if fields.iter().count() > 1 => {
+                Some(Self::Struct(fields.iter().cloned().collect()))
+            }
+            Signature::Dict { .. } if is_string_variant_dict(ty) => dict_fields.map(Self::Dict),
+            Signature::Array(child) => Self::new(child, annotations),
+            Signature::Dict { value, .. } => Self::new(value, annotations),
+            _ => None,
+        }
+    }
+}
+
+fn is_string_variant_dict(signature: &Signature) -> bool {
+    matches!(
+        signature,
+        Signature::Dict { key, value }
+            if matches!(**key, Signature::Str) && matches!(**value, Signature::Variant)
+    )
+}
+
+fn parse_dict_fields(value: &str) -> Vec<(String, Signature)> {
+    value
+        .split_whitespace()
+        .filter_map(|entry| {
+            let (key, ty) = entry.split_once(':')?;
+            let ty = Signature::from_str(ty).ok()?;
+
+            Some((key.to_string(), ty))
+        })
+        .collect()
+}
+
+/// The type name hinted by the `annotations`, if any.
+///
+/// The QtDBus annotation is looked up with the `qt_suffix` suffix, if any.
+fn type_name_hint(annotations: &[Annotation], qt_suffix: Option<&str>) -> Option<String> {
+    let find = |name: &str| annotations.iter().find(|a| a.name() == name);
+
+    match qt_suffix {
+        Some(suffix) => qt_type_name(find(&format!("{QT_TYPE_NAME_ANNOTATION}.{suffix}"))?.value()),
+        None => match find(TYPE_NAME_ANNOTATION) {
+            Some(a) => is_identifier(a.value()).then(|| a.value().to_string()),
+            None => qt_type_name(find(QT_TYPE_NAME_ANNOTATION)?.value()),
+        },
+    }
+}
+
+/// The names of the items the `proxy` and `interface` macros generate for `iface`, besides methods.
+fn macro_item_names(iface: &Interface<'_>) -> Vec<String> {
+    let idx = iface.name().rfind('.').unwrap() + 1;
+    let name = &iface.name()[idx..];
+    let mut names = vec![
+        name.to_string(),
+        format!("{name}Proxy"),
+        format!("{name}ProxyBlocking"),
+        format!("{name}Signals"),
+    ];
+    for s in iface.signals() {
+        let s = s.name();
+        names.extend([
+            s.to_string(),
+            format!("{s}Args"),
+            format!("{s}Stream"),
+            format!("{s}Iterator"),
+        ]);
+    }
+
+    names
+}
+
+/// The name of a type named after the `name` of an argument or member.
+fn type_name(name: &str) -> String {
+    pascal_case(&to_identifier(&to_snakecase(name)))
+}
+
+/// The name of the (element) type in a QtDBus type name, e.g `Foo` in `QList<ns::Foo>`.
+///
+/// Qt's own types, e.g `QVariantMap`, aren't considered.
+fn qt_type_name(value: &str) -> Option<String> {
+    let mut name = value.trim();
+    while let Some((_, inner)) = name.strip_suffix('>').and_then(|n| n.split_once('<')) {
+        // The values of maps, e.g `QMap<QString, Foo>`.
+        name = inner.rsplit(',').next()?.trim();
+    }
+    let name = name.rsplit("::").next()?;
+    let mut chars = name.chars();
+    let is_qt_type = chars.next() == Some('Q') && chars.next().is_some_and(|c| c.is_uppercase());
+
+    (is_identifier(name) && !is_qt_type).then(|| name.to_string())
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
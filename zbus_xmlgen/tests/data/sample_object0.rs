@@ -1,26 +1,41 @@
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct Rule(
+    pub Vec<i32>,
+    pub i32,
+    pub std::collections::HashMap<String, String>,
+    pub i32,
+    pub Vec<i32>,
+    pub i32,
+    pub Vec<String>,
+    pub i32,
+    pub bool,
+);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct BarplexSigArg1(pub String, pub zbus::zvariant::OwnedObjectPath);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct Bar(pub i32, pub i32);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct Baz(pub i32, pub i32);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct BazifyBar(pub i32, pub i32, pub u32);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct MogrifyMeBar(pub i32, pub i32, pub Vec<zbus::zvariant::OwnedValue>);
+
 #[proxy(interface = "com.example.SampleInterface0", assume_defaults = true)]
 pub trait SampleInterface0 {
     /// BarplexSig method
-    fn barplex_sig(
-        &self,
-        rule: &(
-            &[i32],
-            i32,
-            std::collections::HashMap<&str, &str>,
-            i32,
-            &[i32],
-            i32,
-            &[&str],
-            i32,
-            bool,
-        ),
-    ) -> zbus::Result<Vec<(String, zbus::zvariant::OwnedObjectPath)>>;
+    fn barplex_sig(&self, rule: &Rule) -> zbus::Result<Vec<BarplexSigArg1>>;
 
     /// Bazic method
-    fn bazic(&self, bar: &(i32, i32), foo: &(i32,)) -> zbus::Result<((i32, i32), Vec<(i32,)>)>;
+    fn bazic(&self, bar: &Bar, foo: &(i32,)) -> zbus::Result<(Baz, Vec<(i32,)>)>;
 
     /// Bazify method
-    fn bazify(&self, bar: &(i32, i32, u32)) -> zbus::Result<zbus::zvariant::OwnedValue>;
+    fn bazify(&self, bar: &BazifyBar) -> zbus::Result<zbus::zvariant::OwnedValue>;
 
     /// Frobate method
     fn frobate(
@@ -30,7 +45,7 @@ pub trait SampleInterface0 {
     ) -> zbus::Result<(String, std::collections::HashMap<u32, String>)>;
 
     /// MogrifyMe method
-    fn mogrify_me(&self, bar: &(i32, i32, &[&zbus::zvariant::Value<'_>])) -> zbus::Result<()>;
+    fn mogrify_me(&self, bar: &MogrifyMeBar) -> zbus::Result<()>;
 
     /// Odyssey method
     #[allow(clippy::too_many_arguments)]
@@ -0,0 +1,65 @@
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+#[zvariant(signature = "dict")]
+pub struct ListOptions {
+    #[serde(
+        rename = "include-hidden",
+        with = "zbus::zvariant::as_value::optional",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub include_hidden: Option<bool>,
+    #[serde(
+        rename = "max-count",
+        with = "zbus::zvariant::as_value::optional",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub max_count: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct Device(pub String, pub zbus::zvariant::OwnedObjectPath, pub u32);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct Point(pub i32, pub i32);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct MoveArg2(pub i32, pub i32);
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+#[zvariant(signature = "dict")]
+pub struct Details {
+    #[serde(
+        rename = "Name",
+        with = "zbus::zvariant::as_value::optional",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub name: Option<String>,
+    #[serde(
+        rename = "Version",
+        with = "zbus::zvariant::as_value::optional",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub version: Option<(u32, u32)>,
+}
+
+#[proxy(interface = "com.example.SampleInterface1", assume_defaults = true)]
+pub trait SampleInterface1 {
+    /// ListDevices method
+    fn list_devices(&self, options: &ListOptions) -> zbus::Result<Vec<Device>>;
+
+    /// Move method
+    fn move_(&self, from: &Point, to: &Point) -> zbus::Result<MoveArg2>;
+
+    /// DeviceAdded signal
+    #[zbus(signal)]
+    fn device_added(&self, device: Device, details: Details) -> zbus::Result<()>;
+
+    /// Settings property
+    #[zbus(property)]
+    fn settings(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+}
@@ -0,0 +1,59 @@
+use zbus::proxy;
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct Info(pub String, pub u64);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct StatusInfo(pub String, pub u32, pub bool);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct ConfigureOption(pub String, pub zbus::zvariant::OwnedValue);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct DescribeDevice(pub String, pub String);
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, zbus::zvariant::Type)]
+pub struct ChangedChanged(pub String, pub zbus::zvariant::OwnedObjectPath);
+
+#[proxy(interface = "com.example.Files", assume_defaults = true)]
+pub trait Files {
+
+    /// Open method
+    fn open(&self, path: &str) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+
+    /// Stat method
+    fn stat(&self, path: &str) -> zbus::Result<Info>;
+
+    /// Opened signal
+    #[zbus(signal)]
+    fn opened(&self, file: zbus::zvariant::ObjectPath<'_>) -> zbus::Result<()>;
+
+    /// Count property
+    #[zbus(property)]
+    fn count(&self) -> zbus::Result<u32>;
+}
+#[proxy(interface = "com.example.Printer", assume_defaults = true)]
+pub trait Printer {
+
+    /// Print method
+    fn print(&self, file: &zbus::zvariant::ObjectPath<'_>, copies: u32) -> zbus::Result<()>;
+
+    /// Status method
+    fn status(&self) -> zbus::Result<StatusInfo>;
+
+    /// Busy property
+    #[zbus(property)]
+    fn busy(&self) -> zbus::Result<bool>;
+}
+#[proxy(interface = "com.example.Device", assume_defaults = true)]
+pub trait Device {
+
+    /// Configure method
+    fn configure(&self, option: &ConfigureOption) -> zbus::Result<()>;
+
+    /// Describe method
+    fn describe(&self) -> zbus::Result<DescribeDevice>;
+
+    /// Changed signal
+    #[zbus(signal)]
+    fn changed(&self, changed: ChangedChanged) -> zbus::Result<()>;
+}
@@ -2,22 +2,25 @@ use pretty_assertions::assert_eq;
 use std::{env, error::Error, io::Write, path::Path};
 
 use zbus_xml::Node;
-use zbus_xmlgen::GenTrait;
+use zbus_xmlgen::{generate_proxies, generate_proxies_file, GenInterface, GenTrait};
 
 macro_rules! gen_diff {
-    ($infile:literal, $outfile:literal) => {{
+    ($infile:literal, $outfile:literal) => {
+        gen_diff!($infile, $outfile, |interface| GenTrait {
+            interface,
+            path: None,
+            service: None,
+            format: true,
+        }
+        .to_string())
+    };
+    ($infile:literal, $outfile:literal, $gen:expr) => {{
         let input = include_str!(concat!("data/", $infile));
         let expected = include_str!(concat!("data/", $outfile));
         #[cfg(windows)]
         let expected = expected.replace("\r\n", "\n");
         let node = Node::from_reader(input.as_bytes())?;
-        let gen = GenTrait {
-            interface: &node.interfaces()[0],
-            path: None,
-            service: None,
-            format: true,
-        }
-        .to_string();
+        let gen = ($gen)(&node.interfaces()[0]);
 
         if env::var("TEST_OVERWRITE").is_ok() {
             let path = Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -42,3 +45,90 @@ macro_rules! gen_diff {
 fn sample_object0() -> Result<(), Box<dyn Error>> {
     gen_diff!("sample_object0.xml", "sample_object0.rs")
 }
+
+#[test]
+fn sample_object1() -> Result<(), Box<dyn Error>> {
+    gen_diff!("sample_object1.xml", "sample_object1.rs")
+}
+
+#[test]
+fn sample_object0_server() -> Result<(), Box<dyn Error>> {
+    gen_diff!(
+        "sample_object0.xml",
+        "sample_object0_server.rs",
+        |interface| {
+            GenInterface {
+                interface,
+                format: true,
+            }
+            .to_string()
+        }
+    )
+}
+
+#[test]
+fn sample_object0_build() -> Result<(), Box<dyn Error>> {
+    let xml_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join("sample_object0.xml");
+    let out_path = env::temp_dir().join(format!("zbus_xmlgen_{}.rs", std::process::id()));
+    generate_proxies_file(&xml_path, &out_path)?;
+    let generated = std::fs::read_to_string(&out_path)?;
+    std::fs::remove_file(&out_path)?;
+
+    assert!(generated.starts_with("use zbus::proxy;\n"));
+    assert!(generated.contains("pub trait SampleInterface0 {"));
+
+    Ok(())
+}
+
+#[test]
+fn sample_objects() -> Result<(), Box<dyn Error>> {
+    let input = include_str!("data/sample_objects.xml");
+    let expected = include_str!("data/sample_objects.rs");
+    #[cfg(windows)]
+    let expected = expected.replace("\r\n", "\n");
+    let node = Node::from_reader(input.as_bytes())?;
+    let gen = generate_proxies(&node);
+
+    if env::var("TEST_OVERWRITE").is_ok() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("data")
+            .join("sample_objects.rs");
+        std::fs::write(path, gen)?;
+        return Ok(());
+    }
+
+    assert_eq!(gen, expected);
+    Ok(())
+}
+
+// Ensure the generated code is accepted by the `proxy` and `interface` macros.
+#[allow(dead_code, clippy::disallowed_names)]
+mod sample_object0 {
+    use zbus::proxy;
+
+    include!("data/sample_object0.rs");
+}
+
+#[allow(dead_code)]
+mod sample_object1 {
+    use zbus::proxy;
+
+    include!("data/sample_object1.rs");
+}
+
+// The proxies of several interfaces, as generated for a build script.
+#[allow(dead_code)]
+mod sample_objects {
+    include!("data/sample_objects.rs");
+}
+
+#[allow(dead_code, clippy::disallowed_names, clippy::type_complexity)]
+mod sample_object0_server {
+    use zbus::interface;
+
+    include!("data/sample_object0_server.rs");
+}
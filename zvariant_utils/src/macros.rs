@@ -216,6 +216,31 @@ pub fn iter_meta_lists(
 /// The syntax for inner attributes is the same as for the outer attributes, but you can specify
 /// only one inner attribute per outer attribute.
 ///
+/// # Repeated nested attribute lists
+///
+/// Wrapping the inner attributes in brackets allows the nested list to be specified any number of
+/// times, each occurrence being collected into a `Vec`:
+///
+/// ```
+/// # use zvariant_utils::def_attrs;
+/// def_attrs! {
+///     crate zbus;
+///
+///     pub OuterAttributes("outer") {
+///         annotation [{
+///             /// An example of repeated nested attributes.
+///             pub AnnotationAttributes("annotation") {
+///                 name str,
+///                 value str
+///             }
+///         }]
+///     };
+/// }
+/// ```
+///
+/// With this, `#[zbus(annotation(name = "a", value = "1"), annotation(name = "b", value = "2"))]`
+/// results in an `annotation` field of type `Vec<AnnotationAttributes>` with two elements.
+///
 /// # Using attribute names for attribute lists
 ///
 /// It is possible to use multiple different "crate" names as follows:
@@ -264,6 +289,12 @@ macro_rules! def_attrs {
             $($attr_name:ident $kind:tt),+
         }
     }) => {::std::option::Option<$name>};
+    (@attr_ty [{
+        $(#[$m:meta])*
+        $vis:vis $name:ident($what:literal) {
+            $($attr_name:ident $kind:tt),+
+        }
+    }]) => {::std::vec::Vec<$name>};
     (@match_attr_with $attr_name:ident, $meta:ident, $self:ident, $matched:expr) => {
         if let ::std::option::Option::Some(value) = $matched? {
             if $self.$attr_name.is_some() {
@@ -366,10 +397,49 @@ macro_rules! def_attrs {
                 };
         }
     };
+    (@match_attr [{
+        $(#[$m:meta])*
+        $vis:vis $name:ident($what:literal) $body:tt
+    }] $attr_name:ident, $meta:expr, $self:ident) => {
+        if $meta.path().is_ident(::std::stringify!($attr_name)) {
+            return match $meta {
+                ::syn::Meta::List(meta) => {
+                    $self.$attr_name.push($name::parse_nested_metas(
+                        meta.parse_args_with(::syn::punctuated::Punctuated::<::syn::Meta, ::syn::Token![,]>::parse_terminated)?
+                    )?);
+                    ::std::result::Result::Ok(())
+                }
+                _ => Err(::syn::Error::new(
+                    $meta.span(),
+                    ::std::format!(::std::concat!(
+                        "attribute `", ::std::stringify!($attr_name),
+                        "` must be a list"
+                    )),
+                ))
+            };
+        }
+    };
     (@def_ty str) => {};
     (@def_ty bool) => {};
     (@def_ty [str]) => {};
     (@def_ty none) => {};
+    (
+        @def_ty [{
+            $(#[$m:meta])*
+            $vis:vis $name:ident($what:literal) {
+                $($attr_name:ident $kind:tt),+
+            }
+        }]
+    ) => {
+        $crate::def_attrs!(
+            @def_ty {
+                $(#[$m])*
+                $vis $name($what) {
+                    $($attr_name $kind),+
+                }
+            }
+        );
+    };
     (
         @def_ty {
             $(#[$m:meta])*